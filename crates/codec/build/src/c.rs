@@ -0,0 +1,675 @@
+// Echidna - Codec - Build
+
+use crate::*;
+
+// names that can't be used as C identifiers
+const C_KEYWORDS: [&str; 40] = [
+    "auto","break","case","char","const","continue","default","do","double","else",
+    "enum","extern","float","for","goto","if","inline","int","long","register",
+    "restrict","return","short","signed","sizeof","static","struct","switch","typedef","union",
+    "unsigned","void","volatile","while","_Bool","_Complex","_Imaginary","bool","true","false",
+];
+
+// types and results shared by all generated headers
+const HEADER_RUNTIME: &str = r#"#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifndef CODEC_RUNTIME_H
+#define CODEC_RUNTIME_H
+
+typedef enum {
+    CODEC_OK = 0,
+    // The buffer ends before the encoded value does.
+    CODEC_TRUNCATED = 1,
+    // Unknown enum variant or option tag.
+    CODEC_INVALID_TAG = 2,
+    // The value doesn't fit in the buffer to encode into.
+    CODEC_BUFFER_FULL = 3,
+    // The strings and arrays of a decoded value don't fit in the arena.
+    CODEC_ARENA_FULL = 4,
+} codec_result;
+
+// UTF-8 bytes, not counting the 0 that decoding puts after them.
+typedef struct {
+    char *data;
+    uint32_t len;
+} codec_string;
+
+// Caller memory that decoding places strings, arrays and optional values in.
+typedef struct {
+    uint8_t *data;
+    size_t size;
+    size_t used;
+} codec_arena;
+
+#endif
+"#;
+
+// reading and writing little-endian values, included in every generated source
+const SOURCE_RUNTIME: &str = r#"#include <string.h>
+
+#if defined(__GNUC__)
+#define CODEC_UNUSED __attribute__((unused))
+#else
+#define CODEC_UNUSED
+#endif
+
+#define CODEC_TRY(x) do { codec_result codec_r_ = (x); if (codec_r_ != CODEC_OK) { return codec_r_; } } while (0)
+
+// allocate `n` elements for `p` from the arena of reader `r`
+#define CODEC_ALLOC(p, n) do { if ((n) > 0) { (p) = codec_alloc(r->arena, sizeof(*(p)), (n)); if ((p) == NULL) { return CODEC_ARENA_FULL; } } else { (p) = NULL; } } while (0)
+
+typedef union {
+    uint64_t u;
+    double d;
+    void *p;
+} codec_align;
+
+typedef struct {
+    uint8_t *p;
+    uint8_t *end;
+} codec_writer;
+
+typedef struct {
+    const uint8_t *p;
+    const uint8_t *end;
+    codec_arena *arena;
+} codec_reader;
+
+static CODEC_UNUSED void *codec_alloc(codec_arena *arena, size_t size, size_t count) {
+    size_t pad;
+    void *p;
+    if (arena == NULL) {
+        return NULL;
+    }
+    pad = (sizeof(codec_align) - (uintptr_t)(arena->data + arena->used) % sizeof(codec_align)) % sizeof(codec_align);
+    if ((count > (arena->size - arena->used) / size) || (pad > arena->size - arena->used - count * size)) {
+        return NULL;
+    }
+    p = arena->data + arena->used + pad;
+    arena->used += pad + count * size;
+    return p;
+}
+
+static CODEC_UNUSED codec_result codec_put(codec_writer *w, uint64_t v, size_t n) {
+    size_t i;
+    if ((size_t)(w->end - w->p) < n) {
+        return CODEC_BUFFER_FULL;
+    }
+    for (i = 0; i < n; i++) {
+        w->p[i] = (uint8_t)(v >> (8 * i));
+    }
+    w->p += n;
+    return CODEC_OK;
+}
+
+static CODEC_UNUSED codec_result codec_get(codec_reader *r, uint64_t *v, size_t n) {
+    size_t i;
+    if ((size_t)(r->end - r->p) < n) {
+        return CODEC_TRUNCATED;
+    }
+    *v = 0;
+    for (i = 0; i < n; i++) {
+        *v |= (uint64_t)r->p[i] << (8 * i);
+    }
+    r->p += n;
+    return CODEC_OK;
+}
+
+#define CODEC_INTEGER(name, type, n) \
+    static CODEC_UNUSED codec_result codec_put_##name(codec_writer *w, type v) { \
+        return codec_put(w, (uint64_t)v, n); \
+    } \
+    static CODEC_UNUSED codec_result codec_get_##name(codec_reader *r, type *v) { \
+        uint64_t b; \
+        CODEC_TRY(codec_get(r, &b, n)); \
+        *v = (type)b; \
+        return CODEC_OK; \
+    }
+
+CODEC_INTEGER(u8, uint8_t, 1)
+CODEC_INTEGER(i8, int8_t, 1)
+CODEC_INTEGER(u16, uint16_t, 2)
+CODEC_INTEGER(i16, int16_t, 2)
+CODEC_INTEGER(u32, uint32_t, 4)
+CODEC_INTEGER(i32, int32_t, 4)
+CODEC_INTEGER(u64, uint64_t, 8)
+CODEC_INTEGER(i64, int64_t, 8)
+
+static CODEC_UNUSED codec_result codec_put_bool(codec_writer *w, bool v) {
+    return codec_put(w, v ? 1 : 0, 1);
+}
+
+static CODEC_UNUSED codec_result codec_get_bool(codec_reader *r, bool *v) {
+    uint64_t b;
+    CODEC_TRY(codec_get(r, &b, 1));
+    *v = b != 0;
+    return CODEC_OK;
+}
+
+static CODEC_UNUSED codec_result codec_put_f32(codec_writer *w, float v) {
+    uint32_t b;
+    memcpy(&b, &v, 4);
+    return codec_put(w, b, 4);
+}
+
+static CODEC_UNUSED codec_result codec_get_f32(codec_reader *r, float *v) {
+    uint64_t b;
+    uint32_t b32;
+    CODEC_TRY(codec_get(r, &b, 4));
+    b32 = (uint32_t)b;
+    memcpy(v, &b32, 4);
+    return CODEC_OK;
+}
+
+static CODEC_UNUSED codec_result codec_put_f64(codec_writer *w, double v) {
+    uint64_t b;
+    memcpy(&b, &v, 8);
+    return codec_put(w, b, 8);
+}
+
+static CODEC_UNUSED codec_result codec_get_f64(codec_reader *r, double *v) {
+    uint64_t b;
+    CODEC_TRY(codec_get(r, &b, 8));
+    memcpy(v, &b, 8);
+    return CODEC_OK;
+}
+
+static CODEC_UNUSED codec_result codec_put_string(codec_writer *w, const codec_string *s) {
+    CODEC_TRY(codec_put(w, s->len, 4));
+    if ((size_t)(w->end - w->p) < s->len) {
+        return CODEC_BUFFER_FULL;
+    }
+    if (s->len > 0) {
+        memcpy(w->p, s->data, s->len);
+    }
+    w->p += s->len;
+    return CODEC_OK;
+}
+
+static CODEC_UNUSED codec_result codec_get_string(codec_reader *r, codec_string *s) {
+    CODEC_TRY(codec_get_u32(r, &s->len));
+    if ((size_t)(r->end - r->p) < s->len) {
+        return CODEC_TRUNCATED;
+    }
+    s->data = codec_alloc(r->arena, 1, (size_t)s->len + 1);
+    if (s->data == NULL) {
+        return CODEC_ARENA_FULL;
+    }
+    memcpy(s->data, r->p, s->len);
+    s->data[s->len] = 0;
+    r->p += s->len;
+    return CODEC_OK;
+}
+
+// length of a collection whose elements take at least `min` bytes each
+static CODEC_UNUSED codec_result codec_get_count(codec_reader *r, uint32_t *len, size_t min) {
+    CODEC_TRY(codec_get_u32(r, len));
+    if ((min > 0) && (*len > (size_t)(r->end - r->p) / min)) {
+        return CODEC_TRUNCATED;
+    }
+    return CODEC_OK;
+}
+"#;
+
+fn c_name(name: &str) -> String {
+    if C_KEYWORDS.contains(&name) {
+        format!("{}_",name)
+    }
+    else {
+        name.to_string()
+    }
+}
+
+// part of the name of generated collection types, `vec_u8`, `map_string_f64`
+fn mangle(ty: &Type) -> String {
+    match ty {
+        Type::Vec(ty) => format!("vec_{}",mangle(ty)),
+        Type::Set(ty) => format!("set_{}",mangle(ty)),
+        Type::Map(key,value) => format!("map_{}_{}",mangle(key),mangle(value)),
+        Type::Option(ty) => format!("opt_{}",mangle(ty)),
+        Type::Named(name) => name.clone(),
+        ty => ty.to_string(),
+    }
+}
+
+// `Option<T>` is a pointer to `T`, `NULL` for `None`
+fn c_type(ty: &Type,prefix: &str) -> String {
+    match ty {
+        Type::Bool => "bool".to_string(),
+        Type::U8 => "uint8_t".to_string(),
+        Type::I8 => "int8_t".to_string(),
+        Type::U16 => "uint16_t".to_string(),
+        Type::I16 => "int16_t".to_string(),
+        Type::U32 => "uint32_t".to_string(),
+        Type::I32 => "int32_t".to_string(),
+        Type::U64 => "uint64_t".to_string(),
+        Type::I64 => "int64_t".to_string(),
+        Type::F32 => "float".to_string(),
+        Type::F64 => "double".to_string(),
+        Type::String => "codec_string".to_string(),
+        Type::Vec(_) | Type::Set(_) | Type::Map(_,_) => format!("{}_{}",prefix,mangle(ty)),
+        Type::Option(ty) => {
+            let inner = c_type(ty,prefix);
+            if inner.ends_with('*') { format!("{}*",inner) } else { format!("{} *",inner) }
+        },
+        Type::Named(name) => c_name(name),
+        ty => unreachable!("`{}` is not portable",ty),
+    }
+}
+
+fn c_decl(ty: &Type,prefix: &str,name: &str) -> String {
+    let ty = c_type(ty,prefix);
+    if ty.ends_with('*') { format!("{}{}",ty,name) } else { format!("{} {}",ty,name) }
+}
+
+fn c_string(s: &str) -> String {
+    let mut r = "\"".to_string();
+    for b in s.bytes() {
+        match b {
+            b'"' => r += "\\\"",
+            b'\\' => r += "\\\\",
+            b'\n' => r += "\\n",
+            b'\t' => r += "\\t",
+            0x20..=0x7E => r.push(b as char),
+            b => r += &format!("\\{:03o}",b),
+        }
+    }
+    r += "\"";
+    r
+}
+
+fn c_value(ty: &Type,value: &Value) -> String {
+    match (ty,value) {
+        (_,Value::Bool(b)) => b.to_string(),
+        (Type::U64,Value::Int(i)) => format!("UINT64_C({})",i),
+        (Type::I64,Value::Int(i)) if *i == i64::MIN as i128 => "INT64_MIN".to_string(),
+        (Type::I64,Value::Int(i)) => format!("INT64_C({})",i),
+        (Type::U32,Value::Int(i)) => format!("{}u",i),
+        (Type::I32,Value::Int(i)) if *i == i32::MIN as i128 => "INT32_MIN".to_string(),
+        (_,Value::Int(i)) if *i < 0 => format!("({})",i),
+        (_,Value::Int(i)) => i.to_string(),
+        (Type::F32,Value::Float(x)) if *x < 0.0 => format!("({:?}f)",x),
+        (Type::F32,Value::Float(x)) => format!("{:?}f",x),
+        (_,Value::Float(x)) if *x < 0.0 => format!("({:?})",x),
+        (_,Value::Float(x)) => format!("{:?}",x),
+        (_,Value::String(s)) => c_string(s),
+    }
+}
+
+// fewest bytes a value of `ty` encodes to, to reject lengths the input can't hold
+fn min_size(schema: &Schema,ty: &Type,seen: &mut Vec<String>) -> usize {
+    match ty {
+        Type::Bool | Type::U8 | Type::I8 | Type::Option(_) => 1,
+        Type::U16 | Type::I16 => 2,
+        Type::U32 | Type::I32 | Type::F32 | Type::String | Type::Vec(_) | Type::Set(_) | Type::Map(_,_) => 4,
+        Type::U64 | Type::I64 | Type::F64 => 8,
+        Type::Named(name) => {
+            if seen.contains(name) {
+                return 0;
+            }
+            seen.push(name.clone());
+            let size = match schema.item(name).map(|item| &item.kind) {
+                Some(ItemKind::Struct(fields)) => fields.iter().map(|field| min_size(schema,&field.ty,seen)).sum(),
+                _ => 4,
+            };
+            seen.pop();
+            size
+        },
+        ty => unreachable!("`{}` is not portable",ty),
+    }
+}
+
+// size of `ty` if all its values encode to the same number of bytes
+fn fixed_size(ty: &Type) -> Option<usize> {
+    match ty {
+        Type::Bool | Type::U8 | Type::I8 => Some(1),
+        Type::U16 | Type::I16 => Some(2),
+        Type::U32 | Type::I32 | Type::F32 => Some(4),
+        Type::U64 | Type::I64 | Type::F64 => Some(8),
+        _ => None,
+    }
+}
+
+// the collection types used by `ty`, innermost first
+fn collect_collections(ty: &Type,collections: &mut Vec<Type>) {
+    match ty {
+        Type::Vec(item) | Type::Set(item) | Type::Option(item) => collect_collections(item,collections),
+        Type::Map(key,value) => {
+            collect_collections(key,collections);
+            collect_collections(value,collections);
+        },
+        _ => { },
+    }
+    if matches!(ty,Type::Vec(_) | Type::Set(_) | Type::Map(_,_)) && !collections.contains(ty) {
+        collections.push(ty.clone());
+    }
+}
+
+// items that have to be defined before `item`, because it contains them by value
+fn value_dependencies(item: &Item) -> Vec<String> {
+    let fields: Vec<&Field> = match &item.kind {
+        ItemKind::Struct(fields) => fields.iter().collect(),
+        ItemKind::Enum(variants) => variants.iter().flat_map(|variant| variant.fields.iter()).collect(),
+    };
+    fields.iter().filter_map(|field| if let Type::Named(name) = &field.ty { Some(name.clone()) } else { None }).collect()
+}
+
+fn render_comment(doc: &[String],indent: &str) -> String {
+    let mut r = String::new();
+    for line in doc {
+        r += indent;
+        r += "//";
+        if !line.is_empty() {
+            r += " ";
+            r += line;
+        }
+        r += "\n";
+    }
+    r
+}
+
+fn render_fields(fields: &[Field],prefix: &str,indent: &str) -> String {
+    let mut r = String::new();
+    for field in fields {
+        r += &render_comment(&field.doc,indent);
+        r += &format!("{}{};\n",indent,c_decl(&field.ty,prefix,&c_name(&field.name)));
+    }
+    r
+}
+
+// statements writing `expr` of type `ty` to writer `w`; `depth` keeps the loop variables of nested collections apart
+fn render_encode(ty: &Type,expr: &str,indent: &str,depth: usize) -> String {
+    let inner = format!("{}    ",indent);
+    match ty {
+        Type::String => format!("{}CODEC_TRY(codec_put_string(w, &{}));\n",indent,expr),
+        Type::Vec(item) | Type::Set(item) => {
+            let mut r = format!("{}CODEC_TRY(codec_put_u32(w, {}.len));\n",indent,expr);
+            r += &format!("{}for (uint32_t i{} = 0; i{} < {}.len; i{}++) {{\n",indent,depth,depth,expr,depth);
+            r += &render_encode(item,&format!("{}.items[i{}]",expr,depth),&inner,depth + 1);
+            r += &format!("{}}}\n",indent);
+            r
+        },
+        Type::Map(key,value) => {
+            let mut r = format!("{}CODEC_TRY(codec_put_u32(w, {}.len));\n",indent,expr);
+            r += &format!("{}for (uint32_t i{} = 0; i{} < {}.len; i{}++) {{\n",indent,depth,depth,expr,depth);
+            r += &render_encode(key,&format!("{}.keys[i{}]",expr,depth),&inner,depth + 1);
+            r += &render_encode(value,&format!("{}.values[i{}]",expr,depth),&inner,depth + 1);
+            r += &format!("{}}}\n",indent);
+            r
+        },
+        Type::Option(ty) => {
+            let mut r = format!("{}if ({} == NULL) {{\n{}CODEC_TRY(codec_put_u8(w, 0));\n{}}}\n",indent,expr,inner,indent);
+            r += &format!("{}else {{\n{}CODEC_TRY(codec_put_u8(w, 1));\n",indent,inner);
+            r += &render_encode(ty,&format!("(*{})",expr),&inner,depth);
+            r += &format!("{}}}\n",indent);
+            r
+        },
+        Type::Named(name) => format!("{}CODEC_TRY(enc_{}(w, &{}));\n",indent,c_name(name),expr),
+        ty => format!("{}CODEC_TRY(codec_put_{}(w, {}));\n",indent,ty,expr),
+    }
+}
+
+// statements decoding a value of type `ty` from reader `r` into `target`
+fn render_decode(schema: &Schema,ty: &Type,target: &str,indent: &str,depth: usize) -> String {
+    let inner = format!("{}    ",indent);
+    match ty {
+        Type::String => format!("{}CODEC_TRY(codec_get_string(r, &{}));\n",indent,target),
+        Type::Vec(item) | Type::Set(item) => {
+            let mut r = format!("{}CODEC_TRY(codec_get_count(r, &{}.len, {}));\n",indent,target,min_size(schema,item,&mut Vec::new()));
+            r += &format!("{}CODEC_ALLOC({}.items, {}.len);\n",indent,target,target);
+            r += &format!("{}for (uint32_t i{} = 0; i{} < {}.len; i{}++) {{\n",indent,depth,depth,target,depth);
+            r += &render_decode(schema,item,&format!("{}.items[i{}]",target,depth),&inner,depth + 1);
+            r += &format!("{}}}\n",indent);
+            r
+        },
+        Type::Map(key,value) => {
+            let min = min_size(schema,key,&mut Vec::new()) + min_size(schema,value,&mut Vec::new());
+            let mut r = format!("{}CODEC_TRY(codec_get_count(r, &{}.len, {}));\n",indent,target,min);
+            r += &format!("{}CODEC_ALLOC({}.keys, {}.len);\n",indent,target,target);
+            r += &format!("{}CODEC_ALLOC({}.values, {}.len);\n",indent,target,target);
+            r += &format!("{}for (uint32_t i{} = 0; i{} < {}.len; i{}++) {{\n",indent,depth,depth,target,depth);
+            r += &render_decode(schema,key,&format!("{}.keys[i{}]",target,depth),&inner,depth + 1);
+            r += &render_decode(schema,value,&format!("{}.values[i{}]",target,depth),&inner,depth + 1);
+            r += &format!("{}}}\n",indent);
+            r
+        },
+        Type::Option(ty) => {
+            let mut r = format!("{}{{\n{}uint8_t t{};\n{}CODEC_TRY(codec_get_u8(r, &t{}));\n",indent,inner,depth,inner,depth);
+            r += &format!("{}if (t{} == 0) {{\n{}    {} = NULL;\n{}}}\n",inner,depth,inner,target,inner);
+            r += &format!("{}else if (t{} == 1) {{\n{}    CODEC_ALLOC({}, 1);\n",inner,depth,inner,target);
+            r += &render_decode(schema,ty,&format!("(*{})",target),&format!("{}    ",inner),depth + 1);
+            r += &format!("{}}}\n{}else {{\n{}    return CODEC_INVALID_TAG;\n{}}}\n{}}}\n",inner,inner,inner,inner,indent);
+            r
+        },
+        Type::Named(name) => format!("{}CODEC_TRY(dec_{}(r, &{}));\n",indent,c_name(name),target),
+        ty => format!("{}CODEC_TRY(codec_get_{}(r, &{}));\n",indent,ty,target),
+    }
+}
+
+// statements adding the encoded size of `expr` to `n`
+fn render_size(ty: &Type,expr: &str,indent: &str,depth: usize) -> String {
+    let inner = format!("{}    ",indent);
+    match ty {
+        Type::String => format!("{}n += 4 + (size_t){}.len;\n",indent,expr),
+        Type::Vec(item) | Type::Set(item) => {
+            if let Some(size) = fixed_size(item) {
+                format!("{}n += 4 + (size_t){}.len * {};\n",indent,expr,size)
+            }
+            else {
+                let mut r = format!("{}n += 4;\n",indent);
+                r += &format!("{}for (uint32_t i{} = 0; i{} < {}.len; i{}++) {{\n",indent,depth,depth,expr,depth);
+                r += &render_size(item,&format!("{}.items[i{}]",expr,depth),&inner,depth + 1);
+                r += &format!("{}}}\n",indent);
+                r
+            }
+        },
+        Type::Map(key,value) => {
+            let mut r = format!("{}n += 4;\n",indent);
+            r += &format!("{}for (uint32_t i{} = 0; i{} < {}.len; i{}++) {{\n",indent,depth,depth,expr,depth);
+            r += &render_size(key,&format!("{}.keys[i{}]",expr,depth),&inner,depth + 1);
+            r += &render_size(value,&format!("{}.values[i{}]",expr,depth),&inner,depth + 1);
+            r += &format!("{}}}\n",indent);
+            r
+        },
+        Type::Option(ty) => {
+            let mut r = format!("{}n += 1;\n{}if ({} != NULL) {{\n",indent,indent,expr);
+            r += &render_size(ty,&format!("(*{})",expr),&inner,depth);
+            r += &format!("{}}}\n",indent);
+            r
+        },
+        Type::Named(name) => format!("{}n += {}_size(&{});\n",indent,c_name(name),expr),
+        ty => format!("{}n += {};\n",indent,fixed_size(ty).unwrap()),
+    }
+}
+
+fn render_header(schema: &Schema,guard: &str,prefix: &str) -> String {
+    let mut r = format!("#ifndef {}\n#define {}\n\n{}\n",guard,guard,HEADER_RUNTIME);
+    for c in &schema.consts {
+        r += &render_comment(&c.doc,"");
+        r += &format!("#define {} {}\n",c.name,c_value(&c.ty,&c.value));
+    }
+    if !schema.consts.is_empty() {
+        r += "\n";
+    }
+    let mut collections = Vec::<Type>::new();
+    for item in &schema.items {
+        let name = c_name(&item.name);
+        r += &format!("typedef struct {} {};\n",name,name);
+        let fields: Vec<&Field> = match &item.kind {
+            ItemKind::Struct(fields) => fields.iter().collect(),
+            ItemKind::Enum(variants) => variants.iter().flat_map(|variant| variant.fields.iter()).collect(),
+        };
+        for field in fields {
+            collect_collections(&field.ty,&mut collections);
+        }
+    }
+    for ty in &collections {
+        let name = c_type(ty,prefix);
+        r += &format!("typedef struct {} {};\n",name,name);
+    }
+    for ty in &collections {
+        r += &format!("\nstruct {} {{\n",c_type(ty,prefix));
+        match ty {
+            Type::Map(key,value) => {
+                r += &format!("    {};\n",c_decl(key,prefix,"*keys"));
+                r += &format!("    {};\n",c_decl(value,prefix,"*values"));
+            },
+            Type::Vec(item) | Type::Set(item) => r += &format!("    {};\n",c_decl(item,prefix,"*items")),
+            _ => { },
+        }
+        r += "    uint32_t len;\n};\n";
+    }
+    // items in an order where everything an item contains by value is defined before it
+    let mut done = Vec::<&str>::new();
+    while done.len() < schema.items.len() {
+        let before = done.len();
+        for item in &schema.items {
+            if done.contains(&item.name.as_str()) || value_dependencies(item).iter().any(|name| !done.contains(&name.as_str())) {
+                continue;
+            }
+            done.push(&item.name);
+            r += &render_item(item,prefix);
+        }
+        if done.len() == before {
+            panic!("items in the schema contain each other");
+        }
+    }
+    r += "\n";
+    for item in &schema.items {
+        let name = c_name(&item.name);
+        r += &format!("// Encode `value` into `buffer` of `size` bytes, setting `*written` if it isn't NULL.\ncodec_result {}_encode(const {} *value, uint8_t *buffer, size_t size, size_t *written);\n",name,name);
+        r += &format!("// Decode from `buffer` of `size` bytes, setting `*consumed` if it isn't NULL.\n// Strings, arrays and optional values are placed in `arena`.\ncodec_result {}_decode({} *value, const uint8_t *buffer, size_t size, size_t *consumed, codec_arena *arena);\n",name,name);
+        r += &format!("// Number of bytes `value` encodes to.\nsize_t {}_size(const {} *value);\n\n",name,name);
+    }
+    r += &format!("#endif // {}\n",guard);
+    r
+}
+
+fn render_item(item: &Item,prefix: &str) -> String {
+    let name = c_name(&item.name);
+    let mut r = "\n".to_string();
+    for c in &item.consts {
+        r += &render_comment(&c.doc,"");
+        r += &format!("#define {}_{} {}\n",name,c.name,c_value(&c.ty,&c.value));
+    }
+    if !item.consts.is_empty() {
+        r += "\n";
+    }
+    match &item.kind {
+        ItemKind::Struct(fields) => {
+            r += &render_comment(&item.doc,"");
+            r += &format!("struct {} {{\n",name);
+            r += &render_fields(fields,prefix,"    ");
+            r += "};\n";
+        },
+        ItemKind::Enum(variants) => {
+            r += "typedef enum {\n";
+            for (i,variant) in variants.iter().enumerate() {
+                r += &render_comment(&variant.doc,"    ");
+                r += &format!("    {}_{} = {},\n",name,variant.name,i);
+            }
+            r += &format!("}} {}_tag;\n\n",name);
+            r += &render_comment(&item.doc,"");
+            r += &format!("struct {} {{\n    {}_tag tag;\n",name,name);
+            if variants.iter().any(|variant| !variant.fields.is_empty()) {
+                r += "    union {\n";
+                for variant in variants.iter().filter(|variant| !variant.fields.is_empty()) {
+                    r += "        struct {\n";
+                    r += &render_fields(&variant.fields,prefix,"            ");
+                    r += &format!("        }} {};\n",c_name(&variant.name));
+                }
+                r += "    } as;\n";
+            }
+            r += "};\n";
+        },
+    }
+    r
+}
+
+fn render_source(schema: &Schema,header: &str) -> String {
+    let mut r = format!("#include \"{}\"\n\n{}\n",header,SOURCE_RUNTIME);
+    for item in &schema.items {
+        let name = c_name(&item.name);
+        r += &format!("static codec_result enc_{}(codec_writer *w, const {} *v);\n",name,name);
+        r += &format!("static codec_result dec_{}(codec_reader *r, {} *v);\n",name,name);
+    }
+    for item in &schema.items {
+        let name = c_name(&item.name);
+        let mut encode = String::new();
+        let mut decode = String::new();
+        let mut size = String::new();
+        match &item.kind {
+            ItemKind::Struct(fields) => {
+                if fields.is_empty() {
+                    encode += "    (void)w;\n    (void)v;\n";
+                    decode += "    (void)r;\n    (void)v;\n";
+                }
+                for field in fields {
+                    let field_name = c_name(&field.name);
+                    encode += &render_encode(&field.ty,&format!("v->{}",field_name),"    ",0);
+                    decode += &render_decode(schema,&field.ty,&format!("v->{}",field_name),"    ",0);
+                    size += &render_size(&field.ty,&format!("value->{}",field_name),"    ",0);
+                }
+                // fixed size structs don't look at the value
+                if !size.contains("value") {
+                    size.insert_str(0,"    (void)value;\n");
+                }
+            },
+            ItemKind::Enum(variants) => {
+                encode += "    CODEC_TRY(codec_put_u32(w, (uint32_t)v->tag));\n    switch (v->tag) {\n";
+                decode += "    uint32_t tag;\n    CODEC_TRY(codec_get_u32(r, &tag));\n    switch (tag) {\n";
+                size += "    n += 4;\n    switch (value->tag) {\n";
+                for variant in variants {
+                    encode += &format!("        case {}_{}:\n",name,variant.name);
+                    decode += &format!("        case {}_{}:\n            v->tag = {}_{};\n",name,variant.name,name,variant.name);
+                    size += &format!("        case {}_{}:\n",name,variant.name);
+                    for field in &variant.fields {
+                        let field_name = c_name(&field.name);
+                        encode += &render_encode(&field.ty,&format!("v->as.{}.{}",c_name(&variant.name),field_name),"            ",0);
+                        decode += &render_decode(schema,&field.ty,&format!("v->as.{}.{}",c_name(&variant.name),field_name),"            ",0);
+                        size += &render_size(&field.ty,&format!("value->as.{}.{}",c_name(&variant.name),field_name),"            ",0);
+                    }
+                    encode += "            break;\n";
+                    decode += "            break;\n";
+                    size += "            break;\n";
+                }
+                encode += "        default:\n            return CODEC_INVALID_TAG;\n    }\n";
+                decode += "        default:\n            return CODEC_INVALID_TAG;\n    }\n";
+                size += "        default:\n            break;\n    }\n";
+            },
+        }
+        r += &format!("\nstatic codec_result enc_{}(codec_writer *w, const {} *v) {{\n{}    return CODEC_OK;\n}}\n",name,name,encode);
+        r += &format!("\nstatic codec_result dec_{}(codec_reader *r, {} *v) {{\n{}    return CODEC_OK;\n}}\n",name,name,decode);
+        r += &format!("\ncodec_result {}_encode(const {} *value, uint8_t *buffer, size_t size, size_t *written) {{\n",name,name);
+        r += "    codec_writer w;\n    codec_result result;\n    w.p = buffer;\n    w.end = buffer + size;\n";
+        r += &format!("    result = enc_{}(&w, value);\n",name);
+        r += "    if ((result == CODEC_OK) && (written != NULL)) {\n        *written = (size_t)(w.p - buffer);\n    }\n    return result;\n}\n";
+        r += &format!("\ncodec_result {}_decode({} *value, const uint8_t *buffer, size_t size, size_t *consumed, codec_arena *arena) {{\n",name,name);
+        r += "    codec_reader r;\n    codec_result result;\n    r.p = buffer;\n    r.end = buffer + size;\n    r.arena = arena;\n";
+        r += &format!("    result = dec_{}(&r, value);\n",name);
+        r += "    if ((result == CODEC_OK) && (consumed != NULL)) {\n        *consumed = (size_t)(r.p - buffer);\n    }\n    return result;\n}\n";
+        r += &format!("\nsize_t {}_size(const {} *value) {{\n    size_t n = 0;\n{}    return n;\n}}\n",name,name,size);
+    }
+    r
+}
+
+/// C99 header and source for `schema`, for `name.h` and `name.c`.
+///
+/// Every item becomes a struct, with `Item_encode` writing into a caller
+/// buffer, `Item_decode` reading from one and `Item_size`. Nothing is
+/// allocated: decoding places strings, arrays and optional values in a
+/// caller `codec_arena`. Arrays and sets are `{ items, len }`, maps are
+/// `{ keys, values, len }`, optional values are pointers that are `NULL`
+/// for `None`, and enums are a `tag` with the fields of each variant in
+/// the union `as`. Collection types are named after `name`, like
+/// `sensors_vec_u8`.
+///
+/// Panics if a field has a type that only Rust has, see `Type::is_portable`.
+pub fn generate_c(schema: &Schema,name: &str) -> (String,String) {
+    check_portable(schema,"C");
+    let prefix: String = name.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect();
+    let guard = format!("{}_H",prefix.to_uppercase());
+    (render_header(schema,&guard,&prefix),render_source(schema,&format!("{}.h",name)))
+}
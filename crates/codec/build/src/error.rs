@@ -0,0 +1,36 @@
+// Echidna - Codec - Build
+
+use std::{
+    fmt,
+    io,
+};
+
+#[derive(Debug)]
+pub enum Error {
+    /// Reading a schema file or writing the output failed.
+    Io {
+        file: String,
+        error: io::Error,
+    },
+    /// Schema file is malformed, at 1-based `line` and `column`.
+    Parse {
+        file: String,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+}
+
+// `file:line:column: message`, like compiler errors, so editors can jump to it
+impl fmt::Display for Error {
+    fn fmt(&self,f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io { file,error } => write!(f,"{}: {}",file,error),
+            Error::Parse { file,line,column,message } => write!(f,"{}:{}:{}: {}",file,line,column,message),
+        }
+    }
+}
+
+impl std::error::Error for Error { }
+
+pub type Result<T> = std::result::Result<T,Error>;
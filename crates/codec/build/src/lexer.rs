@@ -0,0 +1,233 @@
+// Echidna - Codec - Build
+
+use crate::*;
+
+#[derive(Clone,Debug,PartialEq)]
+pub(crate) enum Token {
+    Ident(String),
+    Int(i128),
+    Float(f64),
+    Str(String),
+    Punct(char),
+    End,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self,f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Ident(ident) => write!(f,"`{}`",ident),
+            Token::Int(i) => write!(f,"`{}`",i),
+            Token::Float(x) => write!(f,"`{:?}`",x),
+            Token::Str(s) => write!(f,"{:?}",s),
+            Token::Punct(c) => write!(f,"`{}`",c),
+            Token::End => write!(f,"end of file"),
+        }
+    }
+}
+
+/// Token with its position, and the comment lines right above it.
+pub(crate) struct Spanned {
+    pub(crate) token: Token,
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+    pub(crate) doc: Vec<String>,
+}
+
+pub(crate) struct Lexer<'a> {
+    file: &'a str,
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+    column: usize,
+}
+
+impl<'a> Lexer<'a> {
+
+    pub(crate) fn new(file: &'a str,source: &str) -> Lexer<'a> {
+        Lexer {
+            file: file,
+            chars: source.chars().collect(),
+            pos: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+
+    pub(crate) fn error<T>(&self,line: usize,column: usize,message: String) -> Result<T> {
+        Err(Error::Parse {
+            file: self.file.to_string(),
+            line: line,
+            column: column,
+            message: message,
+        })
+    }
+
+    fn peek(&self,ahead: usize) -> Option<char> {
+        self.chars.get(self.pos + ahead).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek(0)?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        }
+        else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    // skip whitespace and `#` comments, collecting the comments that start
+    // their own line as documentation; an empty line ends such a block
+    fn skip_trivia(&mut self,after_line: usize) -> Vec<String> {
+        let mut doc = Vec::<String>::new();
+        let mut newlines = 0;
+        while let Some(c) = self.peek(0) {
+            if c == '#' {
+                let own_line = self.line > after_line;
+                let mut comment = String::new();
+                self.bump();
+                while let Some(c) = self.peek(0).filter(|c| *c != '\n') {
+                    comment.push(c);
+                    self.bump();
+                }
+                if own_line {
+                    doc.push(comment.strip_prefix(' ').unwrap_or(&comment).trim_end().to_string());
+                }
+                newlines = 0;
+            }
+            else if c.is_whitespace() {
+                if c == '\n' {
+                    newlines += 1;
+                    if newlines >= 2 {
+                        doc.clear();
+                    }
+                }
+                self.bump();
+            }
+            else {
+                break;
+            }
+        }
+        doc
+    }
+
+    fn number(&mut self,line: usize,column: usize) -> Result<Token> {
+        let mut text = String::new();
+        if (self.peek(0) == Some('0')) && matches!(self.peek(1),Some('x') | Some('X')) {
+            self.bump();
+            self.bump();
+            while let Some(c) = self.peek(0) {
+                if c.is_ascii_hexdigit() || (c == '_') {
+                    text.push(c);
+                    self.bump();
+                }
+                else {
+                    break;
+                }
+            }
+            return match i128::from_str_radix(&text.replace('_',""),16) {
+                Ok(i) => Ok(Token::Int(i)),
+                Err(_) => self.error(line,column,format!("invalid hexadecimal number `0x{}`",text)),
+            };
+        }
+        let mut float = false;
+        while let Some(c) = self.peek(0) {
+            if c.is_ascii_digit() || (c == '_') {
+                text.push(c);
+            }
+            else if (c == '.') && !float && self.peek(1).is_some_and(|c| c.is_ascii_digit()) {
+                float = true;
+                text.push(c);
+            }
+            else if (c == 'e') || (c == 'E') {
+                float = true;
+                text.push(c);
+                if let Some(sign) = self.peek(1).filter(|c| (*c == '-') || (*c == '+')) {
+                    self.bump();
+                    text.push(sign);
+                }
+            }
+            else {
+                break;
+            }
+            self.bump();
+        }
+        let text = text.replace('_',"");
+        if float {
+            match text.parse::<f64>() {
+                Ok(x) => Ok(Token::Float(x)),
+                Err(_) => self.error(line,column,format!("invalid number `{}`",text)),
+            }
+        }
+        else {
+            match text.parse::<i128>() {
+                Ok(i) => Ok(Token::Int(i)),
+                Err(_) => self.error(line,column,format!("number `{}` is too large",text)),
+            }
+        }
+    }
+
+    fn string(&mut self,line: usize,column: usize) -> Result<Token> {
+        self.bump();
+        let mut s = String::new();
+        loop {
+            match self.bump() {
+                None | Some('\n') => return self.error(line,column,"unterminated string".to_string()),
+                Some('"') => return Ok(Token::Str(s)),
+                Some('\\') => {
+                    let (l,c) = (self.line,self.column);
+                    match self.bump() {
+                        Some('n') => s.push('\n'),
+                        Some('t') => s.push('\t'),
+                        Some('\\') => s.push('\\'),
+                        Some('"') => s.push('"'),
+                        _ => return self.error(l,c - 1,"unknown escape in string".to_string()),
+                    }
+                },
+                Some(c) => s.push(c),
+            }
+        }
+    }
+
+    /// All tokens in the source, ending with `Token::End`.
+    pub(crate) fn tokens(&mut self) -> Result<Vec<Spanned>> {
+        let mut tokens = Vec::<Spanned>::new();
+        let mut last_line = 0;
+        loop {
+            let doc = self.skip_trivia(last_line);
+            let (line,column) = (self.line,self.column);
+            let token = match self.peek(0) {
+                None => Token::End,
+                Some(c) if c.is_ascii_alphabetic() || (c == '_') => {
+                    let mut ident = String::new();
+                    while let Some(c) = self.peek(0).filter(|c| c.is_ascii_alphanumeric() || (*c == '_')) {
+                        ident.push(c);
+                        self.bump();
+                    }
+                    Token::Ident(ident)
+                },
+                Some(c) if c.is_ascii_digit() => self.number(line,column)?,
+                Some('"') => self.string(line,column)?,
+                Some(c) if "{}[]<>,?=-".contains(c) => {
+                    self.bump();
+                    Token::Punct(c)
+                },
+                Some(c) => return self.error(line,column,format!("unexpected character `{}`",c)),
+            };
+            last_line = self.line;
+            let end = token == Token::End;
+            tokens.push(Spanned {
+                token: token,
+                line: line,
+                column: column,
+                doc: doc,
+            });
+            if end {
+                return Ok(tokens);
+            }
+        }
+    }
+}
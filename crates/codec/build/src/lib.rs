@@ -0,0 +1,199 @@
+// Echidna - Codec - Build
+
+//! Code generation from schema files, for use in `build.rs`.
+//!
+//! Schema files describe structs, enums and constants in a small
+//! language-neutral format, so messages can be defined without writing
+//! Rust:
+//!
+//! ```text
+//! # Position of a tracked object.
+//! struct Pose {
+//!     u64 ts
+//!     f32[] position
+//!     map<string,f32> scores
+//!     Pose[] history
+//!     const u32 MAX_SCORES = 16
+//! }
+//!
+//! enum Shape {
+//!     Empty
+//!     Circle { f32 radius }
+//! }
+//! ```
+//!
+//! Fields are a type followed by a name. The types are `bool`, `u8` to
+//! `u64`, `i8` to `i64`, `f32`, `f64`, `string`, the structs and enums
+//! defined in the schema, `T[]` for a `Vec<T>`, `T?` for an `Option<T>`,
+//! `set<T>` and `map<K,V>`. An item can only refer to itself, directly or
+//! through other items, inside a `T[]` or a map. `#` starts a comment;
+//! comments right above an item, field or constant become its
+//! documentation.
+//!
+//! A build script turns schema files into Rust types with
+//! `#[derive(Codec)]`:
+//!
+//! ```no_run
+//! codec_build::Builder::new()
+//!     .file("msg/sensors.msg")
+//!     .compile("sensors.rs")
+//!     .unwrap_or_else(|e| panic!("{}",e));
+//! ```
+//!
+//! and the crate includes them with
+//! `include!(concat!(env!("OUT_DIR"),"/sensors.rs"));`.
+//!
+//! `Builder::compile_python` writes a Python module with a dataclass for
+//! every item instead, and `Builder::compile_c` a C99 header and source
+//! that don't allocate, both encoding to the same bytes. The generators
+//! also work on the schema of a type derived with
+//! `#[codec(schema)]`, from `codec::schema::Schema::of`.
+
+use {
+    codec::schema::*,
+    std::{
+        env,
+        fmt,
+        fs,
+        io,
+        path::PathBuf,
+    },
+};
+
+mod error;
+pub use error::*;
+
+mod lexer;
+use lexer::*;
+
+mod parser;
+use parser::*;
+pub use parser::parse;
+
+mod rust;
+pub use rust::*;
+
+mod python;
+pub use python::*;
+
+mod c;
+pub use c::*;
+
+// panic at the first field of a type that only the Rust generator supports, see `Type::is_portable`
+pub(crate) fn check_portable(schema: &Schema,language: &str) {
+    for item in &schema.items {
+        let fields: Vec<&Field> = match &item.kind {
+            ItemKind::Struct(fields) => fields.iter().collect(),
+            ItemKind::Enum(variants) => variants.iter().flat_map(|variant| variant.fields.iter()).collect(),
+        };
+        for field in fields {
+            if !field.ty.is_portable() {
+                panic!("`{}` of `{}.{}` can't be generated for {}",field.ty,item.name,field.name,language);
+            }
+        }
+    }
+}
+
+/// Generates Rust source from schema files.
+pub struct Builder {
+    files: Vec<PathBuf>,
+    krate: String,
+    out_dir: Option<PathBuf>,
+}
+
+impl Builder {
+
+    pub fn new() -> Builder {
+        Builder {
+            files: Vec::new(),
+            krate: "::codec".to_string(),
+            out_dir: None,
+        }
+    }
+
+    /// Add a schema file. Types in one file can refer to types in the others.
+    pub fn file<P: Into<PathBuf>>(mut self,path: P) -> Builder {
+        self.files.push(path.into());
+        self
+    }
+
+    /// Path to the `codec` crate in the generated code, `::codec` by default.
+    pub fn crate_path(mut self,path: &str) -> Builder {
+        self.krate = path.to_string();
+        self
+    }
+
+    /// Directory to write to, `OUT_DIR` by default.
+    pub fn out_dir<P: Into<PathBuf>>(mut self,path: P) -> Builder {
+        self.out_dir = Some(path.into());
+        self
+    }
+
+    // parse all files into one schema, and the list of files for the header
+    fn parse(&self) -> Result<(Schema,String)> {
+        let mut schemas = Vec::<Schema>::new();
+        let mut references = Vec::<Reference>::new();
+        let mut files = Vec::<String>::new();
+        for path in &self.files {
+            let file = path.display().to_string();
+            println!("cargo:rerun-if-changed={}",file);
+            let source = fs::read_to_string(path).map_err(|e| Error::Io { file: file.clone(),error: e, })?;
+            let (schema,mut refs) = parse_unresolved(&file,&source)?;
+            schemas.push(schema);
+            references.append(&mut refs);
+            files.push(file);
+        }
+        resolve(&schemas.iter().collect::<Vec<&Schema>>(),&references)?;
+        let mut merged = Schema::default();
+        for mut schema in schemas {
+            merged.consts.append(&mut schema.consts);
+            merged.items.append(&mut schema.items);
+        }
+        Ok((merged,files.join(", ")))
+    }
+
+    // write `code` to `name` in the output directory
+    fn write(&self,name: &str,code: String) -> Result<()> {
+        let out_dir = match &self.out_dir {
+            Some(dir) => dir.clone(),
+            None => env::var_os("OUT_DIR").map(PathBuf::from).ok_or_else(|| Error::Io {
+                file: name.to_string(),
+                error: io::Error::new(io::ErrorKind::NotFound,"OUT_DIR is not set, call this from build.rs or set out_dir"),
+            })?,
+        };
+        let out = out_dir.join(name);
+        fs::write(&out,code).map_err(|e| Error::Io { file: out.display().to_string(),error: e, })
+    }
+
+    /// Parse all files, and write the Rust types for them to `name` in the
+    /// output directory.
+    ///
+    /// Also tells cargo to run the build script again when a file changes.
+    pub fn compile(&self,name: &str) -> Result<()> {
+        let (schema,files) = self.parse()?;
+        self.write(name,format!("// generated by codec-build from {}, do not edit\n\n{}",files,generate_rust(&schema,&self.krate)))
+    }
+
+    /// Parse all files, and write a Python module for them to `name` in the
+    /// output directory.
+    pub fn compile_python(&self,name: &str) -> Result<()> {
+        let (schema,files) = self.parse()?;
+        self.write(name,format!("# generated by codec-build from {}, do not edit\n\n{}",files,generate_python(&schema)))
+    }
+
+    /// Parse all files, and write a C99 header and source for them to
+    /// `name.h` and `name.c` in the output directory.
+    pub fn compile_c(&self,name: &str) -> Result<()> {
+        let (schema,files) = self.parse()?;
+        let (header,source) = generate_c(&schema,name);
+        let comment = format!("// generated by codec-build from {}, do not edit\n\n",files);
+        self.write(&format!("{}.h",name),format!("{}{}",comment,header))?;
+        self.write(&format!("{}.c",name),format!("{}{}",comment,source))
+    }
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Builder::new()
+    }
+}
@@ -0,0 +1,406 @@
+// Echidna - Codec - Build
+
+use crate::*;
+
+/// Use of a named type, resolved once all files are parsed.
+pub(crate) struct Reference {
+    pub(crate) name: String,
+    pub(crate) file: String,
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+    /// Struct or enum the use is in.
+    pub(crate) item: String,
+    /// Whether the item stores the named type inline, instead of in a list or map.
+    pub(crate) by_value: bool,
+}
+
+pub(crate) struct Parser<'a> {
+    lexer: Lexer<'a>,
+    tokens: Vec<Spanned>,
+    pos: usize,
+    item: String,
+    pub(crate) references: Vec<Reference>,
+}
+
+impl<'a> Parser<'a> {
+
+    pub(crate) fn new(file: &'a str,source: &str) -> Result<Parser<'a>> {
+        let mut lexer = Lexer::new(file,source);
+        let tokens = lexer.tokens()?;
+        Ok(Parser {
+            lexer: lexer,
+            tokens: tokens,
+            pos: 0,
+            item: String::new(),
+            references: Vec::new(),
+        })
+    }
+
+    fn peek(&self) -> &Spanned {
+        &self.tokens[self.pos]
+    }
+
+    fn next(&mut self) -> &Spanned {
+        let token = &self.tokens[self.pos];
+        if token.token != Token::End {
+            self.pos += 1;
+        }
+        token
+    }
+
+    // error at the token about to be parsed
+    fn error<T>(&self,message: String) -> Result<T> {
+        let token = self.peek();
+        self.lexer.error(token.line,token.column,message)
+    }
+
+    fn expected<T>(&self,what: &str) -> Result<T> {
+        self.error(format!("expected {}, found {}",what,self.peek().token))
+    }
+
+    fn is_punct(&self,c: char) -> bool {
+        self.peek().token == Token::Punct(c)
+    }
+
+    fn parse_punct(&mut self,c: char) -> bool {
+        if self.is_punct(c) {
+            self.next();
+            true
+        }
+        else {
+            false
+        }
+    }
+
+    fn expect_punct(&mut self,c: char) -> Result<()> {
+        if self.parse_punct(c) { Ok(()) } else { self.expected(&format!("`{}`",c)) }
+    }
+
+    fn is_ident(&self,ident: &str) -> bool {
+        matches!(&self.peek().token,Token::Ident(i) if i == ident)
+    }
+
+    // name of a new item, field, variant or constant, which may not repeat one of `taken`
+    fn parse_name(&mut self,what: &str,taken: &[String]) -> Result<String> {
+        let name = match &self.peek().token {
+            Token::Ident(ident) if !KEYWORDS.contains(&ident.as_str()) => ident.clone(),
+            _ => return self.expected(&format!("{} name",what)),
+        };
+        if (what == "type") && Type::primitive(&name).is_some() {
+            return self.error(format!("`{}` is a built-in type",name));
+        }
+        if taken.contains(&name) {
+            return self.error(format!("{} `{}` is defined more than once",what,name));
+        }
+        self.next();
+        Ok(name)
+    }
+
+    // Type = Base { `[` `]` | `?` } .
+    // Base = PRIMITIVE | `map` `<` Type `,` Type `>` | `set` `<` Type `>` | IDENT .
+    fn parse_type(&mut self) -> Result<Type> {
+        let (line,column) = (self.peek().line,self.peek().column);
+        let first = self.references.len();
+        let name = match &self.peek().token {
+            Token::Ident(ident) => ident.clone(),
+            _ => return self.expected("type"),
+        };
+        self.next();
+        let mut ty = if let Some(ty) = Type::primitive(&name) {
+            ty
+        }
+        else if name == "map" {
+            self.expect_punct('<')?;
+            let key = self.parse_key_type()?;
+            self.expect_punct(',')?;
+            let value = self.parse_type()?;
+            self.expect_punct('>')?;
+            Type::Map(Box::new(key),Box::new(value))
+        }
+        else if name == "set" {
+            self.expect_punct('<')?;
+            let item = self.parse_key_type()?;
+            self.expect_punct('>')?;
+            Type::Set(Box::new(item))
+        }
+        else if KEYWORDS.contains(&name.as_str()) {
+            return self.lexer.error(line,column,format!("expected type, found `{}`",name));
+        }
+        else {
+            self.references.push(Reference {
+                name: name.clone(),
+                file: String::new(),
+                line: line,
+                column: column,
+                item: self.item.clone(),
+                by_value: true,
+            });
+            Type::Named(name)
+        };
+        loop {
+            if self.parse_punct('[') {
+                self.expect_punct(']')?;
+                ty = Type::Vec(Box::new(ty));
+            }
+            else if self.parse_punct('?') {
+                ty = Type::Option(Box::new(ty));
+            }
+            else {
+                if !is_by_value(&ty) {
+                    for reference in &mut self.references[first..] {
+                        reference.by_value = false;
+                    }
+                }
+                return Ok(ty);
+            }
+        }
+    }
+
+    // map keys and set items are hashed, which only the integers, `bool` and `string` can be
+    fn parse_key_type(&mut self) -> Result<Type> {
+        let (line,column) = (self.peek().line,self.peek().column);
+        let ty = self.parse_type()?;
+        if !(ty.is_integer() || (ty == Type::Bool) || (ty == Type::String)) {
+            return self.lexer.error(line,column,format!("`{}` can't be a map key or set item, only integers, `bool` and `string` can",ty));
+        }
+        Ok(ty)
+    }
+
+    // Value = [ `-` ] INTEGER | [ `-` ] FLOAT | `true` | `false` | STRING .
+    fn parse_value(&mut self,ty: &Type) -> Result<Value> {
+        let negative = self.parse_punct('-');
+        let value = match (&self.peek().token,negative) {
+            (Token::Int(i),_) => Value::Int(if negative { -*i } else { *i }),
+            (Token::Float(x),_) => Value::Float(if negative { -*x } else { *x }),
+            (Token::Ident(ident),false) if ident == "true" => Value::Bool(true),
+            (Token::Ident(ident),false) if ident == "false" => Value::Bool(false),
+            (Token::Str(s),false) => Value::String(s.clone()),
+            _ => return self.expected("value"),
+        };
+        let value = match (ty,value) {
+            (Type::Bool,Value::Bool(b)) => Value::Bool(b),
+            (Type::String,Value::String(s)) => Value::String(s),
+            (ty,Value::Int(i)) if ty.is_integer() => {
+                let (min,max) = match ty {
+                    Type::U8 => (0,u8::MAX as i128),
+                    Type::I8 => (i8::MIN as i128,i8::MAX as i128),
+                    Type::U16 => (0,u16::MAX as i128),
+                    Type::I16 => (i16::MIN as i128,i16::MAX as i128),
+                    Type::U32 => (0,u32::MAX as i128),
+                    Type::I32 => (i32::MIN as i128,i32::MAX as i128),
+                    Type::U64 => (0,u64::MAX as i128),
+                    _ => (i64::MIN as i128,i64::MAX as i128),
+                };
+                if (i < min) || (i > max) {
+                    return self.error(format!("{} is out of range for `{}`",i,ty));
+                }
+                Value::Int(i)
+            },
+            (ty,Value::Int(i)) if ty.is_float() => Value::Float(i as f64),
+            (ty,Value::Float(x)) if ty.is_float() => Value::Float(x),
+            (ty,_) if !(ty.is_integer() || ty.is_float() || (*ty == Type::Bool) || (*ty == Type::String)) => {
+                return self.error(format!("constants of type `{}` are not supported",ty));
+            },
+            (ty,value) => return self.error(format!("`{}` is not a `{}`",value,ty)),
+        };
+        self.next();
+        Ok(value)
+    }
+
+    // Const = `const` Type IDENT `=` Value .
+    fn parse_const(&mut self,taken: &[String]) -> Result<Const> {
+        let doc = self.next().doc.clone();
+        let ty = self.parse_type()?;
+        let name = self.parse_name("constant",taken)?;
+        self.expect_punct('=')?;
+        let value = self.parse_value(&ty)?;
+        Ok(Const {
+            doc: doc,
+            name: name,
+            ty: ty,
+            value: value,
+        })
+    }
+
+    // Field = Type IDENT .
+    fn parse_field(&mut self,fields: &[Field]) -> Result<Field> {
+        let doc = self.peek().doc.clone();
+        let ty = self.parse_type()?;
+        let taken: Vec<String> = fields.iter().map(|field| field.name.clone()).collect();
+        let name = self.parse_name("field",&taken)?;
+        Ok(Field {
+            doc: doc,
+            name: name,
+            ty: ty,
+        })
+    }
+
+    // Struct = `struct` IDENT `{` { Field | Const } `}` .
+    fn parse_struct(&mut self,doc: Vec<String>,taken: &[String]) -> Result<Item> {
+        let name = self.parse_name("type",taken)?;
+        self.item = name.clone();
+        self.expect_punct('{')?;
+        let mut fields = Vec::<Field>::new();
+        let mut consts = Vec::<Const>::new();
+        while !self.parse_punct('}') {
+            if self.is_ident("const") {
+                let taken: Vec<String> = consts.iter().map(|c| c.name.clone()).collect();
+                consts.push(self.parse_const(&taken)?);
+            }
+            else if self.peek().token == Token::End {
+                return self.expected("`}`");
+            }
+            else {
+                let field = self.parse_field(&fields)?;
+                fields.push(field);
+            }
+        }
+        Ok(Item {
+            doc: doc,
+            name: name,
+            kind: ItemKind::Struct(fields),
+            consts: consts,
+        })
+    }
+
+    // Enum = `enum` IDENT `{` { Variant | Const } `}` .
+    // Variant = IDENT [ `{` { Field } `}` ] .
+    fn parse_enum(&mut self,doc: Vec<String>,taken: &[String]) -> Result<Item> {
+        let name = self.parse_name("type",taken)?;
+        self.item = name.clone();
+        self.expect_punct('{')?;
+        let mut variants = Vec::<Variant>::new();
+        let mut consts = Vec::<Const>::new();
+        while !self.parse_punct('}') {
+            if self.is_ident("const") {
+                let taken: Vec<String> = consts.iter().map(|c| c.name.clone()).collect();
+                consts.push(self.parse_const(&taken)?);
+                continue;
+            }
+            let doc = self.peek().doc.clone();
+            let taken: Vec<String> = variants.iter().map(|v| v.name.clone()).collect();
+            let name = self.parse_name("variant",&taken)?;
+            let mut fields = Vec::<Field>::new();
+            if self.parse_punct('{') {
+                while !self.parse_punct('}') {
+                    if self.peek().token == Token::End {
+                        return self.expected("`}`");
+                    }
+                    let field = self.parse_field(&fields)?;
+                    fields.push(field);
+                }
+            }
+            variants.push(Variant {
+                doc: doc,
+                name: name,
+                fields: fields,
+            });
+        }
+        Ok(Item {
+            doc: doc,
+            name: name,
+            kind: ItemKind::Enum(variants),
+            consts: consts,
+        })
+    }
+
+    // Schema = { Struct | Enum | Const } .
+    pub(crate) fn parse_schema(&mut self) -> Result<Schema> {
+        let mut schema = Schema::default();
+        loop {
+            let mut taken: Vec<String> = schema.items.iter().map(|item| item.name.clone()).collect();
+            taken.extend(schema.consts.iter().map(|c| c.name.clone()));
+            if self.is_ident("struct") {
+                let doc = self.next().doc.clone();
+                let item = self.parse_struct(doc,&taken)?;
+                schema.items.push(item);
+            }
+            else if self.is_ident("enum") {
+                let doc = self.next().doc.clone();
+                let item = self.parse_enum(doc,&taken)?;
+                schema.items.push(item);
+            }
+            else if self.is_ident("const") {
+                let c = self.parse_const(&taken)?;
+                schema.consts.push(c);
+            }
+            else if self.peek().token == Token::End {
+                return Ok(schema);
+            }
+            else {
+                return self.expected("`struct`, `enum` or `const`");
+            }
+        }
+    }
+}
+
+/// Words that can't be used as names in schema files.
+const KEYWORDS: [&str; 6] = ["struct","enum","const","map","set","string"];
+
+// a named type, or an option of one, is stored inline
+fn is_by_value(ty: &Type) -> bool {
+    match ty {
+        Type::Named(_) => true,
+        Type::Option(ty) => is_by_value(ty),
+        _ => false,
+    }
+}
+
+// whether item `from` stores item `to` inline, directly or through other items
+fn contains(references: &[Reference],from: &str,to: &str,visited: &mut Vec<String>) -> bool {
+    if from == to {
+        return true;
+    }
+    if visited.iter().any(|item| item == from) {
+        return false;
+    }
+    visited.push(from.to_string());
+    references.iter().filter(|reference| reference.by_value && (reference.item == from)).any(|reference| contains(references,&reference.name,to,visited))
+}
+
+/// Check that every named type in `references` is an item in one of `schemas`,
+/// and that no item contains itself, which would make it infinitely large.
+pub(crate) fn resolve(schemas: &[&Schema],references: &[Reference]) -> Result<()> {
+    for reference in references {
+        if !schemas.iter().any(|schema| schema.item(&reference.name).is_some()) {
+            return Err(Error::Parse {
+                file: reference.file.clone(),
+                line: reference.line,
+                column: reference.column,
+                message: format!("unknown type `{}`",reference.name),
+            });
+        }
+    }
+    for reference in references.iter().filter(|reference| reference.by_value) {
+        if contains(references,&reference.name,&reference.item,&mut Vec::new()) {
+            return Err(Error::Parse {
+                file: reference.file.clone(),
+                line: reference.line,
+                column: reference.column,
+                message: format!("`{}` would contain itself, refer to `{}` through a list or map instead",reference.item,reference.name),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Parse the schema in `source`, read from `file`.
+///
+/// Every named type has to be defined in the same source. Errors carry
+/// `file` and the line and column where parsing failed.
+pub fn parse(file: &str,source: &str) -> Result<Schema> {
+    let (schema,references) = parse_unresolved(file,source)?;
+    resolve(&[&schema],&references)?;
+    Ok(schema)
+}
+
+// parse, leaving the named types to be checked against other files too
+pub(crate) fn parse_unresolved(file: &str,source: &str) -> Result<(Schema,Vec<Reference>)> {
+    let mut parser = Parser::new(file,source)?;
+    let schema = parser.parse_schema()?;
+    let mut references = parser.references;
+    for reference in &mut references {
+        reference.file = file.to_string();
+    }
+    Ok((schema,references))
+}
@@ -0,0 +1,359 @@
+// Echidna - Codec - Build
+
+use crate::*;
+
+// names that can't be used as Python identifiers, or would hide the methods of the generated classes
+const PYTHON_RESERVED: [&str; 37] = [
+    "False","None","True","and","as","assert","async","await","break","class",
+    "continue","def","del","elif","else","except","finally","for","from","global",
+    "if","import","in","is","lambda","nonlocal","not","or","pass","raise",
+    "return","try","while","with","yield","encode","decode",
+];
+
+// helpers shared by all generated classes
+const PRELUDE: &str = r#"from __future__ import annotations
+
+import dataclasses
+import struct
+import typing
+
+
+class DecodeError(Exception):
+    """Bytes that don't decode to the expected type."""
+
+
+_BOOL = struct.Struct("<?")
+_U8 = struct.Struct("<B")
+_I8 = struct.Struct("<b")
+_U16 = struct.Struct("<H")
+_I16 = struct.Struct("<h")
+_U32 = struct.Struct("<I")
+_I32 = struct.Struct("<i")
+_U64 = struct.Struct("<Q")
+_I64 = struct.Struct("<q")
+_F32 = struct.Struct("<f")
+_F64 = struct.Struct("<d")
+
+
+class _Reader:
+    __slots__ = ("data", "pos")
+
+    def __init__(self, data):
+        self.data = data
+        self.pos = 0
+
+    def unpack(self, s):
+        if self.pos + s.size > len(self.data):
+            raise DecodeError("buffer truncated")
+        (value,) = s.unpack_from(self.data, self.pos)
+        self.pos += s.size
+        return value
+
+    def array(self, code):
+        n = self.unpack(_U32)
+        size = n * struct.calcsize(code)
+        if self.pos + size > len(self.data):
+            raise DecodeError("buffer truncated")
+        values = list(struct.unpack_from("<%d%s" % (n, code), self.data, self.pos))
+        self.pos += size
+        return values
+
+    def string(self):
+        n = self.unpack(_U32)
+        if self.pos + n > len(self.data):
+            raise DecodeError("buffer truncated")
+        s = bytes(self.data[self.pos:self.pos + n]).decode("utf-8", "replace")
+        self.pos += n
+        return s
+
+
+def _encode_string(out, s):
+    b = s.encode("utf-8")
+    out += _U32.pack(len(b))
+    out += b
+
+
+class _Codec:
+    """Encoding and decoding, the same bytes as the Rust `Codec` impls."""
+
+    def encode(self) -> bytes:
+        out = bytearray()
+        self._encode(out)
+        return bytes(out)
+
+    @classmethod
+    def decode(cls, data) -> typing.Tuple[int, typing.Any]:
+        """Decode from the start of `data`, returns the number of bytes used and the value."""
+        r = _Reader(data)
+        value = cls._decode(r)
+        return r.pos, value
+"#;
+
+fn python_name(name: &str) -> String {
+    if PYTHON_RESERVED.contains(&name) {
+        format!("{}_",name)
+    }
+    else {
+        name.to_string()
+    }
+}
+
+fn python_string(s: &str) -> String {
+    let mut r = "\"".to_string();
+    for c in s.chars() {
+        match c {
+            '"' => r += "\\\"",
+            '\\' => r += "\\\\",
+            '\n' => r += "\\n",
+            '\t' => r += "\\t",
+            c if (c as u32) < 0x20 => r += &format!("\\x{:02x}",c as u32),
+            c => r.push(c),
+        }
+    }
+    r += "\"";
+    r
+}
+
+fn python_value(value: &Value) -> String {
+    match value {
+        Value::Bool(true) => "True".to_string(),
+        Value::Bool(false) => "False".to_string(),
+        Value::Int(i) => i.to_string(),
+        Value::Float(x) => format!("{:?}",x),
+        Value::String(s) => python_string(s),
+    }
+}
+
+fn python_type(ty: &Type) -> String {
+    match ty {
+        Type::Bool => "bool".to_string(),
+        Type::F32 | Type::F64 => "float".to_string(),
+        Type::String => "str".to_string(),
+        Type::Vec(ty) => format!("typing.List[{}]",python_type(ty)),
+        Type::Set(ty) => format!("typing.Set[{}]",python_type(ty)),
+        Type::Map(key,value) => format!("typing.Dict[{},{}]",python_type(key),python_type(value)),
+        Type::Option(ty) => format!("typing.Optional[{}]",python_type(ty)),
+        Type::Named(name) => python_name(name),
+        _ => "int".to_string(),
+    }
+}
+
+// `struct` format character and the constant in `PRELUDE` for fixed size types
+fn struct_code(ty: &Type) -> Option<(char,&'static str)> {
+    Some(match ty {
+        Type::Bool => ('?',"_BOOL"),
+        Type::U8 => ('B',"_U8"),
+        Type::I8 => ('b',"_I8"),
+        Type::U16 => ('H',"_U16"),
+        Type::I16 => ('h',"_I16"),
+        Type::U32 => ('I',"_U32"),
+        Type::I32 => ('i',"_I32"),
+        Type::U64 => ('Q',"_U64"),
+        Type::I64 => ('q',"_I64"),
+        Type::F32 => ('f',"_F32"),
+        Type::F64 => ('d',"_F64"),
+        _ => return None,
+    })
+}
+
+// statements appending `expr` of type `ty` to `out`; `depth` keeps the loop variables of nested collections apart
+fn render_encode(ty: &Type,expr: &str,indent: &str,depth: usize) -> String {
+    let inner = format!("{}    ",indent);
+    match ty {
+        Type::String => format!("{}_encode_string(out, {})\n",indent,expr),
+        Type::Vec(item) | Type::Set(item) => {
+            let mut r = format!("{}out += _U32.pack(len({}))\n",indent,expr);
+            if let Some((code,_)) = struct_code(item) {
+                r += &format!("{}out += struct.pack(\"<%d{}\" % len({}), *{})\n",indent,code,expr,expr);
+            }
+            else {
+                r += &format!("{}for v{} in {}:\n",indent,depth,expr);
+                r += &render_encode(item,&format!("v{}",depth),&inner,depth + 1);
+            }
+            r
+        },
+        Type::Map(key,value) => {
+            let mut r = format!("{}out += _U32.pack(len({}))\n",indent,expr);
+            r += &format!("{}for k{}, v{} in {}.items():\n",indent,depth,depth,expr);
+            r += &render_encode(key,&format!("k{}",depth),&inner,depth + 1);
+            r += &render_encode(value,&format!("v{}",depth),&inner,depth + 1);
+            r
+        },
+        Type::Option(ty) => {
+            let mut r = format!("{}if {} is None:\n{}out.append(0)\n{}else:\n{}out.append(1)\n",indent,expr,inner,indent,inner);
+            r += &render_encode(ty,expr,&inner,depth);
+            r
+        },
+        Type::Named(_) => format!("{}{}._encode(out)\n",indent,expr),
+        ty => format!("{}out += {}.pack({})\n",indent,struct_code(ty).unwrap().1,expr),
+    }
+}
+
+// statements decoding a value of type `ty` from `r` into `target`
+fn render_decode(ty: &Type,target: &str,indent: &str,depth: usize) -> String {
+    let inner = format!("{}    ",indent);
+    match ty {
+        Type::String => format!("{}{} = r.string()\n",indent,target),
+        Type::Vec(item) | Type::Set(item) => {
+            let set = matches!(ty,Type::Set(_));
+            if let Some((code,_)) = struct_code(item) {
+                if set {
+                    format!("{}{} = set(r.array(\"{}\"))\n",indent,target,code)
+                }
+                else {
+                    format!("{}{} = r.array(\"{}\")\n",indent,target,code)
+                }
+            }
+            else {
+                let mut r = format!("{}{} = {}\n",indent,target,if set { "set()" } else { "[]" });
+                r += &format!("{}for _ in range(r.unpack(_U32)):\n",indent);
+                r += &render_decode(item,&format!("v{}",depth),&inner,depth + 1);
+                r += &format!("{}{}.{}(v{})\n",inner,target,if set { "add" } else { "append" },depth);
+                r
+            }
+        },
+        Type::Map(key,value) => {
+            let mut r = format!("{}{} = {{}}\n",indent,target);
+            r += &format!("{}for _ in range(r.unpack(_U32)):\n",indent);
+            r += &render_decode(key,&format!("k{}",depth),&inner,depth + 1);
+            r += &render_decode(value,&format!("v{}",depth),&inner,depth + 1);
+            r += &format!("{}{}[k{}] = v{}\n",inner,target,depth,depth);
+            r
+        },
+        Type::Option(ty) => {
+            let mut r = format!("{}t{} = r.unpack(_U8)\n",indent,depth);
+            r += &format!("{}if t{} == 0:\n{}{} = None\n",indent,depth,inner,target);
+            r += &format!("{}elif t{} == 1:\n",indent,depth);
+            r += &render_decode(ty,target,&inner,depth + 1);
+            r += &format!("{}else:\n{}raise DecodeError(\"invalid tag %d\" % t{})\n",indent,inner,depth);
+            r
+        },
+        Type::Named(name) => format!("{}{} = {}._decode(r)\n",indent,target,python_name(name)),
+        ty => format!("{}{} = r.unpack({})\n",indent,target,struct_code(ty).unwrap().1),
+    }
+}
+
+fn render_comment(doc: &[String],indent: &str) -> String {
+    let mut r = String::new();
+    for line in doc {
+        r += indent;
+        r += "#";
+        if !line.is_empty() {
+            r += " ";
+            r += line;
+        }
+        r += "\n";
+    }
+    r
+}
+
+fn render_docstring(doc: &[String],indent: &str) -> String {
+    if doc.is_empty() {
+        return String::new();
+    }
+    let text = doc.join(&format!("\n{}",indent)).replace("\"\"\"","\\\"\\\"\\\"");
+    format!("{}\"\"\"{}\"\"\"\n\n",indent,text)
+}
+
+fn render_consts(consts: &[Const],indent: &str) -> String {
+    let mut r = String::new();
+    for c in consts {
+        r += &render_comment(&c.doc,indent);
+        r += &format!("{}{} = {}\n",indent,python_name(&c.name),python_value(&c.value));
+    }
+    r
+}
+
+// dataclass fields, and the `_encode` method writing them after `tag`, if any
+fn render_fields(fields: &[Field],tag: Option<usize>) -> String {
+    let mut r = String::new();
+    for field in fields {
+        r += &render_comment(&field.doc,"    ");
+        r += &format!("    {}: {}\n",python_name(&field.name),python_type(&field.ty));
+    }
+    if !fields.is_empty() {
+        r += "\n";
+    }
+    r += "    def _encode(self, out):\n";
+    if let Some(tag) = tag {
+        r += &format!("        out += _U32.pack({})\n",tag);
+    }
+    else if fields.is_empty() {
+        r += "        pass\n";
+    }
+    for field in fields {
+        r += &render_encode(&field.ty,&format!("self.{}",python_name(&field.name)),"        ",0);
+    }
+    r
+}
+
+// statements decoding `fields` and returning `class` constructed from them
+fn render_construct(class: &str,fields: &[Field],indent: &str) -> String {
+    let mut r = String::new();
+    let mut args = Vec::<String>::new();
+    for field in fields {
+        let name = python_name(&field.name);
+        r += &render_decode(&field.ty,&format!("f_{}",name),indent,0);
+        args.push(format!("{}=f_{}",name,name));
+    }
+    r += &format!("{}return {}({})\n",indent,class,args.join(", "));
+    r
+}
+
+/// Python source for `schema`, a module with a dataclass for every item.
+///
+/// Every class gets `encode()`, returning `bytes`, and `decode(data)`,
+/// returning the number of bytes used and the value, like `Codec::decode`.
+/// Enum variants are subclasses of the enum, so `Shape.Circle(radius=1.0)`
+/// is a `Shape`. Names that are Python keywords get a trailing `_`. The
+/// module only uses the standard library.
+///
+/// Panics if a field has a type that only Rust has, see `Type::is_portable`.
+pub fn generate_python(schema: &Schema) -> String {
+    check_portable(schema,"Python");
+    let mut r = PRELUDE.to_string();
+    if !schema.consts.is_empty() {
+        r += "\n\n";
+        r += &render_consts(&schema.consts,"");
+    }
+    for item in &schema.items {
+        let name = python_name(&item.name);
+        match &item.kind {
+            ItemKind::Struct(fields) => {
+                r += &format!("\n\n@dataclasses.dataclass\nclass {}(_Codec):\n",name);
+                r += &render_docstring(&item.doc,"    ");
+                if !item.consts.is_empty() {
+                    r += &render_consts(&item.consts,"    ");
+                    r += "\n";
+                }
+                r += &render_fields(fields,None);
+                r += "\n    @classmethod\n    def _decode(cls, r):\n";
+                r += &render_construct("cls",fields,"        ");
+            },
+            ItemKind::Enum(variants) => {
+                r += &format!("\n\nclass {}(_Codec):\n",name);
+                r += &render_docstring(&item.doc,"    ");
+                if !item.consts.is_empty() {
+                    r += &render_consts(&item.consts,"    ");
+                    r += "\n";
+                }
+                r += "    @classmethod\n    def _decode(cls, r):\n        tag = r.unpack(_U32)\n";
+                for (i,variant) in variants.iter().enumerate() {
+                    r += &format!("        if tag == {}:\n",i);
+                    r += &render_construct(&format!("{}.{}",name,python_name(&variant.name)),&variant.fields,"            ");
+                }
+                r += "        raise DecodeError(\"invalid tag %d\" % tag)\n";
+                for (i,variant) in variants.iter().enumerate() {
+                    let variant_name = python_name(&variant.name);
+                    let class = format!("_{}_{}",name,variant_name);
+                    r += &format!("\n\n@dataclasses.dataclass\nclass {}({}):\n",class,name);
+                    r += &render_docstring(&variant.doc,"    ");
+                    r += &format!("    __qualname__ = \"{}.{}\"\n\n",name,variant_name);
+                    r += &render_fields(&variant.fields,Some(i));
+                    r += &format!("\n\n{}.{} = {}\n",name,variant_name,class);
+                }
+            },
+        }
+    }
+    r
+}
@@ -0,0 +1,152 @@
+// Echidna - Codec - Build
+
+use crate::*;
+
+// Rust keywords that have to be written as raw identifiers
+const RUST_KEYWORDS: [&str; 47] = [
+    "abstract","as","async","await","become","box","break","const","continue","do",
+    "dyn","else","enum","extern","false","final","fn","for","if","impl",
+    "in","let","loop","macro","match","mod","move","mut","override","priv",
+    "pub","ref","return","static","struct","trait","true","try","type","typeof",
+    "unsafe","unsized","use","virtual","where","while","yield",
+];
+
+// keywords that can't be raw identifiers either
+const PATH_KEYWORDS: [&str; 4] = ["crate","self","Self","super"];
+
+fn rust_name(name: &str) -> String {
+    if RUST_KEYWORDS.contains(&name) {
+        format!("r#{}",name)
+    }
+    else if PATH_KEYWORDS.contains(&name) {
+        format!("{}_",name)
+    }
+    else {
+        name.to_string()
+    }
+}
+
+// `krate` is the path to the `codec` crate, for its own types
+fn rust_type(ty: &Type,krate: &str) -> String {
+    match ty {
+        Type::Bool => "bool".to_string(),
+        Type::U8 => "u8".to_string(),
+        Type::I8 => "i8".to_string(),
+        Type::U16 => "u16".to_string(),
+        Type::I16 => "i16".to_string(),
+        Type::U32 => "u32".to_string(),
+        Type::I32 => "i32".to_string(),
+        Type::U64 => "u64".to_string(),
+        Type::I64 => "i64".to_string(),
+        Type::F32 => "f32".to_string(),
+        Type::F64 => "f64".to_string(),
+        Type::String => "::std::string::String".to_string(),
+        Type::Vec(ty) => format!("::std::vec::Vec<{}>",rust_type(ty,krate)),
+        Type::Set(ty) => format!("::std::collections::HashSet<{}>",rust_type(ty,krate)),
+        Type::Map(key,value) => format!("::std::collections::HashMap<{},{}>",rust_type(key,krate),rust_type(value,krate)),
+        Type::Option(ty) => format!("::core::option::Option<{}>",rust_type(ty,krate)),
+        Type::Named(name) => rust_name(name),
+        Type::F16 => format!("{}::F16",krate),
+        Type::BF16 => format!("{}::BF16",krate),
+        Type::Quantized { min,max,bits, } => format!("{}::Quantized<f32,{{ {} }},{{ {} }},{{ {} }}>",krate,min,max,bits),
+        Type::Bitset => format!("{}::Bitset",krate),
+        Type::Ipv4 => "::std::net::Ipv4Addr".to_string(),
+        Type::Ipv6 => "::std::net::Ipv6Addr".to_string(),
+        Type::Ip => "::std::net::IpAddr".to_string(),
+        Type::SocketV4 => "::std::net::SocketAddrV4".to_string(),
+        Type::SocketV6 => "::std::net::SocketAddrV6".to_string(),
+        Type::Socket => "::std::net::SocketAddr".to_string(),
+        Type::Checked(ty) => format!("{}::Checked<{}>",krate,rust_type(ty,krate)),
+        Type::Compressed(ty) => format!("{}::Compressed<{}>",krate,rust_type(ty,krate)),
+    }
+}
+
+fn render_doc(doc: &[String],indent: &str) -> String {
+    let mut r = String::new();
+    for line in doc {
+        r += indent;
+        r += "///";
+        if !line.is_empty() {
+            r += " ";
+            r += line;
+        }
+        r += "\n";
+    }
+    r
+}
+
+fn render_const(c: &Const,krate: &str,indent: &str,vis: &str) -> String {
+    let mut r = render_doc(&c.doc,indent);
+    let ty = match c.ty {
+        Type::String => "&str".to_string(),
+        _ => rust_type(&c.ty,krate),
+    };
+    let value = match &c.value {
+        Value::Float(x) if x.fract() == 0.0 && x.abs() < 1e15 => format!("{:.1}",x),
+        value => value.to_string(),
+    };
+    r += &format!("{}{}const {}: {} = {};\n",indent,vis,rust_name(&c.name),ty,value);
+    r
+}
+
+fn render_fields(fields: &[Field],krate: &str,indent: &str,vis: &str) -> String {
+    let mut r = String::new();
+    for field in fields {
+        r += &render_doc(&field.doc,indent);
+        r += &format!("{}{}{}: {},\n",indent,vis,rust_name(&field.name),rust_type(&field.ty,krate));
+    }
+    r
+}
+
+/// Rust source for `schema`, with a `#[derive(Codec)]` type for every item.
+///
+/// `krate` is the path to the `codec` crate, usually `::codec`. The types
+/// encode exactly like hand-written ones with the same fields.
+pub fn generate_rust(schema: &Schema,krate: &str) -> String {
+    let mut r = String::new();
+    for c in &schema.consts {
+        r += &render_const(c,krate,"","pub ");
+        r += "\n";
+    }
+    let derive = if krate == "::codec" {
+        "#[derive(Clone,Debug,PartialEq,::codec::Codec)]\n".to_string()
+    }
+    else {
+        format!("#[derive(Clone,Debug,PartialEq,{}::Codec)]\n#[codec(crate = \"{}\")]\n",krate,krate)
+    };
+    for item in &schema.items {
+        r += &render_doc(&item.doc,"");
+        r += &derive;
+        match &item.kind {
+            ItemKind::Struct(fields) => {
+                r += &format!("pub struct {} {{\n",rust_name(&item.name));
+                r += &render_fields(fields,krate,"    ","pub ");
+                r += "}\n";
+            },
+            ItemKind::Enum(variants) => {
+                r += &format!("pub enum {} {{\n",rust_name(&item.name));
+                for variant in variants {
+                    r += &render_doc(&variant.doc,"    ");
+                    if !variant.fields.is_empty() {
+                        r += &format!("    {} {{\n",rust_name(&variant.name));
+                        r += &render_fields(&variant.fields,krate,"        ","");
+                        r += "    },\n";
+                    }
+                    else {
+                        r += &format!("    {},\n",rust_name(&variant.name));
+                    }
+                }
+                r += "}\n";
+            },
+        }
+        if !item.consts.is_empty() {
+            r += &format!("\nimpl {} {{\n",rust_name(&item.name));
+            for c in &item.consts {
+                r += &render_const(c,krate,"    ","pub ");
+            }
+            r += "}\n";
+        }
+        r += "\n";
+    }
+    r
+}
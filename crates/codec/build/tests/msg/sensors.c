@@ -0,0 +1,507 @@
+// generated by codec-build from sensors.msg, do not edit
+
+#include "sensors.h"
+
+#include <string.h>
+
+#if defined(__GNUC__)
+#define CODEC_UNUSED __attribute__((unused))
+#else
+#define CODEC_UNUSED
+#endif
+
+#define CODEC_TRY(x) do { codec_result codec_r_ = (x); if (codec_r_ != CODEC_OK) { return codec_r_; } } while (0)
+
+// allocate `n` elements for `p` from the arena of reader `r`
+#define CODEC_ALLOC(p, n) do { if ((n) > 0) { (p) = codec_alloc(r->arena, sizeof(*(p)), (n)); if ((p) == NULL) { return CODEC_ARENA_FULL; } } else { (p) = NULL; } } while (0)
+
+typedef union {
+    uint64_t u;
+    double d;
+    void *p;
+} codec_align;
+
+typedef struct {
+    uint8_t *p;
+    uint8_t *end;
+} codec_writer;
+
+typedef struct {
+    const uint8_t *p;
+    const uint8_t *end;
+    codec_arena *arena;
+} codec_reader;
+
+static CODEC_UNUSED void *codec_alloc(codec_arena *arena, size_t size, size_t count) {
+    size_t pad;
+    void *p;
+    if (arena == NULL) {
+        return NULL;
+    }
+    pad = (sizeof(codec_align) - (uintptr_t)(arena->data + arena->used) % sizeof(codec_align)) % sizeof(codec_align);
+    if ((count > (arena->size - arena->used) / size) || (pad > arena->size - arena->used - count * size)) {
+        return NULL;
+    }
+    p = arena->data + arena->used + pad;
+    arena->used += pad + count * size;
+    return p;
+}
+
+static CODEC_UNUSED codec_result codec_put(codec_writer *w, uint64_t v, size_t n) {
+    size_t i;
+    if ((size_t)(w->end - w->p) < n) {
+        return CODEC_BUFFER_FULL;
+    }
+    for (i = 0; i < n; i++) {
+        w->p[i] = (uint8_t)(v >> (8 * i));
+    }
+    w->p += n;
+    return CODEC_OK;
+}
+
+static CODEC_UNUSED codec_result codec_get(codec_reader *r, uint64_t *v, size_t n) {
+    size_t i;
+    if ((size_t)(r->end - r->p) < n) {
+        return CODEC_TRUNCATED;
+    }
+    *v = 0;
+    for (i = 0; i < n; i++) {
+        *v |= (uint64_t)r->p[i] << (8 * i);
+    }
+    r->p += n;
+    return CODEC_OK;
+}
+
+#define CODEC_INTEGER(name, type, n) \
+    static CODEC_UNUSED codec_result codec_put_##name(codec_writer *w, type v) { \
+        return codec_put(w, (uint64_t)v, n); \
+    } \
+    static CODEC_UNUSED codec_result codec_get_##name(codec_reader *r, type *v) { \
+        uint64_t b; \
+        CODEC_TRY(codec_get(r, &b, n)); \
+        *v = (type)b; \
+        return CODEC_OK; \
+    }
+
+CODEC_INTEGER(u8, uint8_t, 1)
+CODEC_INTEGER(i8, int8_t, 1)
+CODEC_INTEGER(u16, uint16_t, 2)
+CODEC_INTEGER(i16, int16_t, 2)
+CODEC_INTEGER(u32, uint32_t, 4)
+CODEC_INTEGER(i32, int32_t, 4)
+CODEC_INTEGER(u64, uint64_t, 8)
+CODEC_INTEGER(i64, int64_t, 8)
+
+static CODEC_UNUSED codec_result codec_put_bool(codec_writer *w, bool v) {
+    return codec_put(w, v ? 1 : 0, 1);
+}
+
+static CODEC_UNUSED codec_result codec_get_bool(codec_reader *r, bool *v) {
+    uint64_t b;
+    CODEC_TRY(codec_get(r, &b, 1));
+    *v = b != 0;
+    return CODEC_OK;
+}
+
+static CODEC_UNUSED codec_result codec_put_f32(codec_writer *w, float v) {
+    uint32_t b;
+    memcpy(&b, &v, 4);
+    return codec_put(w, b, 4);
+}
+
+static CODEC_UNUSED codec_result codec_get_f32(codec_reader *r, float *v) {
+    uint64_t b;
+    uint32_t b32;
+    CODEC_TRY(codec_get(r, &b, 4));
+    b32 = (uint32_t)b;
+    memcpy(v, &b32, 4);
+    return CODEC_OK;
+}
+
+static CODEC_UNUSED codec_result codec_put_f64(codec_writer *w, double v) {
+    uint64_t b;
+    memcpy(&b, &v, 8);
+    return codec_put(w, b, 8);
+}
+
+static CODEC_UNUSED codec_result codec_get_f64(codec_reader *r, double *v) {
+    uint64_t b;
+    CODEC_TRY(codec_get(r, &b, 8));
+    memcpy(v, &b, 8);
+    return CODEC_OK;
+}
+
+static CODEC_UNUSED codec_result codec_put_string(codec_writer *w, const codec_string *s) {
+    CODEC_TRY(codec_put(w, s->len, 4));
+    if ((size_t)(w->end - w->p) < s->len) {
+        return CODEC_BUFFER_FULL;
+    }
+    if (s->len > 0) {
+        memcpy(w->p, s->data, s->len);
+    }
+    w->p += s->len;
+    return CODEC_OK;
+}
+
+static CODEC_UNUSED codec_result codec_get_string(codec_reader *r, codec_string *s) {
+    CODEC_TRY(codec_get_u32(r, &s->len));
+    if ((size_t)(r->end - r->p) < s->len) {
+        return CODEC_TRUNCATED;
+    }
+    s->data = codec_alloc(r->arena, 1, (size_t)s->len + 1);
+    if (s->data == NULL) {
+        return CODEC_ARENA_FULL;
+    }
+    memcpy(s->data, r->p, s->len);
+    s->data[s->len] = 0;
+    r->p += s->len;
+    return CODEC_OK;
+}
+
+// length of a collection whose elements take at least `min` bytes each
+static CODEC_UNUSED codec_result codec_get_count(codec_reader *r, uint32_t *len, size_t min) {
+    CODEC_TRY(codec_get_u32(r, len));
+    if ((min > 0) && (*len > (size_t)(r->end - r->p) / min)) {
+        return CODEC_TRUNCATED;
+    }
+    return CODEC_OK;
+}
+
+static codec_result enc_Header(codec_writer *w, const Header *v);
+static codec_result dec_Header(codec_reader *r, Header *v);
+static codec_result enc_Point(codec_writer *w, const Point *v);
+static codec_result dec_Point(codec_reader *r, Point *v);
+static codec_result enc_Scan(codec_writer *w, const Scan *v);
+static codec_result dec_Scan(codec_reader *r, Scan *v);
+static codec_result enc_Status(codec_writer *w, const Status *v);
+static codec_result dec_Status(codec_reader *r, Status *v);
+
+static codec_result enc_Header(codec_writer *w, const Header *v) {
+    CODEC_TRY(codec_put_u64(w, v->ts));
+    CODEC_TRY(codec_put_string(w, &v->frame));
+    CODEC_TRY(codec_put_u32(w, v->seq));
+    return CODEC_OK;
+}
+
+static codec_result dec_Header(codec_reader *r, Header *v) {
+    CODEC_TRY(codec_get_u64(r, &v->ts));
+    CODEC_TRY(codec_get_string(r, &v->frame));
+    CODEC_TRY(codec_get_u32(r, &v->seq));
+    return CODEC_OK;
+}
+
+codec_result Header_encode(const Header *value, uint8_t *buffer, size_t size, size_t *written) {
+    codec_writer w;
+    codec_result result;
+    w.p = buffer;
+    w.end = buffer + size;
+    result = enc_Header(&w, value);
+    if ((result == CODEC_OK) && (written != NULL)) {
+        *written = (size_t)(w.p - buffer);
+    }
+    return result;
+}
+
+codec_result Header_decode(Header *value, const uint8_t *buffer, size_t size, size_t *consumed, codec_arena *arena) {
+    codec_reader r;
+    codec_result result;
+    r.p = buffer;
+    r.end = buffer + size;
+    r.arena = arena;
+    result = dec_Header(&r, value);
+    if ((result == CODEC_OK) && (consumed != NULL)) {
+        *consumed = (size_t)(r.p - buffer);
+    }
+    return result;
+}
+
+size_t Header_size(const Header *value) {
+    size_t n = 0;
+    n += 8;
+    n += 4 + (size_t)value->frame.len;
+    n += 4;
+    return n;
+}
+
+static codec_result enc_Point(codec_writer *w, const Point *v) {
+    CODEC_TRY(codec_put_f32(w, v->x));
+    CODEC_TRY(codec_put_f32(w, v->y));
+    CODEC_TRY(codec_put_f32(w, v->z));
+    return CODEC_OK;
+}
+
+static codec_result dec_Point(codec_reader *r, Point *v) {
+    CODEC_TRY(codec_get_f32(r, &v->x));
+    CODEC_TRY(codec_get_f32(r, &v->y));
+    CODEC_TRY(codec_get_f32(r, &v->z));
+    return CODEC_OK;
+}
+
+codec_result Point_encode(const Point *value, uint8_t *buffer, size_t size, size_t *written) {
+    codec_writer w;
+    codec_result result;
+    w.p = buffer;
+    w.end = buffer + size;
+    result = enc_Point(&w, value);
+    if ((result == CODEC_OK) && (written != NULL)) {
+        *written = (size_t)(w.p - buffer);
+    }
+    return result;
+}
+
+codec_result Point_decode(Point *value, const uint8_t *buffer, size_t size, size_t *consumed, codec_arena *arena) {
+    codec_reader r;
+    codec_result result;
+    r.p = buffer;
+    r.end = buffer + size;
+    r.arena = arena;
+    result = dec_Point(&r, value);
+    if ((result == CODEC_OK) && (consumed != NULL)) {
+        *consumed = (size_t)(r.p - buffer);
+    }
+    return result;
+}
+
+size_t Point_size(const Point *value) {
+    size_t n = 0;
+    (void)value;
+    n += 4;
+    n += 4;
+    n += 4;
+    return n;
+}
+
+static codec_result enc_Scan(codec_writer *w, const Scan *v) {
+    CODEC_TRY(enc_Header(w, &v->header));
+    CODEC_TRY(codec_put_u32(w, v->points.len));
+    for (uint32_t i0 = 0; i0 < v->points.len; i0++) {
+        CODEC_TRY(enc_Point(w, &v->points.items[i0]));
+    }
+    CODEC_TRY(codec_put_u32(w, v->intensities.len));
+    for (uint32_t i0 = 0; i0 < v->intensities.len; i0++) {
+        CODEC_TRY(codec_put_u8(w, v->intensities.items[i0]));
+    }
+    CODEC_TRY(codec_put_u32(w, v->stats.len));
+    for (uint32_t i0 = 0; i0 < v->stats.len; i0++) {
+        CODEC_TRY(codec_put_string(w, &v->stats.keys[i0]));
+        CODEC_TRY(codec_put_f64(w, v->stats.values[i0]));
+    }
+    CODEC_TRY(codec_put_u32(w, v->rings.len));
+    for (uint32_t i0 = 0; i0 < v->rings.len; i0++) {
+        CODEC_TRY(codec_put_u16(w, v->rings.items[i0]));
+    }
+    if (v->reference == NULL) {
+        CODEC_TRY(codec_put_u8(w, 0));
+    }
+    else {
+        CODEC_TRY(codec_put_u8(w, 1));
+        CODEC_TRY(enc_Header(w, &(*v->reference)));
+    }
+    if (v->offsets == NULL) {
+        CODEC_TRY(codec_put_u8(w, 0));
+    }
+    else {
+        CODEC_TRY(codec_put_u8(w, 1));
+        CODEC_TRY(codec_put_u32(w, (*v->offsets).len));
+        for (uint32_t i0 = 0; i0 < (*v->offsets).len; i0++) {
+            CODEC_TRY(codec_put_i16(w, (*v->offsets).items[i0]));
+        }
+    }
+    CODEC_TRY(codec_put_bool(w, v->type));
+    return CODEC_OK;
+}
+
+static codec_result dec_Scan(codec_reader *r, Scan *v) {
+    CODEC_TRY(dec_Header(r, &v->header));
+    CODEC_TRY(codec_get_count(r, &v->points.len, 12));
+    CODEC_ALLOC(v->points.items, v->points.len);
+    for (uint32_t i0 = 0; i0 < v->points.len; i0++) {
+        CODEC_TRY(dec_Point(r, &v->points.items[i0]));
+    }
+    CODEC_TRY(codec_get_count(r, &v->intensities.len, 1));
+    CODEC_ALLOC(v->intensities.items, v->intensities.len);
+    for (uint32_t i0 = 0; i0 < v->intensities.len; i0++) {
+        CODEC_TRY(codec_get_u8(r, &v->intensities.items[i0]));
+    }
+    CODEC_TRY(codec_get_count(r, &v->stats.len, 12));
+    CODEC_ALLOC(v->stats.keys, v->stats.len);
+    CODEC_ALLOC(v->stats.values, v->stats.len);
+    for (uint32_t i0 = 0; i0 < v->stats.len; i0++) {
+        CODEC_TRY(codec_get_string(r, &v->stats.keys[i0]));
+        CODEC_TRY(codec_get_f64(r, &v->stats.values[i0]));
+    }
+    CODEC_TRY(codec_get_count(r, &v->rings.len, 2));
+    CODEC_ALLOC(v->rings.items, v->rings.len);
+    for (uint32_t i0 = 0; i0 < v->rings.len; i0++) {
+        CODEC_TRY(codec_get_u16(r, &v->rings.items[i0]));
+    }
+    {
+        uint8_t t0;
+        CODEC_TRY(codec_get_u8(r, &t0));
+        if (t0 == 0) {
+            v->reference = NULL;
+        }
+        else if (t0 == 1) {
+            CODEC_ALLOC(v->reference, 1);
+            CODEC_TRY(dec_Header(r, &(*v->reference)));
+        }
+        else {
+            return CODEC_INVALID_TAG;
+        }
+    }
+    {
+        uint8_t t0;
+        CODEC_TRY(codec_get_u8(r, &t0));
+        if (t0 == 0) {
+            v->offsets = NULL;
+        }
+        else if (t0 == 1) {
+            CODEC_ALLOC(v->offsets, 1);
+            CODEC_TRY(codec_get_count(r, &(*v->offsets).len, 2));
+            CODEC_ALLOC((*v->offsets).items, (*v->offsets).len);
+            for (uint32_t i1 = 0; i1 < (*v->offsets).len; i1++) {
+                CODEC_TRY(codec_get_i16(r, &(*v->offsets).items[i1]));
+            }
+        }
+        else {
+            return CODEC_INVALID_TAG;
+        }
+    }
+    CODEC_TRY(codec_get_bool(r, &v->type));
+    return CODEC_OK;
+}
+
+codec_result Scan_encode(const Scan *value, uint8_t *buffer, size_t size, size_t *written) {
+    codec_writer w;
+    codec_result result;
+    w.p = buffer;
+    w.end = buffer + size;
+    result = enc_Scan(&w, value);
+    if ((result == CODEC_OK) && (written != NULL)) {
+        *written = (size_t)(w.p - buffer);
+    }
+    return result;
+}
+
+codec_result Scan_decode(Scan *value, const uint8_t *buffer, size_t size, size_t *consumed, codec_arena *arena) {
+    codec_reader r;
+    codec_result result;
+    r.p = buffer;
+    r.end = buffer + size;
+    r.arena = arena;
+    result = dec_Scan(&r, value);
+    if ((result == CODEC_OK) && (consumed != NULL)) {
+        *consumed = (size_t)(r.p - buffer);
+    }
+    return result;
+}
+
+size_t Scan_size(const Scan *value) {
+    size_t n = 0;
+    n += Header_size(&value->header);
+    n += 4;
+    for (uint32_t i0 = 0; i0 < value->points.len; i0++) {
+        n += Point_size(&value->points.items[i0]);
+    }
+    n += 4 + (size_t)value->intensities.len * 1;
+    n += 4;
+    for (uint32_t i0 = 0; i0 < value->stats.len; i0++) {
+        n += 4 + (size_t)value->stats.keys[i0].len;
+        n += 8;
+    }
+    n += 4 + (size_t)value->rings.len * 2;
+    n += 1;
+    if (value->reference != NULL) {
+        n += Header_size(&(*value->reference));
+    }
+    n += 1;
+    if (value->offsets != NULL) {
+        n += 4 + (size_t)(*value->offsets).len * 2;
+    }
+    n += 1;
+    return n;
+}
+
+static codec_result enc_Status(codec_writer *w, const Status *v) {
+    CODEC_TRY(codec_put_u32(w, (uint32_t)v->tag));
+    switch (v->tag) {
+        case Status_Idle:
+            break;
+        case Status_Recording:
+            CODEC_TRY(codec_put_string(w, &v->as.Recording.path));
+            CODEC_TRY(codec_put_u64(w, v->as.Recording.bytes));
+            break;
+        case Status_Failed:
+            CODEC_TRY(codec_put_i32(w, v->as.Failed.code));
+            break;
+        default:
+            return CODEC_INVALID_TAG;
+    }
+    return CODEC_OK;
+}
+
+static codec_result dec_Status(codec_reader *r, Status *v) {
+    uint32_t tag;
+    CODEC_TRY(codec_get_u32(r, &tag));
+    switch (tag) {
+        case Status_Idle:
+            v->tag = Status_Idle;
+            break;
+        case Status_Recording:
+            v->tag = Status_Recording;
+            CODEC_TRY(codec_get_string(r, &v->as.Recording.path));
+            CODEC_TRY(codec_get_u64(r, &v->as.Recording.bytes));
+            break;
+        case Status_Failed:
+            v->tag = Status_Failed;
+            CODEC_TRY(codec_get_i32(r, &v->as.Failed.code));
+            break;
+        default:
+            return CODEC_INVALID_TAG;
+    }
+    return CODEC_OK;
+}
+
+codec_result Status_encode(const Status *value, uint8_t *buffer, size_t size, size_t *written) {
+    codec_writer w;
+    codec_result result;
+    w.p = buffer;
+    w.end = buffer + size;
+    result = enc_Status(&w, value);
+    if ((result == CODEC_OK) && (written != NULL)) {
+        *written = (size_t)(w.p - buffer);
+    }
+    return result;
+}
+
+codec_result Status_decode(Status *value, const uint8_t *buffer, size_t size, size_t *consumed, codec_arena *arena) {
+    codec_reader r;
+    codec_result result;
+    r.p = buffer;
+    r.end = buffer + size;
+    r.arena = arena;
+    result = dec_Status(&r, value);
+    if ((result == CODEC_OK) && (consumed != NULL)) {
+        *consumed = (size_t)(r.p - buffer);
+    }
+    return result;
+}
+
+size_t Status_size(const Status *value) {
+    size_t n = 0;
+    n += 4;
+    switch (value->tag) {
+        case Status_Idle:
+            break;
+        case Status_Recording:
+            n += 4 + (size_t)value->as.Recording.path.len;
+            n += 8;
+            break;
+        case Status_Failed:
+            n += 4;
+            break;
+        default:
+            break;
+    }
+    return n;
+}
@@ -0,0 +1,163 @@
+// generated by codec-build from sensors.msg, do not edit
+
+#ifndef SENSORS_H
+#define SENSORS_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifndef CODEC_RUNTIME_H
+#define CODEC_RUNTIME_H
+
+typedef enum {
+    CODEC_OK = 0,
+    // The buffer ends before the encoded value does.
+    CODEC_TRUNCATED = 1,
+    // Unknown enum variant or option tag.
+    CODEC_INVALID_TAG = 2,
+    // The value doesn't fit in the buffer to encode into.
+    CODEC_BUFFER_FULL = 3,
+    // The strings and arrays of a decoded value don't fit in the arena.
+    CODEC_ARENA_FULL = 4,
+} codec_result;
+
+// UTF-8 bytes, not counting the 0 that decoding puts after them.
+typedef struct {
+    char *data;
+    uint32_t len;
+} codec_string;
+
+// Caller memory that decoding places strings, arrays and optional values in.
+typedef struct {
+    uint8_t *data;
+    size_t size;
+    size_t used;
+} codec_arena;
+
+#endif
+
+// Highest number of points in a `Scan`.
+#define MAX_POINTS 100000u
+
+typedef struct Header Header;
+typedef struct Point Point;
+typedef struct Scan Scan;
+typedef struct Status Status;
+typedef struct sensors_vec_Point sensors_vec_Point;
+typedef struct sensors_vec_u8 sensors_vec_u8;
+typedef struct sensors_map_string_f64 sensors_map_string_f64;
+typedef struct sensors_set_u16 sensors_set_u16;
+typedef struct sensors_vec_i16 sensors_vec_i16;
+
+struct sensors_vec_Point {
+    Point *items;
+    uint32_t len;
+};
+
+struct sensors_vec_u8 {
+    uint8_t *items;
+    uint32_t len;
+};
+
+struct sensors_map_string_f64 {
+    codec_string *keys;
+    double *values;
+    uint32_t len;
+};
+
+struct sensors_set_u16 {
+    uint16_t *items;
+    uint32_t len;
+};
+
+struct sensors_vec_i16 {
+    int16_t *items;
+    uint32_t len;
+};
+
+// Where a reading was taken.
+struct Header {
+    // Microseconds since the epoch.
+    uint64_t ts;
+    codec_string frame;
+    uint32_t seq;
+};
+
+struct Point {
+    float x;
+    float y;
+    float z;
+};
+
+#define Scan_MIN_RANGE 0.5f
+#define Scan_MAX_RANGE 100.0f
+#define Scan_UNIT "m"
+
+// One sweep of a range sensor.
+struct Scan {
+    Header header;
+    sensors_vec_Point points;
+    sensors_vec_u8 intensities;
+    sensors_map_string_f64 stats;
+    sensors_set_u16 rings;
+    Header *reference;
+    sensors_vec_i16 *offsets;
+    bool type;
+};
+
+#define Status_UNKNOWN (-1)
+
+typedef enum {
+    Status_Idle = 0,
+    // Recording to a file.
+    Status_Recording = 1,
+    Status_Failed = 2,
+} Status_tag;
+
+struct Status {
+    Status_tag tag;
+    union {
+        struct {
+            codec_string path;
+            uint64_t bytes;
+        } Recording;
+        struct {
+            int32_t code;
+        } Failed;
+    } as;
+};
+
+// Encode `value` into `buffer` of `size` bytes, setting `*written` if it isn't NULL.
+codec_result Header_encode(const Header *value, uint8_t *buffer, size_t size, size_t *written);
+// Decode from `buffer` of `size` bytes, setting `*consumed` if it isn't NULL.
+// Strings, arrays and optional values are placed in `arena`.
+codec_result Header_decode(Header *value, const uint8_t *buffer, size_t size, size_t *consumed, codec_arena *arena);
+// Number of bytes `value` encodes to.
+size_t Header_size(const Header *value);
+
+// Encode `value` into `buffer` of `size` bytes, setting `*written` if it isn't NULL.
+codec_result Point_encode(const Point *value, uint8_t *buffer, size_t size, size_t *written);
+// Decode from `buffer` of `size` bytes, setting `*consumed` if it isn't NULL.
+// Strings, arrays and optional values are placed in `arena`.
+codec_result Point_decode(Point *value, const uint8_t *buffer, size_t size, size_t *consumed, codec_arena *arena);
+// Number of bytes `value` encodes to.
+size_t Point_size(const Point *value);
+
+// Encode `value` into `buffer` of `size` bytes, setting `*written` if it isn't NULL.
+codec_result Scan_encode(const Scan *value, uint8_t *buffer, size_t size, size_t *written);
+// Decode from `buffer` of `size` bytes, setting `*consumed` if it isn't NULL.
+// Strings, arrays and optional values are placed in `arena`.
+codec_result Scan_decode(Scan *value, const uint8_t *buffer, size_t size, size_t *consumed, codec_arena *arena);
+// Number of bytes `value` encodes to.
+size_t Scan_size(const Scan *value);
+
+// Encode `value` into `buffer` of `size` bytes, setting `*written` if it isn't NULL.
+codec_result Status_encode(const Status *value, uint8_t *buffer, size_t size, size_t *written);
+// Decode from `buffer` of `size` bytes, setting `*consumed` if it isn't NULL.
+// Strings, arrays and optional values are placed in `arena`.
+codec_result Status_decode(Status *value, const uint8_t *buffer, size_t size, size_t *consumed, codec_arena *arena);
+// Number of bytes `value` encodes to.
+size_t Status_size(const Status *value);
+
+#endif // SENSORS_H
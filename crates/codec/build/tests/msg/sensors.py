@@ -0,0 +1,259 @@
+# generated by codec-build from sensors.msg, do not edit
+
+from __future__ import annotations
+
+import dataclasses
+import struct
+import typing
+
+
+class DecodeError(Exception):
+    """Bytes that don't decode to the expected type."""
+
+
+_BOOL = struct.Struct("<?")
+_U8 = struct.Struct("<B")
+_I8 = struct.Struct("<b")
+_U16 = struct.Struct("<H")
+_I16 = struct.Struct("<h")
+_U32 = struct.Struct("<I")
+_I32 = struct.Struct("<i")
+_U64 = struct.Struct("<Q")
+_I64 = struct.Struct("<q")
+_F32 = struct.Struct("<f")
+_F64 = struct.Struct("<d")
+
+
+class _Reader:
+    __slots__ = ("data", "pos")
+
+    def __init__(self, data):
+        self.data = data
+        self.pos = 0
+
+    def unpack(self, s):
+        if self.pos + s.size > len(self.data):
+            raise DecodeError("buffer truncated")
+        (value,) = s.unpack_from(self.data, self.pos)
+        self.pos += s.size
+        return value
+
+    def array(self, code):
+        n = self.unpack(_U32)
+        size = n * struct.calcsize(code)
+        if self.pos + size > len(self.data):
+            raise DecodeError("buffer truncated")
+        values = list(struct.unpack_from("<%d%s" % (n, code), self.data, self.pos))
+        self.pos += size
+        return values
+
+    def string(self):
+        n = self.unpack(_U32)
+        if self.pos + n > len(self.data):
+            raise DecodeError("buffer truncated")
+        s = bytes(self.data[self.pos:self.pos + n]).decode("utf-8", "replace")
+        self.pos += n
+        return s
+
+
+def _encode_string(out, s):
+    b = s.encode("utf-8")
+    out += _U32.pack(len(b))
+    out += b
+
+
+class _Codec:
+    """Encoding and decoding, the same bytes as the Rust `Codec` impls."""
+
+    def encode(self) -> bytes:
+        out = bytearray()
+        self._encode(out)
+        return bytes(out)
+
+    @classmethod
+    def decode(cls, data) -> typing.Tuple[int, typing.Any]:
+        """Decode from the start of `data`, returns the number of bytes used and the value."""
+        r = _Reader(data)
+        value = cls._decode(r)
+        return r.pos, value
+
+
+# Highest number of points in a `Scan`.
+MAX_POINTS = 100000
+
+
+@dataclasses.dataclass
+class Header(_Codec):
+    """Where a reading was taken."""
+
+    # Microseconds since the epoch.
+    ts: int
+    frame: str
+    seq: int
+
+    def _encode(self, out):
+        out += _U64.pack(self.ts)
+        _encode_string(out, self.frame)
+        out += _U32.pack(self.seq)
+
+    @classmethod
+    def _decode(cls, r):
+        f_ts = r.unpack(_U64)
+        f_frame = r.string()
+        f_seq = r.unpack(_U32)
+        return cls(ts=f_ts, frame=f_frame, seq=f_seq)
+
+
+@dataclasses.dataclass
+class Point(_Codec):
+    x: float
+    y: float
+    z: float
+
+    def _encode(self, out):
+        out += _F32.pack(self.x)
+        out += _F32.pack(self.y)
+        out += _F32.pack(self.z)
+
+    @classmethod
+    def _decode(cls, r):
+        f_x = r.unpack(_F32)
+        f_y = r.unpack(_F32)
+        f_z = r.unpack(_F32)
+        return cls(x=f_x, y=f_y, z=f_z)
+
+
+@dataclasses.dataclass
+class Scan(_Codec):
+    """One sweep of a range sensor."""
+
+    MIN_RANGE = 0.5
+    MAX_RANGE = 100.0
+    UNIT = "m"
+
+    header: Header
+    points: typing.List[Point]
+    intensities: typing.List[int]
+    stats: typing.Dict[str,float]
+    rings: typing.Set[int]
+    reference: typing.Optional[Header]
+    offsets: typing.Optional[typing.List[int]]
+    type: bool
+
+    def _encode(self, out):
+        self.header._encode(out)
+        out += _U32.pack(len(self.points))
+        for v0 in self.points:
+            v0._encode(out)
+        out += _U32.pack(len(self.intensities))
+        out += struct.pack("<%dB" % len(self.intensities), *self.intensities)
+        out += _U32.pack(len(self.stats))
+        for k0, v0 in self.stats.items():
+            _encode_string(out, k0)
+            out += _F64.pack(v0)
+        out += _U32.pack(len(self.rings))
+        out += struct.pack("<%dH" % len(self.rings), *self.rings)
+        if self.reference is None:
+            out.append(0)
+        else:
+            out.append(1)
+            self.reference._encode(out)
+        if self.offsets is None:
+            out.append(0)
+        else:
+            out.append(1)
+            out += _U32.pack(len(self.offsets))
+            out += struct.pack("<%dh" % len(self.offsets), *self.offsets)
+        out += _BOOL.pack(self.type)
+
+    @classmethod
+    def _decode(cls, r):
+        f_header = Header._decode(r)
+        f_points = []
+        for _ in range(r.unpack(_U32)):
+            v0 = Point._decode(r)
+            f_points.append(v0)
+        f_intensities = r.array("B")
+        f_stats = {}
+        for _ in range(r.unpack(_U32)):
+            k0 = r.string()
+            v0 = r.unpack(_F64)
+            f_stats[k0] = v0
+        f_rings = set(r.array("H"))
+        t0 = r.unpack(_U8)
+        if t0 == 0:
+            f_reference = None
+        elif t0 == 1:
+            f_reference = Header._decode(r)
+        else:
+            raise DecodeError("invalid tag %d" % t0)
+        t0 = r.unpack(_U8)
+        if t0 == 0:
+            f_offsets = None
+        elif t0 == 1:
+            f_offsets = r.array("h")
+        else:
+            raise DecodeError("invalid tag %d" % t0)
+        f_type = r.unpack(_BOOL)
+        return cls(header=f_header, points=f_points, intensities=f_intensities, stats=f_stats, rings=f_rings, reference=f_reference, offsets=f_offsets, type=f_type)
+
+
+class Status(_Codec):
+    UNKNOWN = -1
+
+    @classmethod
+    def _decode(cls, r):
+        tag = r.unpack(_U32)
+        if tag == 0:
+            return Status.Idle()
+        if tag == 1:
+            f_path = r.string()
+            f_bytes = r.unpack(_U64)
+            return Status.Recording(path=f_path, bytes=f_bytes)
+        if tag == 2:
+            f_code = r.unpack(_I32)
+            return Status.Failed(code=f_code)
+        raise DecodeError("invalid tag %d" % tag)
+
+
+@dataclasses.dataclass
+class _Status_Idle(Status):
+    __qualname__ = "Status.Idle"
+
+    def _encode(self, out):
+        out += _U32.pack(0)
+
+
+Status.Idle = _Status_Idle
+
+
+@dataclasses.dataclass
+class _Status_Recording(Status):
+    """Recording to a file."""
+
+    __qualname__ = "Status.Recording"
+
+    path: str
+    bytes: int
+
+    def _encode(self, out):
+        out += _U32.pack(1)
+        _encode_string(out, self.path)
+        out += _U64.pack(self.bytes)
+
+
+Status.Recording = _Status_Recording
+
+
+@dataclasses.dataclass
+class _Status_Failed(Status):
+    __qualname__ = "Status.Failed"
+
+    code: int
+
+    def _encode(self, out):
+        out += _U32.pack(2)
+        out += _I32.pack(self.code)
+
+
+Status.Failed = _Status_Failed
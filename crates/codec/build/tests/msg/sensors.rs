@@ -0,0 +1,57 @@
+// generated by codec-build from sensors.msg, do not edit
+
+/// Highest number of points in a `Scan`.
+pub const MAX_POINTS: u32 = 100000;
+
+/// Where a reading was taken.
+#[derive(Clone,Debug,PartialEq,::codec::Codec)]
+pub struct Header {
+    /// Microseconds since the epoch.
+    pub ts: u64,
+    pub frame: ::std::string::String,
+    pub seq: u32,
+}
+
+#[derive(Clone,Debug,PartialEq,::codec::Codec)]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+/// One sweep of a range sensor.
+#[derive(Clone,Debug,PartialEq,::codec::Codec)]
+pub struct Scan {
+    pub header: Header,
+    pub points: ::std::vec::Vec<Point>,
+    pub intensities: ::std::vec::Vec<u8>,
+    pub stats: ::std::collections::HashMap<::std::string::String,f64>,
+    pub rings: ::std::collections::HashSet<u16>,
+    pub reference: ::core::option::Option<Header>,
+    pub offsets: ::core::option::Option<::std::vec::Vec<i16>>,
+    pub r#type: bool,
+}
+
+impl Scan {
+    pub const MIN_RANGE: f32 = 0.5;
+    pub const MAX_RANGE: f32 = 100.0;
+    pub const UNIT: &str = "m";
+}
+
+#[derive(Clone,Debug,PartialEq,::codec::Codec)]
+pub enum Status {
+    Idle,
+    /// Recording to a file.
+    Recording {
+        path: ::std::string::String,
+        bytes: u64,
+    },
+    Failed {
+        code: i32,
+    },
+}
+
+impl Status {
+    pub const UNKNOWN: i8 = -1;
+}
+
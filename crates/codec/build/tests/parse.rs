@@ -0,0 +1,70 @@
+// Echidna - Codec - Build - tests
+
+use codec::schema::*;
+
+fn error(source: &str) -> (usize,usize,String) {
+    match codec_build::parse("test.msg",source) {
+        Err(codec_build::Error::Parse { file,line,column,message }) => {
+            assert_eq!(file,"test.msg");
+            (line,column,message)
+        },
+        Err(e) => panic!("unexpected error {}",e),
+        Ok(_) => panic!("no error for {:?}",source),
+    }
+}
+
+#[test]
+fn test_parse() {
+    let schema = codec_build::parse("sensors.msg",include_str!("msg/sensors.msg")).unwrap();
+    assert_eq!(schema.consts.len(),1);
+    assert_eq!(schema.consts[0].doc,vec!["Highest number of points in a `Scan`.".to_string()]);
+    assert_eq!(schema.consts[0].value,Value::Int(100000));
+    let scan = schema.item("Scan").unwrap();
+    assert_eq!(scan.doc,vec!["One sweep of a range sensor.".to_string()]);
+    if let ItemKind::Struct(fields) = &scan.kind {
+        let types: Vec<String> = fields.iter().map(|field| field.ty.to_string()).collect();
+        assert_eq!(types,vec!["Header","Point[]","u8[]","map<string,f64>","set<u16>","Header?","i16[]?","bool"]);
+    }
+    else {
+        panic!("`Scan` is not a struct");
+    }
+    assert_eq!(scan.consts[1].value,Value::Float(100.0));
+    let header = schema.item("Header").unwrap();
+    if let ItemKind::Struct(fields) = &header.kind {
+        assert_eq!(fields[0].doc,vec!["Microseconds since the epoch.".to_string()]);
+        assert!(fields[2].doc.is_empty());
+    }
+    if let ItemKind::Enum(variants) = &schema.item("Status").unwrap().kind {
+        assert_eq!(variants.len(),3);
+        assert_eq!(variants[1].fields.len(),2);
+        assert_eq!(variants[1].doc,vec!["Recording to a file.".to_string()]);
+    }
+    else {
+        panic!("`Status` is not an enum");
+    }
+}
+
+#[test]
+fn test_parse_errors() {
+    assert_eq!(error("struct A {\n    u32 x\n    Missing y\n}\n"),(3,5,"unknown type `Missing`".to_string()));
+    assert_eq!(error("struct A {\n    u32 x\n    u32 x\n}\n"),(3,9,"field `x` is defined more than once".to_string()));
+    assert_eq!(error("struct A {}\nenum A {}\n"),(2,6,"type `A` is defined more than once".to_string()));
+    assert_eq!(error("struct A {\n    u32 x\n"),(3,1,"expected `}`, found end of file".to_string()));
+    assert_eq!(error("message A {}"),(1,1,"expected `struct`, `enum` or `const`, found `message`".to_string()));
+    assert_eq!(error("struct A {\n  map<f32,u8> m\n}"),(2,7,"`f32` can't be a map key or set item, only integers, `bool` and `string` can".to_string()));
+    assert_eq!(error("const u8 X = 256"),(1,14,"256 is out of range for `u8`".to_string()));
+    assert_eq!(error("const u8 X = \"a\""),(1,14,"`\"a\"` is not a `u8`".to_string()));
+    assert_eq!(error("const string X = \"abc"),(1,18,"unterminated string".to_string()));
+    assert_eq!(error("struct A {\n    u32 x;\n}"),(2,10,"unexpected character `;`".to_string()));
+    assert_eq!(error("struct u8 {}"),(1,8,"`u8` is a built-in type".to_string()));
+    assert_eq!(error("struct A { u32[ x }"),(1,17,"expected `]`, found `x`".to_string()));
+    assert_eq!(error("struct Pose {\n    u64 ts\n    Pose? parent\n}"),(3,5,"`Pose` would contain itself, refer to `Pose` through a list or map instead".to_string()));
+    assert_eq!(error("struct A {\n    B b\n}\nenum B {\n    Some { A? a }\n}"),(2,5,"`A` would contain itself, refer to `B` through a list or map instead".to_string()));
+    assert!(codec_build::parse("test.msg","struct Tree {\n    Tree[] children\n    map<string,Tree?> named\n    Tree[]? extra\n}").is_ok());
+}
+
+#[test]
+fn test_error_display() {
+    let e = codec_build::parse("msg/robot.msg","struct {}").unwrap_err();
+    assert_eq!(e.to_string(),"msg/robot.msg:1:8: expected type name, found `{`");
+}
@@ -0,0 +1,275 @@
+// Echidna - Codec - Build - tests
+
+use {
+    codec::{
+        *,
+        schema::*,
+    },
+    std::{
+        collections::{
+            HashMap,
+            HashSet,
+        },
+        io::Write,
+        path::Path,
+        process::{
+            Command,
+            Stdio,
+        },
+    },
+};
+
+#[allow(dead_code)]
+mod generated {
+    include!("msg/sensors.rs");
+}
+
+// the same types, derived in Rust
+mod derived {
+
+    use {
+        codec::*,
+        std::collections::{
+            HashMap,
+            HashSet,
+        },
+    };
+
+    /// Where a reading was taken.
+    #[derive(Codec)]
+    #[codec(schema)]
+    pub struct Header {
+        /// Microseconds since the epoch.
+        pub ts: u64,
+        pub frame: String,
+        pub seq: u32,
+    }
+
+    #[derive(Codec)]
+    #[codec(schema)]
+    pub struct Point {
+        pub x: f32,
+        pub y: f32,
+        pub z: f32,
+    }
+
+    /// One sweep of a range sensor.
+    #[derive(Codec)]
+    #[codec(schema)]
+    pub struct Scan {
+        pub header: Header,
+        pub points: Vec<Point>,
+        pub intensities: Vec<u8>,
+        pub stats: HashMap<String,f64>,
+        pub rings: HashSet<u16>,
+        pub reference: Option<Header>,
+        pub offsets: Option<Vec<i16>>,
+        pub r#type: bool,
+    }
+
+    #[derive(Codec)]
+    #[codec(schema)]
+    pub enum Status {
+        Idle,
+        /// Recording to a file.
+        Recording {
+            path: String,
+            bytes: u64,
+        },
+        Failed {
+            code: i32,
+        },
+    }
+}
+
+// decodes every `Type hex` line with `module`, and prints the value encoded again
+const ROUNDTRIP: &str = r#"
+import importlib
+import sys
+
+module = importlib.import_module(sys.argv[1])
+for line in sys.stdin:
+    name, data = line.split()
+    data = bytes.fromhex(data)
+    n, value = getattr(module, name).decode(data)
+    assert n == len(data), name
+    print(value.encode().hex())
+"#;
+
+fn python3() -> bool {
+    let found = Command::new("python3").arg("--version").output().map(|output| output.status.success()).unwrap_or(false);
+    if !found {
+        eprintln!("python3 not found, skipping");
+    }
+    found
+}
+
+// run `script` with `dir` on the Python path, returning the lines it prints
+fn run(dir: &Path,script: &str,args: &[&str],input: &str) -> Vec<String> {
+    let mut child = Command::new("python3")
+        .arg("-c").arg(script).args(args)
+        .env("PYTHONPATH",dir)
+        .env("PYTHONDONTWRITEBYTECODE","1")
+        .stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped())
+        .spawn().unwrap();
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success(),"{}",String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).unwrap().lines().map(|line| line.to_string()).collect()
+}
+
+fn hex(buffer: &[u8]) -> String {
+    buffer.iter().map(|b| format!("{:02x}",b)).collect()
+}
+
+fn unhex(s: &str) -> Vec<u8> {
+    (0..s.len()).step_by(2).map(|i| u8::from_str_radix(&s[i..i + 2],16).unwrap()).collect()
+}
+
+fn scan() -> generated::Scan {
+    let mut stats = HashMap::<String,f64>::new();
+    stats.insert("mean".to_string(),2.5);
+    stats.insert("max".to_string(),-1e300);
+    generated::Scan {
+        header: generated::Header { ts: u64::MAX, frame: "lidar ü".to_string(), seq: 7, },
+        points: vec![generated::Point { x: 1.0, y: -2.5, z: 3.25, },generated::Point { x: 0.0, y: 0.0, z: f32::MAX, }],
+        intensities: vec![0,9,255],
+        stats: stats,
+        rings: vec![3,65535].into_iter().collect::<HashSet<u16>>(),
+        reference: Some(generated::Header { ts: 0, frame: String::new(), seq: 1, }),
+        offsets: Some(vec![i16::MIN,-1,1]),
+        r#type: true,
+    }
+}
+
+#[test]
+fn test_generated_is_current() {
+    let schema = codec_build::parse("sensors.msg",include_str!("msg/sensors.msg")).unwrap();
+    let python = format!("# generated by codec-build from sensors.msg, do not edit\n\n{}",codec_build::generate_python(&schema));
+    assert_eq!(python,include_str!("msg/sensors.py"));
+}
+
+#[test]
+fn test_roundtrip() {
+    if !python3() {
+        return;
+    }
+    let scans = vec![
+        scan(),
+        generated::Scan {
+            header: generated::Header { ts: 1, frame: "a".to_string(), seq: 2, },
+            points: Vec::new(),
+            intensities: Vec::new(),
+            stats: HashMap::new(),
+            rings: HashSet::new(),
+            reference: None,
+            offsets: None,
+            r#type: false,
+        },
+    ];
+    let statuses = vec![
+        generated::Status::Idle,
+        generated::Status::Recording { path: "a.rec".to_string(), bytes: 12, },
+        generated::Status::Failed { code: -2, },
+    ];
+    let mut input = String::new();
+    for scan in &scans {
+        input += &format!("Scan {}\n",hex(&scan.encode_to_vec()));
+    }
+    for status in &statuses {
+        input += &format!("Status {}\n",hex(&status.encode_to_vec()));
+    }
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/msg");
+    let output = run(&dir,ROUNDTRIP,&["sensors"],&input);
+    assert_eq!(output.len(),scans.len() + statuses.len());
+    // sets and maps may come back in a different order, so compare the values
+    for (scan,line) in scans.iter().zip(output.iter()) {
+        let buffer = unhex(line);
+        assert_eq!(generated::Scan::decode(&buffer),Ok((buffer.len(),scan.clone())));
+    }
+    for (status,line) in statuses.iter().zip(output[scans.len()..].iter()) {
+        assert_eq!(&unhex(line),&status.encode_to_vec());
+    }
+
+    // values made in Python decode in Rust
+    let output = run(&dir,r#"
+import sensors
+scan = sensors.Scan(
+    header=sensors.Header(ts=2**64 - 1, frame="lidar ü", seq=7),
+    points=[sensors.Point(x=1.0, y=-2.5, z=3.25)],
+    intensities=[0, 9, 255],
+    stats={"mean": 2.5},
+    rings={3},
+    reference=None,
+    offsets=[-32768, 1],
+    type=True,
+)
+print(scan.encode().hex())
+print(sensors.Status.Recording(path="a.rec", bytes=12).encode().hex())
+assert isinstance(sensors.Status.Idle(), sensors.Status)
+assert (sensors.MAX_POINTS, sensors.Scan.MAX_RANGE, sensors.Scan.UNIT, sensors.Status.UNKNOWN) == (100000, 100.0, "m", -1)
+for data in ("", "0100000000", "03000000"):
+    try:
+        sensors.Status.decode(bytes.fromhex(data))
+        raise AssertionError(data)
+    except sensors.DecodeError:
+        pass
+"#,&[],"");
+    let mut stats = HashMap::<String,f64>::new();
+    stats.insert("mean".to_string(),2.5);
+    let scan = generated::Scan {
+        header: generated::Header { ts: u64::MAX, frame: "lidar ü".to_string(), seq: 7, },
+        points: vec![generated::Point { x: 1.0, y: -2.5, z: 3.25, }],
+        intensities: vec![0,9,255],
+        stats: stats,
+        rings: vec![3].into_iter().collect(),
+        reference: None,
+        offsets: Some(vec![-32768,1]),
+        r#type: true,
+    };
+    let buffer = unhex(&output[0]);
+    assert_eq!(generated::Scan::decode(&buffer),Ok((buffer.len(),scan)));
+    let buffer = unhex(&output[1]);
+    assert_eq!(generated::Status::decode(&buffer),Ok((buffer.len(),generated::Status::Recording { path: "a.rec".to_string(), bytes: 12, })));
+}
+
+#[test]
+fn test_derived_schema() {
+    // the derived types describe themselves like the schema file, without the constants
+    let mut parsed = codec_build::parse("sensors.msg",include_str!("msg/sensors.msg")).unwrap();
+    parsed.consts.clear();
+    for item in &mut parsed.items {
+        item.consts.clear();
+    }
+    let mut schema = Schema::of::<derived::Scan>();
+    assert_eq!(schema.items.iter().map(|item| item.name.as_str()).collect::<Vec<&str>>(),vec!["Scan","Header","Point"]);
+    schema.items.extend(Schema::of::<derived::Status>().items);
+    for item in &parsed.items {
+        assert_eq!(schema.item(&item.name),Some(item));
+    }
+    let named = Type::Named("Scan".to_string());
+    assert_eq!(parsed.fingerprint(&named),Schema::of::<derived::Scan>().fingerprint(&named));
+    if !python3() {
+        return;
+    }
+    let dir = std::env::temp_dir().join(format!("codec-build-python-{}",std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("derived.py"),codec_build::generate_python(&schema)).unwrap();
+    let status = derived::Status::Recording { path: "b.rec".to_string(), bytes: 1, }.encode_to_vec();
+    let output = run(&dir,ROUNDTRIP,&["derived"],&format!("Scan {}\nStatus {}\n",hex(&scan().encode_to_vec()),hex(&status)));
+    let buffer = unhex(&output[0]);
+    assert_eq!(generated::Scan::decode(&buffer),Ok((buffer.len(),scan())));
+    assert_eq!(unhex(&output[1]),status);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+#[should_panic(expected = "`socket` of `Beacon.from` can't be generated for Python")]
+fn test_rust_only_types() {
+    #[derive(Codec)]
+    #[codec(schema)]
+    struct Beacon {
+        from: std::net::SocketAddr,
+    }
+    codec_build::generate_python(&Schema::of::<Beacon>());
+}
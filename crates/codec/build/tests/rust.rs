@@ -0,0 +1,152 @@
+// Echidna - Codec - Build - tests
+
+use {
+    codec::*,
+    std::collections::{
+        HashMap,
+        HashSet,
+    },
+};
+
+mod generated {
+    include!("msg/sensors.rs");
+}
+
+// what someone would have written by hand
+mod manual {
+
+    use {
+        codec::*,
+        std::collections::{
+            HashMap,
+            HashSet,
+        },
+    };
+
+    #[derive(Codec)]
+    pub struct Header {
+        pub ts: u64,
+        pub frame: String,
+        pub seq: u32,
+    }
+
+    #[derive(Codec)]
+    pub struct Point {
+        pub x: f32,
+        pub y: f32,
+        pub z: f32,
+    }
+
+    #[derive(Codec)]
+    pub struct Scan {
+        pub header: Header,
+        pub points: Vec<Point>,
+        pub intensities: Vec<u8>,
+        pub stats: HashMap<String,f64>,
+        pub rings: HashSet<u16>,
+        pub reference: Option<Header>,
+        pub offsets: Option<Vec<i16>>,
+        pub kind: bool,
+    }
+
+    #[derive(Codec)]
+    pub enum Status {
+        Idle,
+        Recording {
+            path: String,
+            bytes: u64,
+        },
+        Failed {
+            code: i32,
+        },
+    }
+}
+
+#[test]
+fn test_generated_is_current() {
+    let schema = codec_build::parse("sensors.msg",include_str!("msg/sensors.msg")).unwrap();
+    let rust = format!("// generated by codec-build from sensors.msg, do not edit\n\n{}",codec_build::generate_rust(&schema,"::codec"));
+    assert_eq!(rust,include_str!("msg/sensors.rs"));
+}
+
+#[test]
+fn test_generated_matches_manual() {
+    let mut stats = HashMap::<String,f64>::new();
+    stats.insert("mean".to_string(),2.5);
+    let rings: HashSet<u16> = vec![3].into_iter().collect();
+    let scan = generated::Scan {
+        header: generated::Header { ts: 1, frame: "lidar".to_string(), seq: 7, },
+        points: vec![generated::Point { x: 1.0, y: 2.0, z: 3.0, }],
+        intensities: vec![9,8],
+        stats: stats.clone(),
+        rings: rings.clone(),
+        reference: None,
+        offsets: Some(vec![-1,1]),
+        r#type: true,
+    };
+    let manual = manual::Scan {
+        header: manual::Header { ts: 1, frame: "lidar".to_string(), seq: 7, },
+        points: vec![manual::Point { x: 1.0, y: 2.0, z: 3.0, }],
+        intensities: vec![9,8],
+        stats: stats,
+        rings: rings,
+        reference: None,
+        offsets: Some(vec![-1,1]),
+        kind: true,
+    };
+    let buffer = scan.encode_to_vec();
+    assert_eq!(buffer,manual.encode_to_vec());
+    assert_eq!(generated::Scan::decode(&buffer),Ok((buffer.len(),scan)));
+    let status = generated::Status::Recording { path: "a.rec".to_string(), bytes: 12, };
+    assert_eq!(status.encode_to_vec(),manual::Status::Recording { path: "a.rec".to_string(), bytes: 12, }.encode_to_vec());
+    assert_eq!(generated::Status::Failed { code: -2, }.encode_to_vec(),manual::Status::Failed { code: -2, }.encode_to_vec());
+    assert_eq!(generated::Status::Idle.encode_to_vec(),manual::Status::Idle.encode_to_vec());
+    assert_eq!(generated::MAX_POINTS,100000);
+    assert_eq!((generated::Scan::MIN_RANGE,generated::Scan::MAX_RANGE,generated::Scan::UNIT),(0.5,100.0,"m"));
+    assert_eq!(generated::Status::UNKNOWN,-1);
+}
+
+#[test]
+fn test_keywords() {
+    let schema = codec_build::parse("test.msg","struct A {\n    u8 yield\n    u8 try\n    u8 self\n}\n").unwrap();
+    let rust = codec_build::generate_rust(&schema,"::codec");
+    assert!(rust.contains("pub r#yield: u8,\n    pub r#try: u8,\n    pub self_: u8,\n"),"{}",rust);
+}
+
+#[test]
+fn test_builder() {
+    let dir = std::env::temp_dir().join(format!("codec-build-{}",std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let common = dir.join("common.msg");
+    let robot = dir.join("robot.msg");
+    std::fs::write(&common,"struct Id {\n    u64 value\n}\n").unwrap();
+    std::fs::write(&robot,"struct Robot {\n    Id id\n    Pose pose\n}\n").unwrap();
+    let result = codec_build::Builder::new().file(&common).file(&robot).out_dir(&dir).compile("robot.rs");
+    match result {
+        Err(codec_build::Error::Parse { file,line,column,message }) => {
+            assert_eq!(file,robot.display().to_string());
+            assert_eq!((line,column),(3,5));
+            assert_eq!(message,"unknown type `Pose`");
+        },
+        _ => panic!("expected parse error"),
+    }
+    std::fs::write(&robot,"struct Robot {\n    Id id\n}\n").unwrap();
+    codec_build::Builder::new().file(&common).file(&robot).crate_path("crate::wire").out_dir(&dir).compile("robot.rs").unwrap();
+    let rust = std::fs::read_to_string(dir.join("robot.rs")).unwrap();
+    assert!(rust.contains("#[codec(crate = \"crate::wire\")]\npub struct Robot {\n    pub id: Id,\n}"),"{}",rust);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[derive(Codec)]
+#[codec(schema)]
+struct Beacon {
+    from: std::net::SocketAddr,
+    data: Checked<Vec<u8>>,
+    level: F16,
+}
+
+#[test]
+fn test_codec_types() {
+    let rust = codec_build::generate_rust(&codec::schema::Schema::of::<Beacon>(),"::codec");
+    assert!(rust.contains("    pub from: ::std::net::SocketAddr,\n    pub data: ::codec::Checked<::std::vec::Vec<u8>>,\n    pub level: ::codec::F16,\n"),"{}",rust);
+}
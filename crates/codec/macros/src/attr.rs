@@ -0,0 +1,352 @@
+// Echidna - Codec - Macros
+
+use crate::*;
+
+pub(crate) enum CodecAttr {
+    Crate(String),
+    Transparent,
+    View,
+    Json,
+    Delta,
+    Schema,
+    FixedSize,
+    Name(String),
+    Topic(String),
+    Version(u32),
+    Quantize(Quantize),
+    Bits(Option<u32>),
+}
+
+/// Wire format of a `#[codec(quantize(...))]` field.
+pub(crate) enum Quantize {
+    F16,
+    BF16,
+    Range {
+        min: String,
+        max: String,
+        bits: String,
+    },
+}
+
+impl fmt::Display for Quantize {
+    fn fmt(&self,f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Quantize::F16 => write!(f,"f16"),
+            Quantize::BF16 => write!(f,"bf16"),
+            Quantize::Range { min,max,bits } => write!(f,"min = {}, max = {}, bits = {}",min,max,bits),
+        }
+    }
+}
+
+impl fmt::Display for CodecAttr {
+    fn fmt(&self,f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodecAttr::Crate(path) => write!(f,"crate = \"{}\"",path),
+            CodecAttr::Transparent => write!(f,"transparent"),
+            CodecAttr::View => write!(f,"view"),
+            CodecAttr::Json => write!(f,"json"),
+            CodecAttr::Delta => write!(f,"delta"),
+            CodecAttr::Schema => write!(f,"schema"),
+            CodecAttr::FixedSize => write!(f,"fixed_size"),
+            CodecAttr::Name(name) => write!(f,"name = \"{}\"",name),
+            CodecAttr::Topic(topic) => write!(f,"topic = \"{}\"",topic),
+            CodecAttr::Version(version) => write!(f,"version = {}",version),
+            CodecAttr::Quantize(quantize) => write!(f,"quantize({})",quantize),
+            CodecAttr::Bits(Some(n)) => write!(f,"bits = {}",n),
+            CodecAttr::Bits(None) => write!(f,"bits"),
+        }
+    }
+}
+
+fn unquote(literal: &Literal) -> String {
+    let s = literal.to_string();
+    if (s.len() >= 2) && s.starts_with('"') && s.ends_with('"') {
+        s[1..s.len() - 1].to_string()
+    }
+    else {
+        panic!("string literal expected instead of `{}`",s);
+    }
+}
+
+impl Lexer {
+
+    // `name` `=` [ `-` ] INTEGER_LITERAL
+    fn parse_quantize_param(&mut self,name: &str) -> String {
+        if !self.parse_ident(name) || !self.parse_punct('=') {
+            panic!("`{} =` expected in `quantize`",name);
+        }
+        let minus = self.parse_punct('-');
+        if let Some(literal) = self.parse_literal() {
+            self.parse_punct(',');
+            if minus { format!("-{}",literal) } else { literal.to_string() }
+        }
+        else {
+            panic!("integer expected after `{} =`",name);
+        }
+    }
+
+    // StringParam = `=` STRING_LITERAL .
+    fn parse_string_param(&mut self,name: &str) -> String {
+        if !self.parse_punct('=') {
+            panic!("`=` expected after `{}`",name);
+        }
+        match self.parse_literal() {
+            Some(literal) => unquote(&literal),
+            None => panic!("string literal expected after `{} =`",name),
+        }
+    }
+
+    // Quantize = `f16` | `bf16` | ( `min` `=` INTEGER `,` `max` `=` INTEGER `,` `bits` `=` INTEGER [ `,` ] ) .
+    fn parse_quantize(&mut self) -> Quantize {
+        if self.parse_ident("f16") {
+            Quantize::F16
+        }
+        else if self.parse_ident("bf16") {
+            Quantize::BF16
+        }
+        else {
+            let min = self.parse_quantize_param("min");
+            let max = self.parse_quantize_param("max");
+            let bits = self.parse_quantize_param("bits");
+            Quantize::Range { min: min, max: max, bits: bits, }
+        }
+    }
+
+    // CodecAttrItem = ( `crate` `=` STRING_LITERAL ) | `transparent` | `view` | `json` | `delta` | `schema` | `fixed_size` | ( `name` `=` STRING_LITERAL ) | ( `topic` `=` STRING_LITERAL ) | ( `version` `=` INTEGER_LITERAL ) | ( `quantize` `(` Quantize `)` ) | ( `bits` [ `=` INTEGER_LITERAL ] ) .
+    pub(crate) fn parse_codec_attr_item(&mut self) -> Option<CodecAttr> {
+        if self.parse_ident("crate") {
+            if self.parse_punct('=') {
+                if let Some(literal) = self.parse_literal() {
+                    Some(CodecAttr::Crate(unquote(&literal)))
+                }
+                else {
+                    panic!("string literal expected after `crate =`");
+                }
+            }
+            else {
+                panic!("`=` expected after `crate`");
+            }
+        }
+        else if self.parse_ident("transparent") {
+            Some(CodecAttr::Transparent)
+        }
+        else if self.parse_ident("view") {
+            Some(CodecAttr::View)
+        }
+        else if self.parse_ident("json") {
+            Some(CodecAttr::Json)
+        }
+        else if self.parse_ident("delta") {
+            Some(CodecAttr::Delta)
+        }
+        else if self.parse_ident("schema") {
+            Some(CodecAttr::Schema)
+        }
+        else if self.parse_ident("fixed_size") {
+            Some(CodecAttr::FixedSize)
+        }
+        else if self.parse_ident("name") {
+            Some(CodecAttr::Name(self.parse_string_param("name")))
+        }
+        else if self.parse_ident("topic") {
+            Some(CodecAttr::Topic(self.parse_string_param("topic")))
+        }
+        else if self.parse_ident("version") {
+            if !self.parse_punct('=') {
+                panic!("`=` expected after `version`");
+            }
+            match self.parse_literal().map(|literal| literal.to_string().parse::<u32>()) {
+                Some(Ok(version)) => Some(CodecAttr::Version(version)),
+                _ => panic!("integer expected after `version =`"),
+            }
+        }
+        else if self.parse_ident("quantize") {
+            if let Some(group) = self.parse_paren_group() {
+                Some(CodecAttr::Quantize(Lexer::new(group.stream()).parse_quantize()))
+            }
+            else {
+                panic!("`(` expected after `quantize`");
+            }
+        }
+        else if self.parse_ident("bits") {
+            if self.parse_punct('=') {
+                match self.parse_literal().map(|literal| literal.to_string().parse::<u32>()) {
+                    Some(Ok(n)) if (1..=64).contains(&n) => Some(CodecAttr::Bits(Some(n))),
+                    _ => panic!("number of bits from 1 to 64 expected after `bits =`"),
+                }
+            }
+            else {
+                Some(CodecAttr::Bits(None))
+            }
+        }
+        else if let Some(ident) = self.parse_some_ident() {
+            panic!("unknown codec attribute `{}`",ident);
+        }
+        else {
+            None
+        }
+    }
+
+    // CodecAttr = `codec` `(` [ CodecAttrItem { `,` CodecAttrItem } [ `,` ] ] `)` .
+    pub(crate) fn parse_codec_attr(&mut self) -> Vec<CodecAttr> {
+        let mut items = Vec::<CodecAttr>::new();
+        if self.parse_ident("codec") {
+            if let Some(group) = self.parse_paren_group() {
+                let mut lexer = Lexer::new(group.stream());
+                while let Some(item) = lexer.parse_codec_attr_item() {
+                    items.push(item);
+                    lexer.parse_punct(',');
+                }
+            }
+            else {
+                panic!("`(` expected after `codec`");
+            }
+        }
+        items
+    }
+}
+
+/// Collect all `#[codec(...)]` items from a list of attributes.
+pub(crate) fn codec_attrs(attrs: &[Group]) -> Vec<CodecAttr> {
+    let mut items = Vec::<CodecAttr>::new();
+    for attr in attrs {
+        let mut lexer = Lexer::new(attr.stream());
+        items.append(&mut lexer.parse_codec_attr());
+    }
+    items
+}
+
+/// Path to the `codec` crate as seen from the derived item, `::codec` unless overridden by `#[codec(crate = "path")]`.
+pub(crate) fn crate_path(attrs: &[Group]) -> String {
+    let mut path = "::codec".to_string();
+    for item in codec_attrs(attrs) {
+        if let CodecAttr::Crate(p) = item {
+            path = p;
+        }
+    }
+    path
+}
+
+/// Whether `#[codec(transparent)]` was given.
+pub(crate) fn is_transparent(attrs: &[Group]) -> bool {
+    codec_attrs(attrs).iter().any(|item| matches!(item,CodecAttr::Transparent))
+}
+
+/// Whether `#[codec(view)]` was given.
+pub(crate) fn has_view(attrs: &[Group]) -> bool {
+    codec_attrs(attrs).iter().any(|item| matches!(item,CodecAttr::View))
+}
+
+/// Whether `#[codec(json)]` was given.
+pub(crate) fn has_json(attrs: &[Group]) -> bool {
+    codec_attrs(attrs).iter().any(|item| matches!(item,CodecAttr::Json))
+}
+
+/// Whether `#[codec(delta)]` was given.
+pub(crate) fn has_delta(attrs: &[Group]) -> bool {
+    codec_attrs(attrs).iter().any(|item| matches!(item,CodecAttr::Delta))
+}
+
+/// Whether `#[codec(schema)]` was given.
+pub(crate) fn has_schema(attrs: &[Group]) -> bool {
+    codec_attrs(attrs).iter().any(|item| matches!(item,CodecAttr::Schema))
+}
+
+/// Whether `#[codec(fixed_size)]` was given.
+pub(crate) fn has_fixed_size(attrs: &[Group]) -> bool {
+    codec_attrs(attrs).iter().any(|item| matches!(item,CodecAttr::FixedSize))
+}
+
+/// Encoded size of a field with `#[codec(quantize(...))]`, if the number of bits is a literal.
+pub(crate) fn quantized_size(attrs: &[Group]) -> Option<usize> {
+    let mut size = None;
+    for item in codec_attrs(attrs) {
+        if let CodecAttr::Quantize(quantize) = item {
+            size = match quantize {
+                Quantize::F16 | Quantize::BF16 => Some(2),
+                Quantize::Range { bits,.. } => bits.parse::<u32>().ok().map(|bits| bits.div_ceil(8) as usize),
+            };
+        }
+    }
+    size
+}
+
+/// `name`, `topic` and `version` from `#[codec(...)]`, for `Message`.
+pub(crate) fn message_attrs(attrs: &[Group]) -> (Option<String>,Option<String>,Option<u32>) {
+    let (mut name,mut topic,mut version) = (None,None,None);
+    for item in codec_attrs(attrs) {
+        match item {
+            CodecAttr::Name(n) => name = Some(n),
+            CodecAttr::Topic(t) => topic = Some(t),
+            CodecAttr::Version(v) => version = Some(v),
+            _ => { },
+        }
+    }
+    (name,topic,version)
+}
+
+// contents of a doc comment string literal, with the escapes `Literal` adds undone
+fn unescape_doc(literal: &Literal) -> String {
+    let s = literal.to_string();
+    if !(s.starts_with('"') && s.ends_with('"')) {
+        return String::new();
+    }
+    let mut r = String::new();
+    let mut chars = s[1..s.len() - 1].chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') => r.push('\n'),
+                Some('t') => r.push('\t'),
+                Some(c) => r.push(c),
+                None => { },
+            }
+        }
+        else {
+            r.push(c);
+        }
+    }
+    r
+}
+
+/// Lines of the `///` comments, `#[doc = "..."]`, without the space after `///`.
+pub(crate) fn doc_lines(attrs: &[Group]) -> Vec<String> {
+    let mut lines = Vec::<String>::new();
+    for attr in attrs {
+        let mut lexer = Lexer::new(attr.stream());
+        if lexer.parse_ident("doc") && lexer.parse_punct('=') {
+            if let Some(literal) = lexer.parse_literal() {
+                for line in unescape_doc(&literal).lines() {
+                    lines.push(line.strip_prefix(' ').unwrap_or(line).trim_end().to_string());
+                }
+            }
+        }
+    }
+    lines
+}
+
+/// Wire type of a field with `#[codec(quantize(...))]`, which converts from and into the field type `ty`.
+pub(crate) fn quantized_type(krate: &str,attrs: &[Group],ty: &str) -> Option<String> {
+    let mut wire = None;
+    for item in codec_attrs(attrs) {
+        if let CodecAttr::Quantize(quantize) = item {
+            wire = Some(match quantize {
+                Quantize::F16 => format!("{}::F16",krate),
+                Quantize::BF16 => format!("{}::BF16",krate),
+                Quantize::Range { min,max,bits } => format!("{}::Quantized<{},{{ {} }},{{ {} }},{{ {} }}>",krate,ty,min,max,bits),
+            });
+        }
+    }
+    wire
+}
+
+/// `Some(Some(n))` for `#[codec(bits = n)]`, `Some(None)` for a bitset `#[codec(bits)]`.
+pub(crate) fn bits(attrs: &[Group]) -> Option<Option<u32>> {
+    let mut bits = None;
+    for item in codec_attrs(attrs) {
+        if let CodecAttr::Bits(n) = item {
+            bits = Some(n);
+        }
+    }
+    bits
+}
@@ -0,0 +1,59 @@
+// Echidna - Codec - Macros
+
+use crate::*;
+
+// `mask[i / 8] & (1 << (i % 8)) != 0`
+fn render_bit(i: usize) -> String {
+    format!("(mask[{}] & {}u8) != 0",i / 8,1u32 << (i % 8))
+}
+
+// `#[codec(delta)]`: `delta::Delta` impl, encoding a bitmask of the fields
+// that differ from the reference, followed by those fields.
+pub(crate) fn render_delta(krate: &str,shape: &Shape) -> String {
+    let variant = match &shape.kind {
+        ShapeKind::Struct(variant) => variant,
+        ShapeKind::Enum(_) => panic!("`delta` is only supported on structs"),
+    };
+    if shape.generics.len() > 0 {
+        panic!("`delta` is not supported on generic structs");
+    }
+    let n = variant.fields.len().div_ceil(8);
+    let reference = variant.pattern().replace(": f_",": r_");
+    let mut r = render_impl(&format!("{}::delta::Delta",krate),shape.ident,shape.generics,shape.wheres);
+    r += " { fn encode_delta(&self,reference: &Self,b: &mut ::std::vec::Vec<::core::primitive::u8>) -> ::core::primitive::usize { let ";
+    r += &variant.pattern();
+    r += " = self; let ";
+    r += &reference;
+    r += &format!(" = reference; let mut mask = [0u8; {}]; ",n);
+    for i in 0..variant.fields.len() {
+        let field = &variant.fields[i];
+        r += &format!("if ::core::cmp::PartialEq::ne({},r_{}) {{ mask[{}] |= {}u8; }} ",field.binding(),field.label(),i / 8,1u32 << (i % 8));
+    }
+    r += &format!("b.extend_from_slice(&mask); let mut ofs = {}usize; ",n);
+    for i in 0..variant.fields.len() {
+        r += &format!("if {} {{ ofs += {}; }} ",render_bit(i),variant.fields[i].encode(krate,"encode",&variant.fields[i].binding()));
+    }
+    r += "ofs } fn decode_delta_with(reference: &Self,b: &[::core::primitive::u8],limiter: &mut ";
+    r += krate;
+    r += "::Limiter) -> ";
+    r += krate;
+    r += "::Result<(::core::primitive::usize,Self)> { let ";
+    r += &reference;
+    r += &format!(" = reference; let mask = b.get(..{}).ok_or({}::Error::Truncated)?; let mut ofs = {}usize; limiter.enter()?; ",n,krate,n);
+    for i in 0..variant.fields.len() {
+        let field = &variant.fields[i];
+        r += &format!("let {} = if {} {{ let (l,f) = <{} as {}::Codec>::decode_with(&b[ofs..],limiter)?; ofs += l; {} }} else {{ ::core::clone::Clone::clone(r_{}) }}; ",field.binding(),render_bit(i),field.codec_type(),krate,field.field_from_wire("f"),field.label());
+    }
+    r += "limiter.leave(); ::core::result::Result::Ok((ofs,";
+    r += &variant.pattern();
+    r += ")) } fn skip_delta_with(b: &[::core::primitive::u8],limiter: &mut ";
+    r += krate;
+    r += "::Limiter) -> ";
+    r += krate;
+    r += &format!("::Result<::core::primitive::usize> {{ let mask = b.get(..{}).ok_or({}::Error::Truncated)?; let mut ofs = {}usize; limiter.enter()?; ",n,krate,n);
+    for i in 0..variant.fields.len() {
+        r += &format!("if {} {{ ofs += <{} as {}::Codec>::skip_with(&b[ofs..],limiter)?; }} ",render_bit(i),variant.fields[i].codec_type(),krate);
+    }
+    r += "limiter.leave(); ::core::result::Result::Ok(ofs) } }";
+    r
+}
@@ -187,8 +187,8 @@ impl Lexer {
                         let mut bounds = Vec::<String>::new();
                         if self.parse_punct(':') {
                             while self.parse_punct('\'') {
-                                if self.is_some_ident() {
-                                    bounds.push(ident.to_string());
+                                if let Some(bound) = self.parse_some_ident() {
+                                    bounds.push(bound);
                                 }
                                 else {
                                     panic!("identifier expected after `'`");
@@ -215,15 +215,18 @@ impl Lexer {
                             if let Some(bound) = self.parse_type_param_bound() {
                                 bounds.push(bound);
                             }
-                            self.parse_punct('+');
-                        }
-                        if self.parse_punct('=') {
-                            if let Some(t) = self.parse_type() {
-                                ty = Some(Box::new(t));
-                            }
                             else {
-                                panic!("type expected after `=`");
+                                panic!("type parameter bound expected");
                             }
+                            self.parse_punct('+');
+                        }
+                    }
+                    if self.parse_punct('=') {
+                        if let Some(t) = self.parse_type() {
+                            ty = Some(Box::new(t));
+                        }
+                        else {
+                            panic!("type expected after `=`");
                         }
                     }
                     params.push(Generic::Type {
@@ -232,6 +235,12 @@ impl Lexer {
                         ty: ty,
                     });
                 }
+
+                else {
+                    panic!("generic parameter expected");
+                }
+
+                self.parse_punct(',');
             }
             self.parse_punct('>');
             params
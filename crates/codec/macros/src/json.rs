@@ -0,0 +1,130 @@
+// Echidna - Codec - Macros
+
+use crate::*;
+
+fn is_tuple(variant: &Variant) -> bool {
+    variant.fields.iter().all(|field| field.name.starts_with(|c: char| c.is_ascii_digit()))
+}
+
+// name of the variant as it appears in JSON, `A` for `E::A`
+fn variant_name<'a>(variant: &'a Variant) -> &'a str {
+    variant.path.rsplit("::").next().unwrap()
+}
+
+// JSON of the fields of `variant`, bound as `f_<name>`: `null` for unit
+// items, the value itself for single-field tuples, an array for other
+// tuples and an object otherwise.
+fn render_to_json_fields(krate: &str,variant: &Variant) -> String {
+    let json = format!("{}::json::Json",krate);
+    let to_json = |field: &Field| format!("{}::json::ToJson::to_json({})",krate,field.binding());
+    if variant.fields.len() == 0 {
+        format!("{}::Null",json)
+    }
+    else if is_tuple(variant) {
+        if variant.fields.len() == 1 {
+            to_json(&variant.fields[0])
+        }
+        else {
+            let mut r = format!("{}::Array(::std::vec![",json);
+            for field in &variant.fields {
+                r += &to_json(field);
+                r += ",";
+            }
+            r += "])";
+            r
+        }
+    }
+    else {
+        let mut r = format!("{}::Object(::std::vec![",json);
+        for field in &variant.fields {
+            r += &format!("(::std::string::String::from(\"{}\"),{}),",field.label(),to_json(field));
+        }
+        r += "])";
+        r
+    }
+}
+
+// construct `variant` from JSON expression `v`, the inverse of `render_to_json_fields`
+fn render_from_json_fields(krate: &str,variant: &Variant,v: &str) -> String {
+    let json = format!("{}::json::Json",krate);
+    let error = |what: String| format!("::core::result::Result::Err({}::json::JsonError::Expected(::std::string::String::from(\"{} for `{}`\")))",krate,what,variant.path);
+    let from_json = |field: &Field,value: String| format!("{}: {}::json::FromJson::from_json({})?, ",field.name,krate,value);
+    if variant.fields.len() == 0 {
+        format!("match {} {{ {}::Null => ::core::result::Result::Ok({}), _ => {}, }}",v,json,variant.pattern(),error("null".to_string()))
+    }
+    else if is_tuple(variant) {
+        if variant.fields.len() == 1 {
+            format!("::core::result::Result::Ok({} {{ {}}})",variant.path,from_json(&variant.fields[0],v.to_string()))
+        }
+        else {
+            let n = variant.fields.len();
+            let mut r = format!("match {} {{ {}::Array(items) if items.len() == {} => ::core::result::Result::Ok({} {{ ",v,json,n,variant.path);
+            for i in 0..n {
+                r += &from_json(&variant.fields[i],format!("&items[{}]",i));
+            }
+            r += &format!("}}), _ => {}, }}",error(format!("array of {}",n)));
+            r
+        }
+    }
+    else {
+        let mut r = format!("match {} {{ {}::Object(members) => ::core::result::Result::Ok({} {{ ",v,json,variant.path);
+        for field in &variant.fields {
+            r += &from_json(field,format!("{}::json::field(members,\"{}\")?",krate,field.label()));
+        }
+        r += &format!("}}), _ => {}, }}",error("object".to_string()));
+        r
+    }
+}
+
+// `#[codec(json)]`: `json::ToJson` and `json::FromJson` impls.
+//
+// Enums become `"Variant"` for variants without fields, and
+// `{"Variant": ...}` otherwise. Both forms are accepted when parsing.
+pub(crate) fn render_json(krate: &str,shape: &Shape) -> String {
+    let json = format!("{}::json::Json",krate);
+    let mut r = render_impl(&format!("{}::json::ToJson",krate),shape.ident,shape.generics,shape.wheres);
+    r += &format!(" {{ fn to_json(&self) -> {} {{ ",json);
+    match &shape.kind {
+        ShapeKind::Struct(variant) => {
+            r += &format!("let {} = self; ",variant.pattern());
+            r += &render_to_json_fields(krate,variant);
+        },
+        ShapeKind::Enum(variants) => {
+            r += "match *self { ";
+            for variant in variants {
+                r += &variant.pattern().replace(": f_",": ref f_");
+                r += " => ";
+                if variant.fields.len() == 0 {
+                    r += &format!("{}::String(::std::string::String::from(\"{}\"))",json,variant_name(variant));
+                }
+                else {
+                    r += &format!("{}::Object(::std::vec![(::std::string::String::from(\"{}\"),{})])",json,variant_name(variant),render_to_json_fields(krate,variant));
+                }
+                r += ", ";
+            }
+            r += "}";
+        },
+    }
+    r += " } }";
+    r += &render_impl(&format!("{}::json::FromJson",krate),shape.ident,shape.generics,shape.wheres);
+    r += &format!(" {{ fn from_json(json: &{}) -> ::core::result::Result<Self,{}::json::JsonError> {{ ",json,krate);
+    match &shape.kind {
+        ShapeKind::Struct(variant) => {
+            r += &render_from_json_fields(krate,variant,"json");
+        },
+        ShapeKind::Enum(variants) => {
+            let unknown = format!("::core::result::Result::Err({}::json::JsonError::UnknownVariant(name.clone()))",krate);
+            r += &format!("match json {{ {}::String(name) => match name.as_str() {{ ",json);
+            for variant in variants.iter().filter(|variant| variant.fields.len() == 0) {
+                r += &format!("\"{}\" => ::core::result::Result::Ok({}), ",variant_name(variant),variant.pattern());
+            }
+            r += &format!("_ => {}, }}, {}::Object(members) if members.len() == 1 => {{ let (name,value) = &members[0]; match name.as_str() {{ ",unknown,json);
+            for variant in variants {
+                r += &format!("\"{}\" => {}, ",variant_name(variant),render_from_json_fields(krate,variant,"value"));
+            }
+            r += &format!("_ => {}, }} }}, _ => ::core::result::Result::Err({}::json::JsonError::Expected(::std::string::String::from(\"variant of `{}`\"))), }}",unknown,krate,shape.ident);
+        },
+    }
+    r += " } }";
+    r
+}
@@ -46,6 +46,15 @@ impl Lexer {
         }
     }
 
+    pub fn is_path_sep(&self) -> bool {
+        if let Some(TokenTree::Punct(punct)) = &self.token {
+            (punct.as_char() == ':') && (punct.spacing() == Spacing::Joint)
+        }
+        else {
+            false
+        }
+    }
+
     pub fn parse_punct2(&mut self,c0: char,c1: char) -> bool {
         if let Some(TokenTree::Punct(punct)) = &self.token {
             if (punct.as_char() == c0) && (punct.spacing() == Spacing::Joint) {
@@ -116,6 +125,17 @@ impl Lexer {
         }
     }
 
+    pub fn parse_literal(&mut self) -> Option<Literal> {
+        if let Some(TokenTree::Literal(literal)) = &self.token {
+            let literal = literal.clone();
+            self.step();
+            Some(literal)
+        }
+        else {
+            None
+        }
+    }
+
     pub fn is_paren_group(&self) -> bool {
         if let Some(TokenTree::Group(group)) = &self.token {
             if group.delimiter() == Delimiter::Parenthesis {
@@ -8,6 +8,7 @@ use {
         token_stream::IntoIter,
         Spacing,
         Group,
+        Literal,
     },
     std::fmt,
 };
@@ -36,6 +37,33 @@ use r#struct::*;
 mod r#enum;
 use r#enum::*;
 
+mod attr;
+use attr::*;
+
+mod shape;
+use shape::*;
+
+mod size;
+use size::*;
+
+mod view;
+use view::*;
+
+mod random;
+use random::*;
+
+mod json;
+use json::*;
+
+mod delta;
+use delta::*;
+
+mod schema;
+use schema::*;
+
+mod message;
+use message::*;
+
 pub(crate) enum Item {
     Struct(Struct),
     Tuple(Tuple),
@@ -172,6 +200,9 @@ fn render_path(path: &Path) -> String {
                             r += " as ";
                             r += &render_path(path.as_ref());
                         },
+                        GenericArg::Const(value) => {
+                            r += value;
+                        },
                     }
                 }
                 r += ">";
@@ -203,287 +234,630 @@ fn render_type(ty: &Type) -> String {
             r += "]";
             r
         },
+        Type::Slice(ty) => {
+            let mut r = "[".to_string();
+            r += &render_type(ty);
+            r += "]";
+            r
+        },
     }
 }
 
-fn render_struct(s: &Struct) -> String {
-    let mut r = "impl Codec for ".to_string();
-    r += &s.ident;
-    r += " { fn decode(b: &[u8]) -> Option<(usize,Self)> { let mut ofs = 0usize; ";
-    for field in &s.fields {
-        r += "let f_";
-        r += &field.ident;
-        r += " = if let Some((l,";
-        r += &field.ident;
-        r += ")) = ";
-        r += &render_type(field.ty.as_ref());
-        r += "::decode(&b[ofs..]) { ofs += l; ";
-        r += &field.ident;
-        r += " } else { return None; }; ";
-    }
-    r += " Some((ofs,";
-    r += &s.ident;
-    r += " { ";
-    for field in &s.fields {
-        r += &field.ident;
-        r += ": f_";
-        r += &field.ident;
-        r += ", ";
-    }
-    r += "})) } fn encode(&self,b: &mut Vec<u8>) -> usize { let mut ofs = 0usize; ";
-    for field in &s.fields {
-        r += "ofs += self.";
-        r += &field.ident;
-        r += ".encode(b); "
-    }
-    r += "ofs } fn size(&self) -> usize { let mut ofs = 0usize; ";
-    for field in &s.fields {
-        r += "ofs += self.";
-        r += &field.ident;
-        r += ".size(); ";
+fn render_trait_bound(bound: &TraitBound) -> String {
+    let mut r = String::new();
+    if bound.question {
+        r += "?";
     }
-    r += "ofs } }";
-    //eprintln!("{}",r);
+    if bound.for_lifetimes.len() > 0 {
+        r += "for<";
+        for lifetime in &bound.for_lifetimes {
+            r += "'";
+            r += lifetime;
+            r += ",";
+        }
+        r += "> ";
+    }
+    r += &render_path(&bound.path);
     r
 }
 
-fn render_tuple(t: &Tuple) -> String {
-    let mut r = "impl Codec for ".to_string();
-    r += &t.ident;
-    r += " { fn decode(b: &[u8]) -> Option<(usize,Self)> { let mut ofs = 0usize; ";
-    for i in 0..t.fields.len() {
-        r += "let f_";
-        r += &i.to_string();
-        r += " = if let Some((l,f)) = ";
-        r += &render_type(t.fields[i].ty.as_ref());
-        r += "::decode(&b[ofs..]) { ofs += l; f } else { return None; }; ";
-    }
-    r += " Some((ofs,";
-    r += &t.ident;
-    r += "(";
-    for i in 0..t.fields.len() {
-        r += "f_";
-        r += &i.to_string();
-        r += ", ";
-    }
-    r += "))) } fn encode(&self,b: &mut Vec<u8>) -> usize { let mut ofs = 0usize; ";
-    for i in 0..t.fields.len() {
-        r += "ofs += self.";
-        r += &i.to_string();
-        r += ".encode(b); ";
+fn render_bounds(bounds: &[TypeParamBound]) -> String {
+    let mut r = String::new();
+    let mut first = true;
+    for bound in bounds {
+        if first {
+            first = false;
+        }
+        else {
+            r += " + ";
+        }
+        match bound {
+            TypeParamBound::Lifetime(ident) => {
+                r += "'";
+                r += ident;
+            },
+            TypeParamBound::Trait(trait_bound) => {
+                r += &render_trait_bound(trait_bound);
+            },
+        }
     }
-    r += "ofs } fn size(&self) -> usize { let mut ofs = 0usize; ";
-    for i in 0..t.fields.len() {
-        r += "ofs += self.";
-        r += &i.to_string();
-        r += ".size(); ";
+    r
+}
+
+fn render_lifetime_bounds(bounds: &[String]) -> String {
+    let mut r = String::new();
+    let mut first = true;
+    for bound in bounds {
+        if first {
+            first = false;
+        }
+        else {
+            r += " + ";
+        }
+        r += "'";
+        r += bound;
     }
-    r += " ofs } }";
-    //eprintln!("{}",r);
     r
 }
 
-fn render_enum(e: &Enum) -> String {
-    let mut r = "impl Codec for ".to_string();
-    r += &e.ident;
-    r += " { fn decode(b: &[u8]) -> Option<(usize,Self)> { if let Some((_,a)) = u32::decode(b) { match a { ";
-    for i in 0..e.items.len() {
-        r += &i.to_string();
-        r += " => ";
-        match &e.items[i] {
-            EnumItem::Bare(b) => {
-                r += "Some((4,";
-                r += &e.ident;
-                r += "::";
-                r += &b.ident;
-                r += "))";
-            },
-            EnumItem::Struct(s) => {
-                r += "{ let mut ofs = 4; ";
-                for k in 0..s.fields.len() {
-                    r += "let ";
-                    r += &s.fields[k].ident;
-                    r += " = if let Some((l,";
-                    r += &s.fields[k].ident;
-                    r += ")) = ";
-                    r += &render_type(s.fields[k].ty.as_ref());
-                    r += "::decode(&b[ofs..]) { ofs += l; ";
-                    r += &s.fields[k].ident;
-                    r += " } else { return None; };"
-                }
-                r += "Some((ofs,";
-                r += &e.ident;
-                r += "::";
-                r += &s.ident;
-                r += " { ";
-                for k in 0..s.fields.len() {
-                    r += &s.fields[k].ident;
+// Generic parameters as declared on the impl, with `bound` (if any) added to each type parameter.
+fn render_impl_generics(generics: &[Generic],bound: Option<&str>) -> String {
+    if generics.len() == 0 {
+        return String::new();
+    }
+    let mut r = "<".to_string();
+    for generic in generics {
+        match generic {
+            Generic::Lifetime { ident,bounds } => {
+                r += "'";
+                r += ident;
+                if bounds.len() > 0 {
                     r += ": ";
-                    r += &s.fields[k].ident;
-                    r += ", ";
-                }
-                r += "})) }";
-            },
-            EnumItem::Tuple(t) => {
-                r += "{ let mut ofs = 4; ";
-                for k in 0..t.fields.len() {
-                    r += "let f";
-                    r += &k.to_string();
-                    r += " = if let Some((l,f)) = ";
-                    r += &render_type(t.fields[k].ty.as_ref());
-                    r += "::decode(&b[ofs..]) { ofs += l; f } else { return None; }; ";
+                    r += &render_lifetime_bounds(bounds);
                 }
-                r += "Some((ofs,";
-                r += &e.ident;
-                r += "::";
-                r += &t.ident;
-                r += "(";
-                let mut first = true;
-                for k in 0..t.fields.len() {
-                    if first {
-                        first = false;
-                    }
-                    else {
-                        r += ",";
+            },
+            Generic::Type { ident,bounds,.. } => {
+                r += ident;
+                r += ": ";
+                if bounds.len() > 0 {
+                    r += &render_bounds(bounds);
+                    if bound.is_some() {
+                        r += " + ";
                     }
-                    r += "f";
-                    r += &k.to_string();
                 }
-                r += "))) }";
+                if let Some(bound) = bound {
+                    r += bound;
+                }
             },
-            EnumItem::Discr(_) => { },
         }
-        r += ", ";
-    }
-    r += "_ => None } } else { None } } fn encode(&self,b: &mut Vec<u8>) -> usize { match self { ";
-    for i in 0..e.items.len() {
-        match &e.items[i] {
-            EnumItem::Bare(b) => {
-                r += &e.ident;
-                r += "::";
-                r += &b.ident;
-                r += " => { u32::encode(&";
-                r += &i.to_string();
-                r += ",b); 4 }";
-            },
-            EnumItem::Struct(s) => {
-                r += &e.ident;
-                r += "::";
-                r += &s.ident;
-                r += " { ";
-                for k in 0..s.fields.len() {
-                    r += &s.fields[k].ident;
-                    r += ", ";
-                }
-                r += "} => { u32::encode(&";
-                r += &i.to_string();
-                r += ",b); let mut ofs = 4; ";
-                for k in 0..s.fields.len() {
-                    r += "ofs += ";
-                    r += &s.fields[k].ident;
-                    r += ".encode(b); ";
-                }
-                r += "ofs }";
+        r += ",";
+    }
+    r += ">";
+    r
+}
+
+// Generic arguments naming the type itself.
+fn render_type_generics(generics: &[Generic]) -> String {
+    if generics.len() == 0 {
+        return String::new();
+    }
+    let mut r = "<".to_string();
+    for generic in generics {
+        match generic {
+            Generic::Lifetime { ident,.. } => {
+                r += "'";
+                r += ident;
             },
-            EnumItem::Tuple(t) => {
-                r += &e.ident;
-                r += "::";
-                r += &t.ident;
-                r += "(";
-                let mut first = true;
-                for k in 0..t.fields.len() {
-                    if first {
-                        first = false;
-                    }
-                    else {
+            Generic::Type { ident,.. } => {
+                r += ident;
+            },
+        }
+        r += ",";
+    }
+    r += ">";
+    r
+}
+
+fn render_wheres(wheres: &[Where]) -> String {
+    if wheres.len() == 0 {
+        return String::new();
+    }
+    let mut r = " where ".to_string();
+    for w in wheres {
+        match w {
+            Where::Lifetime { ident,bounds } => {
+                r += "'";
+                r += ident;
+                r += ": ";
+                r += &render_lifetime_bounds(bounds);
+            },
+            Where::Type { for_lifetimes,ty,bounds } => {
+                if for_lifetimes.len() > 0 {
+                    r += "for<";
+                    for lifetime in for_lifetimes {
+                        r += "'";
+                        r += lifetime;
                         r += ",";
                     }
-                    r += "f";
-                    r += &k.to_string();
-                }
-                r += ") => { u32::encode(&";
-                r += &i.to_string();
-                r += ",b); let mut ofs = 4; ";
-                for k in 0..t.fields.len() {
-                    r += "ofs += f";
-                    r += &k.to_string();
-                    r += ".encode(b); ";
+                    r += "> ";
                 }
-                r += "ofs }";
+                r += &render_type(ty);
+                r += ": ";
+                r += &render_bounds(bounds);
             },
-            EnumItem::Discr(_) => { },
         }
-        r += ", ";
-    }
-    r += "} } fn size(&self) -> usize { match self { ";
-    for i in 0..e.items.len() {
-        match &e.items[i] {
-            EnumItem::Bare(b) => {
-                r += &e.ident;
-                r += "::";
-                r += &b.ident;
-                r += " => 4";
-            },
-            EnumItem::Struct(s) => {
-                r += &e.ident;
-                r += "::";
-                r += &s.ident;
-                r += " { ";
-                for k in 0..s.fields.len() {
-                    r += &s.fields[k].ident;
-                    r += ", ";
-                }
-                r += "} => { let mut ofs = 4; ";
-                for k in 0..s.fields.len() {
-                    r += "ofs += ";
-                    r += &s.fields[k].ident;
-                    r += ".size(); ";
-                }
-                r += " ofs }";
+        r += ",";
+    }
+    r
+}
+
+// `impl<..> Trait for Ident<..> where ..`, requiring `Trait` from every type parameter.
+fn render_impl(tr: &str,ident: &str,generics: &[Generic],wheres: &[Where]) -> String {
+    let mut r = "#[automatically_derived] #[allow(unused_mut,unused_variables,unreachable_code)] impl".to_string();
+    r += &render_impl_generics(generics,Some(tr));
+    r += " ";
+    r += tr;
+    r += " for ";
+    r += ident;
+    r += &render_type_generics(generics);
+    r += &render_wheres(wheres);
+    r
+}
+
+// Decode all fields of `variant` from `b[ofs..]` and construct it.
+fn render_decode_fields(krate: &str,variant: &Variant) -> String {
+    let mut r = String::new();
+    for slot in variant.slots() {
+        match slot {
+            Slot::Field(field) => {
+                r += "let ";
+                r += &field.binding();
+                r += " = { let (l,f) = <";
+                r += &field.codec_type();
+                r += " as ";
+                r += krate;
+                r += "::Codec>::decode_with(&b[ofs..],limiter)?; ofs += l; ";
+                r += &field.field_from_wire("f");
+                r += " }; ";
             },
-            EnumItem::Tuple(t) => {
-                r += &e.ident;
-                r += "::";
-                r += &t.ident;
-                r += "(";
-                let mut first = true;
-                for k in 0..t.fields.len() {
-                    if first {
-                        first = false;
-                    }
-                    else {
-                        r += ",";
-                    }
-                    r += "f";
-                    r += &k.to_string();
+            Slot::Packed(fields) => {
+                let len = packed_len(&fields);
+                r += &format!("let p = {}::decode_packed(&b[ofs..],{})?; ofs += {}; ",krate,len,len);
+                for (field,shift) in &fields {
+                    r += &format!("let {} = {}; ",field.binding(),render_unpack(krate,field,*shift));
                 }
-                r += ") => { let mut ofs = 4; ";
-                for k in 0..t.fields.len() {
-                    r += "ofs += f";
-                    r += &k.to_string();
-                    r += ".size(); ";
+            },
+        }
+    }
+    r += "::core::result::Result::Ok((ofs,";
+    r += &variant.pattern();
+    r += "))";
+    r
+}
+
+// Decode all bound fields of `variant` from `b[ofs..]` in place.
+fn render_decode_into_fields(krate: &str,variant: &Variant) -> String {
+    let mut r = String::new();
+    for slot in variant.slots() {
+        match slot {
+            Slot::Field(field) if field.bitset => {
+                // decode into a `Bitset` that borrows the allocation of the field
+                r += "ofs += { let mut bitset = ";
+                r += krate;
+                r += "::Bitset(::core::mem::take(";
+                r += &field.binding();
+                r += ")); let l = ";
+                r += krate;
+                r += "::Codec::decode_into_with(&mut bitset,&b[ofs..],limiter); *";
+                r += &field.binding();
+                r += " = bitset.0; l? }; ";
+            },
+            Slot::Field(field) if field.wire.is_some() => {
+                r += "ofs += { let (l,f) = <";
+                r += &field.codec_type();
+                r += " as ";
+                r += krate;
+                r += "::Codec>::decode_with(&b[ofs..],limiter)?; *";
+                r += &field.binding();
+                r += " = ";
+                r += &field.field_from_wire("f");
+                r += "; l }; ";
+            },
+            Slot::Field(field) => {
+                r += "ofs += ";
+                r += krate;
+                r += "::Codec::decode_into_with(";
+                r += &field.binding();
+                r += ",&b[ofs..],limiter)?; ";
+            },
+            Slot::Packed(fields) => {
+                let len = packed_len(&fields);
+                r += &format!("{{ let p = {}::decode_packed(&b[ofs..],{})?; ofs += {}; ",krate,len,len);
+                for (field,shift) in &fields {
+                    r += &format!("*{} = {}; ",field.binding(),render_unpack(krate,field,*shift));
                 }
-                r += " ofs }";
+                r += "} ";
+            },
+        }
+    }
+    r
+}
+
+// Skip over all fields of `variant` in `b[ofs..]`.
+fn render_skip_fields(krate: &str,variant: &Variant) -> String {
+    let mut r = String::new();
+    for slot in variant.slots() {
+        match slot {
+            Slot::Field(field) => {
+                r += "ofs += <";
+                r += &field.codec_type();
+                r += " as ";
+                r += krate;
+                r += "::Codec>::skip_with(&b[ofs..],limiter)?; ";
+            },
+            Slot::Packed(fields) => {
+                let len = packed_len(&fields);
+                r += &format!("{}::decode_packed(&b[ofs..],{})?; ofs += {}; ",krate,len,len);
+            },
+        }
+    }
+    r
+}
+
+// Annotate all fields of `variant` as `<prefix>.<name>`, or as `prefix` itself if `transparent`.
+//
+// A packed run is annotated as a whole, as `<prefix>.{a,b,c}`.
+fn render_annotate_fields(krate: &str,variant: &Variant,prefix: &str,transparent: bool) -> String {
+    let mut r = String::new();
+    for slot in variant.slots() {
+        let name = match &slot {
+            Slot::Field(field) => {
+                r += "<";
+                r += &field.codec_type();
+                r += " as ";
+                r += krate;
+                r += "::Codec>::annotate_with(annotator,";
+                field.label().to_string()
+            },
+            Slot::Packed(fields) => {
+                r += "annotator.leaf(";
+                let names: Vec<&str> = fields.iter().map(|(f,_)| f.label()).collect();
+                format!("{{{{{}}}}}",names.join(","))
+            },
+        };
+        if transparent {
+            r += prefix;
+        }
+        else {
+            r += "&::std::format!(\"{}.";
+            r += &name;
+            r += "\",";
+            r += prefix;
+            r += ")";
+        }
+        if let Slot::Packed(fields) = &slot {
+            r += ",";
+            r += &packed_len(fields).to_string();
+        }
+        r += ")?; ";
+    }
+    r
+}
+
+// Apply `method` (`encode` or `encode_canonical`) to all bound fields of `variant`.
+fn render_encode_fields(krate: &str,variant: &Variant,method: &str) -> String {
+    let mut r = String::new();
+    for slot in variant.slots() {
+        match slot {
+            Slot::Field(field) => {
+                r += "ofs += ";
+                r += &field.encode(krate,method,&field.binding());
+                r += "; ";
+            },
+            Slot::Packed(fields) => {
+                r += &format!("ofs += {}::encode_packed({},{},b); ",krate,render_pack(krate,&fields),packed_len(&fields));
+            },
+        }
+    }
+    r
+}
+
+// Encode all bound fields of `variant` onto `gather`.
+fn render_gather_fields(krate: &str,variant: &Variant) -> String {
+    let mut r = String::new();
+    for slot in variant.slots() {
+        match slot {
+            Slot::Field(field) => {
+                r += "ofs += ";
+                r += &field.encode_gather(krate,&field.binding());
+                r += "; ";
+            },
+            Slot::Packed(fields) => {
+                r += &format!("ofs += {}::encode_packed({},{},{}::Gather::buffer(gather)); ",krate,render_pack(krate,&fields),packed_len(&fields),krate);
+            },
+        }
+    }
+    r
+}
+
+// signature of `encode_gather`
+fn render_gather_signature(krate: &str) -> String {
+    format!("fn encode_gather<'gather>(&'gather self,gather: &mut {}::Gather<'gather>) -> ::core::primitive::usize {{ ",krate)
+}
+
+fn render_size_fields(krate: &str,variant: &Variant) -> String {
+    let mut r = String::new();
+    for slot in variant.slots() {
+        match slot {
+            Slot::Field(field) => {
+                r += "ofs += ";
+                r += &field.size(krate,&field.binding());
+                r += "; ";
+            },
+            Slot::Packed(fields) => {
+                r += &format!("ofs += {}; ",packed_len(&fields));
             },
-            EnumItem::Discr(_) => { },
         }
-        r += ", ";
+    }
+    r
+}
+
+const ENCODE_METHODS: [&str; 2] = ["encode","encode_canonical"];
+
+// Structs (named, tuple or unit) encode their fields in order.
+//
+// `#[codec(transparent)]` structs encode exactly like their single field, and don't count as a nesting level.
+fn render_struct(krate: &str,shape: &Shape,variant: &Variant) -> String {
+    let transparent = is_transparent(shape.attrs);
+    if transparent && (variant.fields.len() != 1) {
+        panic!("`transparent` requires exactly one field");
+    }
+    let (enter,leave) = if transparent { ("","") } else { ("limiter.enter()?; ","limiter.leave(); ") };
+    let mut r = render_impl(&format!("{}::Codec",krate),shape.ident,shape.generics,shape.wheres);
+    r += " { ";
+    r += &render_size_consts(krate,shape);
+    r += "fn decode_with(b: &[::core::primitive::u8],limiter: &mut ";
+    r += krate;
+    r += "::Limiter) -> ";
+    r += krate;
+    r += "::Result<(::core::primitive::usize,Self)> { ";
+    r += enter;
+    r += "let mut ofs = 0usize; let r = { ";
+    r += &render_decode_fields(krate,variant);
+    r += " }; ";
+    r += leave;
+    r += "r } fn skip_with(b: &[::core::primitive::u8],limiter: &mut ";
+    r += krate;
+    r += "::Limiter) -> ";
+    r += krate;
+    r += "::Result<::core::primitive::usize> { ";
+    r += enter;
+    r += "let mut ofs = 0usize; ";
+    r += &render_skip_fields(krate,variant);
+    r += leave;
+    r += "::core::result::Result::Ok(ofs) } fn annotate_with(annotator: &mut ";
+    r += krate;
+    r += "::Annotator,path: &::core::primitive::str) -> ";
+    r += krate;
+    r += "::Result<()> { ";
+    r += &enter.replace("limiter","annotator.limiter()");
+    r += &render_annotate_fields(krate,variant,"path",transparent);
+    r += &leave.replace("limiter","annotator.limiter()");
+    r += "::core::result::Result::Ok(()) } fn decode_into_with(&mut self,b: &[::core::primitive::u8],limiter: &mut ";
+    r += krate;
+    r += "::Limiter) -> ";
+    r += krate;
+    r += "::Result<::core::primitive::usize> { ";
+    r += enter;
+    r += "let ";
+    r += &variant.pattern();
+    r += " = self; let mut ofs = 0usize; ";
+    r += &render_decode_into_fields(krate,variant);
+    r += leave;
+    r += "::core::result::Result::Ok(ofs) } ";
+    for method in ENCODE_METHODS {
+        r += "fn ";
+        r += method;
+        r += "(&self,b: &mut ::std::vec::Vec<::core::primitive::u8>) -> ::core::primitive::usize { let ";
+        r += &variant.pattern();
+        r += " = self; let mut ofs = 0usize; ";
+        r += &render_encode_fields(krate,variant,method);
+        r += "ofs } ";
+    }
+    r += &render_gather_signature(krate);
+    r += "let ";
+    r += &variant.pattern();
+    r += " = self; let mut ofs = 0usize; ";
+    r += &render_gather_fields(krate,variant);
+    r += "ofs } ";
+    r += "fn size(&self) -> ::core::primitive::usize { ";
+    r += &render_size_shortcut(krate);
+    r += "let ";
+    r += &variant.pattern();
+    r += " = self; let mut ofs = 0usize; ";
+    r += &render_size_fields(krate,variant);
+    r += "ofs } }";
+    r
+}
+
+// Enums encode the variant index as `u32`, followed by the fields of the variant.
+fn render_enum(krate: &str,shape: &Shape,variants: &[Variant]) -> String {
+    if is_transparent(shape.attrs) {
+        panic!("`transparent` is only supported on structs");
+    }
+    // an empty enum has no values, so `self` can't be matched by reference
+    let scrutinee = if variants.len() > 0 { "match self { " } else { "match *self { " };
+    let mut r = render_impl(&format!("{}::Codec",krate),shape.ident,shape.generics,shape.wheres);
+    r += " { ";
+    r += &render_size_consts(krate,shape);
+    r += "fn decode_with(b: &[::core::primitive::u8],limiter: &mut ";
+    r += krate;
+    r += "::Limiter) -> ";
+    r += krate;
+    r += "::Result<(::core::primitive::usize,Self)> { let (mut ofs,a) = <::core::primitive::u32 as ";
+    r += krate;
+    r += "::Codec>::decode_with(b,limiter)?; limiter.enter()?; let r = match a { ";
+    for i in 0..variants.len() {
+        r += &i.to_string();
+        r += " => { ";
+        r += &render_decode_fields(krate,&variants[i]);
+        r += " }, ";
+    }
+    r += "_ => ::core::result::Result::Err(";
+    r += krate;
+    r += "::Error::InvalidTag(a)), }; limiter.leave(); r } fn skip_with(b: &[::core::primitive::u8],limiter: &mut ";
+    r += krate;
+    r += "::Limiter) -> ";
+    r += krate;
+    r += "::Result<::core::primitive::usize> { let (mut ofs,a) = <::core::primitive::u32 as ";
+    r += krate;
+    r += "::Codec>::decode_with(b,limiter)?; limiter.enter()?; match a { ";
+    for i in 0..variants.len() {
+        r += &i.to_string();
+        r += " => { ";
+        r += &render_skip_fields(krate,&variants[i]);
+        r += "}, ";
+    }
+    r += "_ => return ::core::result::Result::Err(";
+    r += krate;
+    r += "::Error::InvalidTag(a)), } limiter.leave(); ::core::result::Result::Ok(ofs) } ";
+    // the tag is annotated with the variant, `path::Variant`, and the fields as `path::Variant.field`
+    r += "fn annotate_with(annotator: &mut ";
+    r += krate;
+    r += "::Annotator,path: &::core::primitive::str) -> ";
+    r += krate;
+    r += "::Result<()> { let (_,a) = <::core::primitive::u32 as ";
+    r += krate;
+    r += "::Codec>::decode_with(annotator.rest(),annotator.limiter())?; match a { ";
+    for i in 0..variants.len() {
+        let name = variants[i].path.rsplit("::").next().unwrap();
+        r += &i.to_string();
+        r += " => { let path = &::std::format!(\"{}::";
+        r += name;
+        r += "\",path); annotator.leaf(path,4)?; annotator.limiter().enter()?; ";
+        r += &render_annotate_fields(krate,&variants[i],"path",false);
+        r += "annotator.limiter().leave(); }, ";
+    }
+    r += "_ => return ::core::result::Result::Err(";
+    r += krate;
+    r += "::Error::InvalidTag(a)), } ::core::result::Result::Ok(()) } ";
+    // decode in place only if the variant stays the same
+    r += "fn decode_into_with(&mut self,b: &[::core::primitive::u8],limiter: &mut ";
+    r += krate;
+    r += "::Limiter) -> ";
+    r += krate;
+    r += "::Result<::core::primitive::usize> { let (mut ofs,a) = <::core::primitive::u32 as ";
+    r += krate;
+    r += "::Codec>::decode_with(b,limiter)?; match (a,&mut *self) { ";
+    for i in 0..variants.len() {
+        r += "(";
+        r += &i.to_string();
+        r += ",";
+        r += &variants[i].pattern();
+        r += ") => { limiter.enter()?; ";
+        r += &render_decode_into_fields(krate,&variants[i]);
+        r += "limiter.leave(); ::core::result::Result::Ok(ofs) }, ";
+    }
+    r += "_ => { let (l,v) = <Self as ";
+    r += krate;
+    r += "::Codec>::decode_with(b,limiter)?; *self = v; ::core::result::Result::Ok(l) }, } } ";
+    for method in ENCODE_METHODS {
+        r += "fn ";
+        r += method;
+        r += "(&self,b: &mut ::std::vec::Vec<::core::primitive::u8>) -> ::core::primitive::usize { ";
+        r += scrutinee;
+        for i in 0..variants.len() {
+            r += &variants[i].pattern();
+            r += " => { let mut ofs = ";
+            r += krate;
+            r += "::Codec::encode(&";
+            r += &i.to_string();
+            r += "u32,b); ";
+            r += &render_encode_fields(krate,&variants[i],method);
+            r += "ofs }, ";
+        }
+        r += "} } ";
+    }
+    r += &render_gather_signature(krate);
+    r += scrutinee;
+    for i in 0..variants.len() {
+        r += &variants[i].pattern();
+        r += " => { let mut ofs = ";
+        r += krate;
+        r += "::Codec::encode(&";
+        r += &i.to_string();
+        r += "u32,";
+        r += krate;
+        r += "::Gather::buffer(gather)); ";
+        r += &render_gather_fields(krate,&variants[i]);
+        r += "ofs }, ";
+    }
+    r += "} } ";
+    r += "fn size(&self) -> ::core::primitive::usize { ";
+    r += &render_size_shortcut(krate);
+    r += scrutinee;
+    for variant in variants {
+        r += &variant.pattern();
+        r += " => { let mut ofs = 4usize; ";
+        r += &render_size_fields(krate,variant);
+        r += "ofs }, ";
     }
     r += "} } }";
-    //eprintln!("{}",r);
     r
 }
 
-#[proc_macro_derive(Codec)]
+fn render_codec(shape: &Shape) -> String {
+    let krate = crate_path(shape.attrs);
+    let mut r = match &shape.kind {
+        ShapeKind::Struct(variant) => render_struct(&krate,shape,variant),
+        ShapeKind::Enum(variants) => render_enum(&krate,shape,variants),
+    };
+    r += &render_fixed_size(&krate,shape);
+    if has_view(shape.attrs) {
+        r += &render_view(&krate,shape);
+    }
+    if has_json(shape.attrs) {
+        r += &render_json(&krate,shape);
+    }
+    if has_delta(shape.attrs) {
+        r += &render_delta(&krate,shape);
+    }
+    if has_schema(shape.attrs) {
+        r += &render_schema(&krate,shape);
+    }
+    r
+}
+
+#[proc_macro_derive(Codec,attributes(codec))]
 pub fn derive_codec(stream: TokenStream) -> TokenStream {
     let mut lexer = Lexer::new(stream);
     if let Some(item) = lexer.parse_item() {
         //eprintln!("{}",item);
-        match item {
-            Item::Struct(s) => render_struct(&s),
-            Item::Tuple(t) => render_tuple(&t),
-            Item::Enum(e) => render_enum(&e),
-        }.parse().unwrap()
+        let r = render_codec(&item.shape());
+        //eprintln!("{}",r);
+        r.parse().unwrap()
+    }
+    else {
+        panic!("only `struct` or `enum` supported");
+    }
+}
+
+#[proc_macro_derive(Random,attributes(codec))]
+pub fn derive_random(stream: TokenStream) -> TokenStream {
+    let mut lexer = Lexer::new(stream);
+    if let Some(item) = lexer.parse_item() {
+        render_random(&item.shape()).parse().unwrap()
+    }
+    else {
+        panic!("only `struct` or `enum` supported");
+    }
+}
+
+#[proc_macro_derive(Message,attributes(codec))]
+pub fn derive_message(stream: TokenStream) -> TokenStream {
+    let mut lexer = Lexer::new(stream);
+    if let Some(item) = lexer.parse_item() {
+        render_message(&item.shape()).parse().unwrap()
     }
     else {
         panic!("only `struct` or `enum` supported");
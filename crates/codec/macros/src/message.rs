@@ -0,0 +1,40 @@
+// Echidna - Codec - Macros
+
+use crate::*;
+
+// `JointState` as `joint_state`, for the default topic
+fn snake_case(ident: &str) -> String {
+    let chars: Vec<char> = ident.chars().collect();
+    let mut r = String::new();
+    for i in 0..chars.len() {
+        if chars[i].is_ascii_uppercase() && (i > 0) {
+            let after_lower = !chars[i - 1].is_ascii_uppercase() && (chars[i - 1] != '_');
+            let before_lower = (i + 1 < chars.len()) && chars[i + 1].is_ascii_lowercase() && chars[i - 1].is_ascii_uppercase();
+            if after_lower || before_lower {
+                r.push('_');
+            }
+        }
+        r.push(chars[i].to_ascii_lowercase());
+    }
+    r
+}
+
+// `Message` impl. The name defaults to the module path and the type, the
+// topic to the type in snake case, and the version to 1.
+pub(crate) fn render_message(shape: &Shape) -> String {
+    if shape.generics.len() > 0 {
+        panic!("`Message` is not supported on generic items");
+    }
+    let krate = crate_path(shape.attrs);
+    let (name,topic,version) = message_attrs(shape.attrs);
+    let name = match name {
+        Some(name) => format!("{:?}",name),
+        None => format!("::core::concat!(::core::module_path!(),\"::{}\")",shape.ident),
+    };
+    let topic = topic.unwrap_or_else(|| format!("/{}",snake_case(shape.ident)));
+    let mut r = render_impl(&format!("{}::Message",krate),shape.ident,shape.generics,shape.wheres);
+    r += &format!(" {{ const NAME: &'static ::core::primitive::str = {}; ",name);
+    r += &format!("const TOPIC: &'static ::core::primitive::str = {:?}; ",topic);
+    r += &format!("const VERSION: ::core::primitive::u32 = {}; }}",version.unwrap_or(1));
+    r
+}
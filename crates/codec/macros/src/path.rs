@@ -13,6 +13,7 @@ pub(crate) enum GenericArg {
         ident: String,
         path: Box<Path>,
     },
+    Const(String),
 }
 
 impl fmt::Display for GenericArg {
@@ -22,6 +23,7 @@ impl fmt::Display for GenericArg {
             GenericArg::Type(ty) => write!(f,"{}",ty),
             GenericArg::Binding { ident,ty } => write!(f,"{}: {}",ident,ty),
             GenericArg::Qualifier { ident,path } => write!(f,"{} as {}",ident,path),
+            GenericArg::Const(value) => write!(f,"{}",value),
         }
     }
 }
@@ -103,7 +105,7 @@ impl Lexer {
                             panic!("type expected after `=`");
                         }
                     }
-                    if self.parse_ident("as") {
+                    else if self.parse_ident("as") {
                         if let Some(path) = self.parse_path() {
                             args.push(GenericArg::Qualifier { ident: ident.clone(), path: Box::new(path), });
                         }
@@ -112,11 +114,25 @@ impl Lexer {
                         }
                     }
                     else {
-                        let mut segs = Vec::<PathSeg>::new();
-                        segs.push(PathSeg::Ident(ident));
-                        args.push(GenericArg::Type(Type::Path(Path { abs: false, segs: segs, })));
+                        // the identifier starts a path, like `Vec<u8>` or `std::string::String`
+                        args.push(GenericArg::Type(Type::Path(self.parse_path_tail(false,PathSeg::Ident(ident)))));
                     }
                 }
+                // const arguments, like `Quantized<f32,0,100,8>` or `{ -1 }`
+                else if let Some(literal) = self.parse_literal() {
+                    args.push(GenericArg::Const(literal.to_string()));
+                }
+                else if self.parse_punct('-') {
+                    if let Some(literal) = self.parse_literal() {
+                        args.push(GenericArg::Const(format!("{{ -{} }}",literal)));
+                    }
+                    else {
+                        panic!("literal expected after `-`");
+                    }
+                }
+                else if let Some(group) = self.parse_brace_group() {
+                    args.push(GenericArg::Const(format!("{{ {} }}",group.stream())));
+                }
                 else {
                     if let Some(ty) = self.parse_type() {
                         args.push(GenericArg::Type(ty));
@@ -139,32 +155,37 @@ impl Lexer {
     }
 
     pub(crate) fn parse_path(&mut self) -> Option<Path> {
-        let abs = if self.is_punct(':') {
+        let abs = if self.is_path_sep() {
             self.parse_punct2(':',':');
             true
         }
         else {
             false
         };
-        let mut segs = Vec::<PathSeg>::new();
         if let Some(seg) = self.parse_path_seg() {
-            segs.push(seg);
-            while self.is_punct(':') || self.is_punct('<') {
-                self.parse_punct2(':',':');
-                if let Some(seg) = self.parse_path_seg() {
-                    segs.push(seg);
-                }
-                else {
-                    panic!("path segment expected after `::`");
-                }
-            }
-            Some(Path {
-                abs: abs,
-                segs: segs,
-            })
+            Some(self.parse_path_tail(abs,seg))
         }
         else {
             None
         }
     }
+
+    // rest of a path after its first segment `seg`
+    pub(crate) fn parse_path_tail(&mut self,abs: bool,seg: PathSeg) -> Path {
+        let mut segs = Vec::<PathSeg>::new();
+        segs.push(seg);
+        while self.is_path_sep() || self.is_punct('<') {
+            self.parse_punct2(':',':');
+            if let Some(seg) = self.parse_path_seg() {
+                segs.push(seg);
+            }
+            else {
+                panic!("path segment expected after `::`");
+            }
+        }
+        Path {
+            abs: abs,
+            segs: segs,
+        }
+    }
 }
@@ -0,0 +1,54 @@
+// Echidna - Codec - Macros
+
+use crate::*;
+
+// construct `variant` with a random value for every field, drawn from the
+// wire type so quantized and packed fields survive the round trip
+fn render_random_fields(krate: &str,variant: &Variant) -> String {
+    let mut r = variant.path.clone();
+    r += " { ";
+    for field in &variant.fields {
+        r += &field.name;
+        r += ": ";
+        if let Some(n) = field.bits {
+            r += &format!("<{} as {}::Packed>::from_bits(rng.next_u64(),{})",render_type(field.ty),krate,n);
+        }
+        else {
+            r += &field.field_from_wire(&format!("<{} as {}::testing::Random>::random(rng)",field.codec_type(),krate));
+        }
+        r += ", ";
+    }
+    r += "}";
+    r
+}
+
+// `testing::Random` impl; enums pick a random variant.
+pub(crate) fn render_random(shape: &Shape) -> String {
+    let krate = crate_path(shape.attrs);
+    let mut r = render_impl(&format!("{}::testing::Random",krate),shape.ident,shape.generics,shape.wheres);
+    r += " { fn random(rng: &mut ";
+    r += &krate;
+    r += "::testing::Rng) -> Self { ";
+    match &shape.kind {
+        ShapeKind::Struct(variant) => {
+            r += &render_random_fields(&krate,variant);
+        },
+        ShapeKind::Enum(variants) => {
+            if variants.len() == 0 {
+                panic!("cannot derive `Random` for an enum without variants");
+            }
+            r += "match rng.below(";
+            r += &variants.len().to_string();
+            r += ") { ";
+            for i in 0..variants.len() {
+                r += &i.to_string();
+                r += " => ";
+                r += &render_random_fields(&krate,&variants[i]);
+                r += ", ";
+            }
+            r += "_ => ::core::unreachable!(), }";
+        },
+    }
+    r += " } }";
+    r
+}
@@ -0,0 +1,62 @@
+// Echidna - Codec - Macros
+
+use crate::*;
+
+fn render_strings(lines: &[String]) -> String {
+    let mut r = "::std::vec![".to_string();
+    for line in lines {
+        r += &format!("::std::string::String::from({:?}),",line);
+    }
+    r += "]";
+    r
+}
+
+// `schema::Field`s of `variant`, tuple fields named `_0`, `_1`, etc.
+fn render_schema_fields(krate: &str,variant: &Variant) -> String {
+    let mut r = "::std::vec![".to_string();
+    for field in &variant.fields {
+        if field.bits.is_some() {
+            panic!("`schema` doesn't support packed `bits = N` fields");
+        }
+        let name = if field.label().starts_with(|c: char| c.is_ascii_digit()) { format!("_{}",field.label()) } else { field.label().to_string() };
+        r += &format!("{}::schema::Field {{ doc: {}, name: ::std::string::String::from(\"{}\"), ty: <{} as {}::schema::Describe>::describe(schema), }}, ",krate,render_strings(&doc_lines(field.attrs)),name,field.codec_type(),krate);
+    }
+    r += "]";
+    r
+}
+
+// `#[codec(schema)]`: `schema::Describe` impl. The item is added before
+// the items of its fields, so recursive types refer to themselves by name
+// instead of being described forever. `Schema::item_name` keeps apart
+// different types with the same name.
+pub(crate) fn render_schema(krate: &str,shape: &Shape) -> String {
+    if shape.generics.len() > 0 {
+        panic!("`schema` is not supported on generic items");
+    }
+    let mut r = render_impl(&format!("{}::schema::Describe",krate),shape.ident,shape.generics,shape.wheres);
+    r += &format!(" {{ fn describe(schema: &mut {}::schema::Schema) -> {}::schema::Type {{ ",krate,krate);
+    if let ShapeKind::Struct(variant) = &shape.kind {
+        // a transparent struct encodes like its field, so it also describes like it
+        if is_transparent(shape.attrs) {
+            r += &format!("<{} as {}::schema::Describe>::describe(schema) }} }}",render_type(variant.fields[0].ty),krate);
+            return r;
+        }
+    }
+    let kind = match &shape.kind {
+        ShapeKind::Struct(variant) => format!("{}::schema::ItemKind::Struct({})",krate,render_schema_fields(krate,variant)),
+        ShapeKind::Enum(variants) => {
+            let mut r = format!("{}::schema::ItemKind::Enum(::std::vec![",krate);
+            for variant in variants {
+                r += &format!("{}::schema::Variant {{ doc: {}, name: ::std::string::String::from(\"{}\"), fields: {}, }}, ",krate,render_strings(&doc_lines(variant.attrs)),variant.path.rsplit("::").next().unwrap(),render_schema_fields(krate,variant));
+            }
+            r += "])";
+            r
+        },
+    };
+    r += &format!("let (name,new) = {}::schema::Schema::item_name::<Self>(schema,\"{}\"); if new {{ ",krate,shape.ident);
+    r += "let i = schema.items.len(); ::std::vec::Vec::push(&mut schema.items,";
+    r += &format!("{}::schema::Item {{ doc: {}, name: ::std::clone::Clone::clone(&name), kind: {}::schema::ItemKind::Struct(::std::vec::Vec::new()), consts: ::std::vec::Vec::new(), }}); ",krate,render_strings(&doc_lines(shape.attrs)),krate);
+    r += &format!("let kind = {}; schema.items[i].kind = kind; }} ",kind);
+    r += &format!("{}::schema::Type::Named(name) }} }}",krate);
+    r
+}
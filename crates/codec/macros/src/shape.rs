@@ -0,0 +1,301 @@
+// Echidna - Codec - Macros
+
+use crate::*;
+
+/// Field of a struct, tuple or enum item, as seen by the code generators.
+///
+/// Tuple fields are named `0`, `1`, etc. Generated code binds every field
+/// to `f_<name>`, so user field names never collide with generated locals.
+///
+/// Fields with `#[codec(quantize(...))]` or `#[codec(bits)]` have a `wire`
+/// type that is decoded instead of `ty`, converted with `From` and `Into`.
+/// Fields with `#[codec(bits = N)]` are packed with their neighbours, see
+/// `Slot`.
+pub(crate) struct Field<'a> {
+    pub(crate) attrs: &'a [Group],
+    pub(crate) visibility: &'a Visibility,
+    pub(crate) name: String,
+    pub(crate) ty: &'a Type,
+    pub(crate) wire: Option<String>,
+    pub(crate) bitset: bool,
+    pub(crate) bits: Option<u32>,
+}
+
+impl<'a> Field<'a> {
+
+    // apply the field attributes, `quantize` and `bits`
+    fn with_attrs(mut self,krate: &str,attrs: &[Group]) -> Self {
+        self.wire = quantized_type(krate,attrs,&render_type(self.ty));
+        match bits(attrs) {
+            Some(_) if self.wire.is_some() => panic!("`bits` and `quantize` can't be combined"),
+            Some(Some(n)) => self.bits = Some(n),
+            Some(None) => {
+                self.wire = Some(format!("{}::Bitset",krate));
+                self.bitset = true;
+            },
+            None => { },
+        }
+        self
+    }
+
+    pub(crate) fn binding(&self) -> String {
+        format!("f_{}",self.label())
+    }
+
+    /// Name without the `r#` of a raw identifier, for use in bindings and text.
+    pub(crate) fn label(&self) -> &str {
+        self.name.trim_start_matches("r#")
+    }
+
+    /// Type that implements `Codec` for this field.
+    pub(crate) fn codec_type(&self) -> String {
+        match &self.wire {
+            Some(wire) => wire.clone(),
+            None => render_type(self.ty),
+        }
+    }
+
+    /// Reference to the wire value, from `value`, a reference to the field.
+    pub(crate) fn to_wire(&self,value: &str) -> String {
+        match &self.wire {
+            Some(wire) => format!("&<{} as ::core::convert::From<{}>>::from(::core::clone::Clone::clone({}))",wire,render_type(self.ty),value),
+            None => value.to_string(),
+        }
+    }
+
+    /// Field value from `value`, a decoded wire value.
+    pub(crate) fn field_from_wire(&self,value: &str) -> String {
+        match &self.wire {
+            Some(wire) => format!("<{} as ::core::convert::Into<{}>>::into({})",wire,render_type(self.ty),value),
+            None => value.to_string(),
+        }
+    }
+
+    /// Encode `value`, a reference to the field, onto `b` with `method`
+    /// (`encode` or `encode_canonical`). Bitsets are encoded straight from
+    /// the `Vec<bool>`, without converting.
+    pub(crate) fn encode(&self,krate: &str,method: &str,value: &str) -> String {
+        if self.bitset {
+            format!("{}::Bitset::encode_bools({},b)",krate,value)
+        }
+        else {
+            format!("{}::Codec::{}({},b)",krate,method,self.to_wire(value))
+        }
+    }
+
+    /// Encode `value`, a reference to the field, onto `gather`. Fields with
+    /// a wire type are converted first, so they go into the scratch buffer.
+    pub(crate) fn encode_gather(&self,krate: &str,value: &str) -> String {
+        if self.wire.is_some() {
+            format!("{{ let b = {}::Gather::buffer(gather); {} }}",krate,self.encode(krate,"encode",value))
+        }
+        else {
+            format!("{}::Codec::encode_gather({},gather)",krate,value)
+        }
+    }
+
+    /// Size of `value`, a reference to the field, encoded.
+    pub(crate) fn size(&self,krate: &str,value: &str) -> String {
+        if self.bitset {
+            format!("{}::Bitset::size_bools({})",krate,value)
+        }
+        else {
+            format!("{}::Codec::size({})",krate,self.to_wire(value))
+        }
+    }
+}
+
+/// Fields as they are laid out on the wire: either a single field, or a
+/// run of consecutive `#[codec(bits = N)]` fields that share bytes.
+///
+/// A packed run holds at most 64 bits, a longer run is split. Each field
+/// is stored at a bit offset, first field in the lowest bits, and the run
+/// is written in little-endian order in as few bytes as fit the bits.
+pub(crate) enum Slot<'v,'a> {
+    Field(&'v Field<'a>),
+    Packed(Vec<(&'v Field<'a>,u32)>),
+}
+
+impl<'v,'a> Slot<'v,'a> {
+
+    /// Whether the slot contains `field`.
+    pub(crate) fn contains(&self,field: &Field) -> bool {
+        match self {
+            Slot::Field(f) => f.name == field.name,
+            Slot::Packed(fields) => fields.iter().any(|(f,_)| f.name == field.name),
+        }
+    }
+}
+
+/// Number of bytes of a packed run.
+pub(crate) fn packed_len(fields: &[(&Field,u32)]) -> usize {
+    let bits: u32 = fields.iter().map(|(f,_)| f.bits.unwrap()).sum();
+    bits.div_ceil(8) as usize
+}
+
+/// Field value from the bits `p` of the packed run it is part of, at `shift`.
+pub(crate) fn render_unpack(krate: &str,field: &Field,shift: u32) -> String {
+    format!("<{} as {}::Packed>::from_bits(p >> {},{})",render_type(field.ty),krate,shift,field.bits.unwrap())
+}
+
+/// All fields of a packed run combined into one `u64`, from the bound fields.
+pub(crate) fn render_pack(krate: &str,fields: &[(&Field,u32)]) -> String {
+    let mut r = "0u64".to_string();
+    for (field,shift) in fields {
+        r += &format!(" | ({}::pack_bits({},{}) << {})",krate,field.binding(),field.bits.unwrap(),shift);
+    }
+    r
+}
+
+/// Struct, tuple or enum item with its path and fields.
+pub(crate) struct Variant<'a> {
+    pub(crate) attrs: &'a [Group],
+    pub(crate) path: String,
+    pub(crate) fields: Vec<Field<'a>>,
+}
+
+impl<'a> Variant<'a> {
+
+    /// Fields grouped as they are laid out on the wire.
+    pub(crate) fn slots(&self) -> Vec<Slot<'_,'a>> {
+        let mut slots = Vec::<Slot>::new();
+        let mut used = 0u32;
+        for field in &self.fields {
+            match field.bits {
+                Some(n) => {
+                    if let Some(Slot::Packed(fields)) = slots.last_mut() {
+                        if used + n <= 64 {
+                            fields.push((field,used));
+                            used += n;
+                            continue;
+                        }
+                    }
+                    slots.push(Slot::Packed(vec![(field,0)]));
+                    used = n;
+                },
+                None => slots.push(Slot::Field(field)),
+            }
+        }
+        slots
+    }
+
+    /// Pattern binding all fields, `Path { a: f_a, 0: f_0, }`.
+    ///
+    /// Braced syntax works for named, tuple and unit items alike, and is
+    /// also usable as an expression to construct the item.
+    pub(crate) fn pattern(&self) -> String {
+        let mut r = self.path.clone();
+        r += " { ";
+        for field in &self.fields {
+            r += &field.name;
+            r += ": ";
+            r += &field.binding();
+            r += ", ";
+        }
+        r += "}";
+        r
+    }
+}
+
+pub(crate) enum ShapeKind<'a> {
+    Struct(Variant<'a>),
+    Enum(Vec<Variant<'a>>),
+}
+
+/// Everything the code generators need to know about an item.
+pub(crate) struct Shape<'a> {
+    pub(crate) visibility: &'a Visibility,
+    pub(crate) ident: &'a str,
+    pub(crate) attrs: &'a [Group],
+    pub(crate) generics: &'a [Generic],
+    pub(crate) wheres: &'a [Where],
+    pub(crate) kind: ShapeKind<'a>,
+}
+
+fn struct_fields<'a>(krate: &str,fields: &'a [StructField]) -> Vec<Field<'a>> {
+    let mut result = Vec::<Field>::new();
+    for field in fields {
+        result.push(Field {
+            attrs: &field.attrs,
+            visibility: &field.visibility,
+            name: field.ident.clone(),
+            ty: &field.ty,
+            wire: None,
+            bitset: false,
+            bits: None,
+        }.with_attrs(krate,&field.attrs));
+    }
+    result
+}
+
+fn tuple_fields<'a>(krate: &str,fields: &'a [TupleField]) -> Vec<Field<'a>> {
+    let mut result = Vec::<Field>::new();
+    for i in 0..fields.len() {
+        result.push(Field {
+            attrs: &fields[i].attrs,
+            visibility: &fields[i].visibility,
+            name: i.to_string(),
+            ty: &fields[i].ty,
+            wire: None,
+            bitset: false,
+            bits: None,
+        }.with_attrs(krate,&fields[i].attrs));
+    }
+    result
+}
+
+impl Item {
+    pub(crate) fn shape(&self) -> Shape<'_> {
+        match self {
+            Item::Struct(s) => Shape {
+                visibility: &s.visibility,
+                ident: &s.ident,
+                attrs: &s.attrs,
+                generics: &s.generics,
+                wheres: &s.wheres,
+                kind: ShapeKind::Struct(Variant {
+                    attrs: &s.attrs,
+                    path: s.ident.clone(),
+                    fields: struct_fields(&crate_path(&s.attrs),&s.fields),
+                }),
+            },
+            Item::Tuple(t) => Shape {
+                visibility: &t.visibility,
+                ident: &t.ident,
+                attrs: &t.attrs,
+                generics: &t.generics,
+                wheres: &t.wheres,
+                kind: ShapeKind::Struct(Variant {
+                    attrs: &t.attrs,
+                    path: t.ident.clone(),
+                    fields: tuple_fields(&crate_path(&t.attrs),&t.fields),
+                }),
+            },
+            Item::Enum(e) => {
+                let krate = crate_path(&e.attrs);
+                let mut variants = Vec::<Variant>::new();
+                for item in &e.items {
+                    let (attrs,ident,fields) = match item {
+                        EnumItem::Bare(b) => (&b.attrs,&b.ident,Vec::new()),
+                        EnumItem::Struct(s) => (&s.attrs,&s.ident,struct_fields(&krate,&s.fields)),
+                        EnumItem::Tuple(t) => (&t.attrs,&t.ident,tuple_fields(&krate,&t.fields)),
+                        EnumItem::Discr(_) => panic!("explicit discriminants are not supported"),
+                    };
+                    variants.push(Variant {
+                        attrs: attrs,
+                        path: format!("{}::{}",e.ident,ident),
+                        fields: fields,
+                    });
+                }
+                Shape {
+                    visibility: &e.visibility,
+                    ident: &e.ident,
+                    attrs: &e.attrs,
+                    generics: &e.generics,
+                    wheres: &e.wheres,
+                    kind: ShapeKind::Enum(variants),
+                }
+            },
+        }
+    }
+}
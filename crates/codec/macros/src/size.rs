@@ -0,0 +1,171 @@
+// Echidna - Codec - Macros
+
+use crate::*;
+
+const SOME: &str = "::core::option::Option::Some";
+const NONE: &str = "::core::option::Option::None";
+
+// `konst` (`FIXED_SIZE` or `MAX_SIZE`) of the fields of `variant` added up, as `Option<usize>` expression.
+fn render_sum(krate: &str,variant: &Variant,konst: &str) -> String {
+    let mut r = "{ let mut n = 0usize; let mut known = true; ".to_string();
+    for slot in variant.slots() {
+        match slot {
+            Slot::Field(field) => {
+                r += "match <";
+                r += &field.codec_type();
+                r += " as ";
+                r += krate;
+                r += "::Codec>::";
+                r += konst;
+                r += &format!(" {{ {}(s) => n += s, {} => known = false, }} ",SOME,NONE);
+            },
+            Slot::Packed(fields) => {
+                r += &format!("n += {}; ",packed_len(&fields));
+            },
+        }
+    }
+    r += &format!("if known {{ {}(n) }} else {{ {} }} }}",SOME,NONE);
+    r
+}
+
+// `FIXED_SIZE` and `MAX_SIZE` for the `Codec` impl.
+//
+// An enum has a fixed size only if all variants have the same fixed size, and a maximum size if all variants have one.
+pub(crate) fn render_size_consts(krate: &str,shape: &Shape) -> String {
+    let mut r = "const FIXED_SIZE: ::core::option::Option<::core::primitive::usize> = ".to_string();
+    match &shape.kind {
+        ShapeKind::Struct(variant) => {
+            r += &render_sum(krate,variant,"FIXED_SIZE");
+            r += "; const MAX_SIZE: ::core::option::Option<::core::primitive::usize> = ";
+            r += &render_sum(krate,variant,"MAX_SIZE");
+            r += "; ";
+        },
+        ShapeKind::Enum(variants) => {
+            r += "{ let mut n: ::core::option::Option<::core::primitive::usize> = ";
+            r += NONE;
+            r += "; let mut fixed = ";
+            r += if variants.len() > 0 { "true" } else { "false" };
+            r += "; ";
+            for variant in variants {
+                r += "match ";
+                r += &render_sum(krate,variant,"FIXED_SIZE");
+                r += &format!(" {{ {}(s) => match n {{ {}(m) => if m != s {{ fixed = false; }}, {} => n = {}(s), }}, {} => fixed = false, }} ",SOME,SOME,NONE,SOME,NONE);
+            }
+            r += &format!("match n {{ {}(m) if fixed => {}(4 + m), _ => {}, }} }}; ",SOME,SOME,NONE);
+            r += "const MAX_SIZE: ::core::option::Option<::core::primitive::usize> = { let mut n = 0usize; let mut bounded = ";
+            r += if variants.len() > 0 { "true" } else { "false" };
+            r += "; ";
+            for variant in variants {
+                r += "match ";
+                r += &render_sum(krate,variant,"MAX_SIZE");
+                r += &format!(" {{ {}(s) => if s > n {{ n = s; }}, {} => bounded = false, }} ",SOME,NONE);
+            }
+            r += &format!("if bounded {{ {}(4 + n) }} else {{ {} }} }}; ",SOME,NONE);
+        },
+    }
+    r
+}
+
+// Start of `size()`, returning the fixed size without walking the value if there is one.
+pub(crate) fn render_size_shortcut(krate: &str) -> String {
+    format!("if let {}(n) = <Self as {}::Codec>::FIXED_SIZE {{ return n; }} ",SOME,krate)
+}
+
+// Encoded size of `field` as far as it can be told from the field alone: primitive types and quantized fields.
+//
+// Primitives are recognized by name, which a type in scope can shadow, so
+// this only decides whether to emit `FixedSize`. `SIZE` itself is always
+// `Codec::FIXED_SIZE`, and `render_fixed_size` makes const evaluation check
+// the guess where the type is defined.
+fn field_size(field: &Field) -> Option<usize> {
+    if field.bitset {
+        return None;
+    }
+    if field.wire.is_some() {
+        return quantized_size(field.attrs);
+    }
+    let ident = match field.ty {
+        Type::Path(path) => match path.segs.as_slice() {
+            [PathSeg::Ident(ident)] if !path.abs => ident,
+            [PathSeg::Ident(root),PathSeg::Ident(primitive),PathSeg::Ident(ident)] if ((root == "core") || (root == "std")) && (primitive == "primitive") => ident,
+            _ => return None,
+        },
+        _ => return None,
+    };
+    match ident.as_str() {
+        "u8" | "i8" | "bool" => Some(1),
+        "u16" | "i16" => Some(2),
+        "u32" | "i32" | "f32" => Some(4),
+        "u64" | "i64" | "f64" => Some(8),
+        _ => None,
+    }
+}
+
+// Encoded size of `variant`, if every field has a known size.
+fn variant_size(variant: &Variant) -> Option<usize> {
+    let mut n = 0usize;
+    for slot in variant.slots() {
+        n += match slot {
+            Slot::Field(field) => field_size(field)?,
+            Slot::Packed(fields) => packed_len(&fields),
+        };
+    }
+    Some(n)
+}
+
+// `FixedSize` impl.
+//
+// The derive can't see the size of other types, so the impl is only
+// emitted by itself when all fields are primitive, packed or quantized,
+// and, for enums, all variants add up to the same size. Otherwise
+// `#[codec(fixed_size)]` asks for it, bounded on the field types. `SIZE`
+// adds up `FIXED_SIZE` of the fields, and for types without generics it
+// is evaluated where the type is defined, not where `SIZE` is used.
+pub(crate) fn render_fixed_size(krate: &str,shape: &Shape) -> String {
+    let variants: Vec<&Variant> = match &shape.kind {
+        ShapeKind::Struct(variant) => vec![variant],
+        ShapeKind::Enum(variants) => variants.iter().collect(),
+    };
+    let sizes: Vec<Option<usize>> = variants.iter().map(|variant| variant_size(variant)).collect();
+    let known = match sizes.first() {
+        Some(Some(n)) => sizes.iter().all(|size| *size == Some(*n)),
+        _ => false,
+    };
+    let requested = has_fixed_size(shape.attrs);
+    if !known && !requested {
+        return String::new();
+    }
+    let is_enum = matches!(shape.kind,ShapeKind::Enum(_));
+    if requested && is_enum && (shape.generics.len() > 0) {
+        panic!("`fixed_size` is not supported on generic enums");
+    }
+    let mut r = render_impl(&format!("{}::FixedSize",krate),shape.ident,shape.generics,shape.wheres);
+    if !known {
+        if shape.wheres.len() == 0 {
+            r += " where ";
+        }
+        let mut bounds = Vec::<String>::new();
+        for variant in &variants {
+            for field in variant.fields.iter().filter(|field| field.bits.is_none()) {
+                let bound = format!("{}: {}::FixedSize,",field.codec_type(),krate);
+                if !bounds.contains(&bound) {
+                    bounds.push(bound);
+                }
+            }
+        }
+        for bound in bounds {
+            r += &bound;
+        }
+    }
+    r += " { const SIZE: ::core::primitive::usize = match <Self as ";
+    r += krate;
+    r += "::Codec>::FIXED_SIZE { ";
+    r += SOME;
+    r += "(n) => n, ";
+    r += NONE;
+    r += &format!(" => ::core::panic!(\"`{}` doesn't have a fixed size\"), }}; }}",shape.ident);
+    if shape.generics.len() == 0 {
+        r += &format!(" const _: ::core::primitive::usize = <{} as {}::FixedSize>::SIZE;",shape.ident,krate);
+    }
+    r
+}
@@ -262,7 +262,17 @@ impl Lexer {
                 }
                 else {
                     let wheres = self.parse_wheres();
-                    if let Some(group) = self.parse_brace_group() {
+                    if self.parse_punct(';') {
+                        Some(StructOrTuple::Struct(Struct {
+                            attrs: attrs,
+                            visibility: visibility,
+                            ident: ident,
+                            generics: generics,
+                            wheres: wheres,
+                            fields: Vec::new(),
+                        }))
+                    }
+                    else if let Some(group) = self.parse_brace_group() {
                         let mut lexer = Lexer::new(group.stream());
                         let mut fields = Vec::<StructField>::new();
                         while let Some(field) = lexer.parse_struct_field() {
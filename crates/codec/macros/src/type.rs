@@ -9,6 +9,7 @@ pub(crate) enum Type {
         ty: Box<Type>,
         expr: Expr,
     },
+    Slice(Box<Type>),
 }
 
 impl fmt::Display for Type {
@@ -34,6 +35,9 @@ impl fmt::Display for Type {
             Type::Array { ty,expr } => {
                 write!(f,"[{}; {}]",ty,expr)
             },
+            Type::Slice(ty) => {
+                write!(f,"[{}]",ty)
+            },
         }
     }
 }
@@ -42,7 +46,8 @@ impl Lexer {
 
     // TupleType = `(` [ Type { `,` Type } [ `,` ] `)` .
     // ArrayType = `[` Type `;` Expr `]` .
-    // Type = Path | TupleType | ArrayType .
+    // SliceType = `[` Type `]` .
+    // Type = Path | TupleType | ArrayType | SliceType .
     pub(crate) fn parse_type(&mut self) -> Option<Type> {
         if let Some(group) = self.parse_paren_group() {
             let mut lexer = Lexer::new(group.stream());
@@ -72,8 +77,11 @@ impl Lexer {
                         panic!("expression expected after `;`");
                     }
                 }
+                else if let None = lexer.token {
+                    Some(Type::Slice(Box::new(ty)))
+                }
                 else {
-                    panic!("`;` expected");
+                    panic!("`;` or `]` expected");
                 }
             }
             else {
@@ -0,0 +1,127 @@
+// Echidna - Codec - Macros
+
+use crate::*;
+
+fn render_visibility(visibility: &Visibility) -> String {
+    match visibility {
+        Visibility::Private => String::new(),
+        Visibility::Public => "pub ".to_string(),
+        Visibility::PubCrate => "pub(crate) ".to_string(),
+        Visibility::PubSelf => "pub(self) ".to_string(),
+        Visibility::PubSuper => "pub(super) ".to_string(),
+        Visibility::PubIn(path) => format!("pub(in {}) ",render_path(path)),
+    }
+}
+
+// Accessor for field `k` of `variant`. Fields before it are passed by their
+// `FIXED_SIZE` where possible, and only skipped over when their size depends
+// on the content.
+fn render_accessor(krate: &str,variant: &Variant,k: usize) -> String {
+    let field = &variant.fields[k];
+    let mut r = render_visibility(field.visibility);
+    r += "fn ";
+    if field.name.starts_with(|c: char| c.is_ascii_digit()) {
+        r += "_";
+    }
+    r += &field.name;
+    r += "(&self) -> ";
+    r += krate;
+    r += "::Result<";
+    r += &render_type(field.ty);
+    r += "> { let b = self.buffer; let mut limiter = ";
+    r += krate;
+    r += "::Limiter::new(&self.limits); let limiter = &mut limiter; let mut ofs = 0usize; ";
+    for slot in variant.slots() {
+        match slot {
+            Slot::Field(before) if !slot.contains(field) => {
+                let ty = before.codec_type();
+                r += "ofs += match <";
+                r += &ty;
+                r += " as ";
+                r += krate;
+                r += "::Codec>::FIXED_SIZE { ::core::option::Option::Some(n) => n, ::core::option::Option::None => <";
+                r += &ty;
+                r += " as ";
+                r += krate;
+                r += "::Codec>::skip_with(b.get(ofs..).ok_or(";
+                r += krate;
+                r += "::Error::Truncated)?,limiter)?, }; ";
+            },
+            Slot::Packed(fields) if !slot.contains(field) => {
+                r += &format!("ofs += {}; ",packed_len(&fields));
+            },
+            Slot::Field(_) => {
+                r += "<";
+                r += &field.codec_type();
+                r += " as ";
+                r += krate;
+                r += "::Codec>::decode_with(b.get(ofs..).ok_or(";
+                r += krate;
+                r += "::Error::Truncated)?,limiter).map(|(_,f)| ";
+                r += &field.field_from_wire("f");
+                r += ") } ";
+                break;
+            },
+            Slot::Packed(fields) => {
+                let shift = fields.iter().find(|(f,_)| f.name == field.name).unwrap().1;
+                r += krate;
+                r += "::decode_packed(b.get(ofs..).ok_or(";
+                r += krate;
+                r += &format!("::Error::Truncated)?,{}).map(|p| {}) }} ",packed_len(&fields),render_unpack(krate,field,shift));
+                break;
+            },
+        }
+    }
+    r
+}
+
+// `#[codec(view)]`: `FooView<'a>` over an encoded `Foo`, with an accessor
+// per field that decodes only that field.
+pub(crate) fn render_view(krate: &str,shape: &Shape) -> String {
+    let variant = match &shape.kind {
+        ShapeKind::Struct(variant) => variant,
+        ShapeKind::Enum(_) => panic!("`view` is only supported on structs"),
+    };
+    if shape.generics.len() > 0 {
+        panic!("`view` is not supported on generic structs");
+    }
+    let vis = render_visibility(shape.visibility);
+    let view = format!("{}View",shape.ident);
+    let mut r = format!("/// Lazy view over an encoded [`{}`], fields are decoded on access.\n",shape.ident);
+    r += "#[derive(::core::clone::Clone,::core::marker::Copy)] ";
+    r += &vis;
+    r += "struct ";
+    r += &view;
+    r += "<'a> { buffer: &'a [::core::primitive::u8], limits: ";
+    r += krate;
+    r += "::DecodeLimits, } #[automatically_derived] #[allow(unused_mut,unused_variables,dead_code)] impl<'a> ";
+    r += &view;
+    r += "<'a> { ";
+    r += &vis;
+    r += "fn new(buffer: &'a [::core::primitive::u8]) -> Self { Self::with_limits(buffer,&";
+    r += krate;
+    r += "::DecodeLimits::default()) } ";
+    r += &vis;
+    r += "fn with_limits(buffer: &'a [::core::primitive::u8],limits: &";
+    r += krate;
+    r += "::DecodeLimits) -> Self { ";
+    r += &view;
+    r += " { buffer: buffer, limits: *limits, } } ";
+    r += &vis;
+    r += "fn as_bytes(&self) -> &'a [::core::primitive::u8] { self.buffer } ";
+    r += &vis;
+    r += "fn decode(&self) -> ";
+    r += krate;
+    r += "::Result<";
+    r += shape.ident;
+    r += "> { <";
+    r += shape.ident;
+    r += " as ";
+    r += krate;
+    r += "::Codec>::decode_limited(self.buffer,&self.limits).map(|(_,f)| f) } ";
+    for k in 0..variant.fields.len() {
+        r += &render_accessor(krate,variant,k);
+    }
+    r += "}";
+    r
+}
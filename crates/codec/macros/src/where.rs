@@ -19,7 +19,7 @@ impl fmt::Display for Where {
         match self {
             Where::Lifetime { ident,bounds }=> {
                 let mut a = String::new();
-                a += &format!("{}",ident);
+                a += &format!("'{}",ident);
                 if bounds.len() > 0 {
                     a += &format!(": ");
                     let mut first = true;
@@ -74,7 +74,10 @@ impl fmt::Display for Where {
 impl Lexer {
 
     fn is_where(&self) -> bool {
-        if self.is_punct(':') {
+        if self.is_punct('\'') {
+            true
+        }
+        else if self.is_punct(':') {
             true
         }
         else if self.is_punct('<') {
@@ -102,13 +105,13 @@ impl Lexer {
         if self.parse_ident("where") {
             let mut wheres = Vec::<Where>::new();
             while self.is_where() {
-                if self.is_punct('\'') {
+                if self.parse_punct('\'') {
                     if let Some(ident) = self.parse_some_ident() {
                         let mut bounds = Vec::<String>::new();
                         if self.parse_punct(':') {
                             while self.parse_punct('\'') {
-                                if self.is_some_ident() {
-                                    bounds.push(ident.to_string());
+                                if let Some(bound) = self.parse_some_ident() {
+                                    bounds.push(bound);
                                 }
                                 else {
                                     panic!("identifier expected after `'`");
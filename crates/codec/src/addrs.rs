@@ -11,8 +11,13 @@ use {
 };
 
 impl Codec for Ipv4Addr {
-    fn decode(buffer: &[u8]) -> Option<(usize,Self)> {
-        Some((4,Ipv4Addr::new(buffer[0],buffer[1],buffer[2],buffer[3])))
+    const FIXED_SIZE: Option<usize> = Some(4);
+
+    fn decode_with(buffer: &[u8],_: &mut Limiter) -> Result<(usize,Self)> {
+        if buffer.len() < 4 {
+            return Err(Error::Truncated);
+        }
+        Ok((4,Ipv4Addr::new(buffer[0],buffer[1],buffer[2],buffer[3])))
     }
 
     fn encode(&self,buffer: &mut Vec<u8>) -> usize {
@@ -29,17 +34,26 @@ impl Codec for Ipv4Addr {
     }   
 }
 
+impl FixedSize for Ipv4Addr {
+    const SIZE: usize = 4;
+}
+
 impl Codec for Ipv6Addr {
-    fn decode(buffer: &[u8]) -> Option<(usize,Self)> {
-        let a = { if let Some((_,r)) = u16::decode(&buffer[0..]) { r } else { return None; } };
-        let b = { if let Some((_,r)) = u16::decode(&buffer[2..]) { r } else { return None; } };
-        let c = { if let Some((_,r)) = u16::decode(&buffer[4..]) { r } else { return None; } };
-        let d = { if let Some((_,r)) = u16::decode(&buffer[6..]) { r } else { return None; } };
-        let e = { if let Some((_,r)) = u16::decode(&buffer[8..]) { r } else { return None; } };
-        let f = { if let Some((_,r)) = u16::decode(&buffer[10..]) { r } else { return None; } };
-        let g = { if let Some((_,r)) = u16::decode(&buffer[12..]) { r } else { return None; } };
-        let h = { if let Some((_,r)) = u16::decode(&buffer[14..]) { r } else { return None; } };
-        Some((16,Ipv6Addr::new(a,b,c,d,e,f,g,h)))
+    const FIXED_SIZE: Option<usize> = Some(16);
+
+    fn decode_with(buffer: &[u8],limiter: &mut Limiter) -> Result<(usize,Self)> {
+        if buffer.len() < 16 {
+            return Err(Error::Truncated);
+        }
+        let a = u16::decode_with(&buffer[0..],limiter)?.1;
+        let b = u16::decode_with(&buffer[2..],limiter)?.1;
+        let c = u16::decode_with(&buffer[4..],limiter)?.1;
+        let d = u16::decode_with(&buffer[6..],limiter)?.1;
+        let e = u16::decode_with(&buffer[8..],limiter)?.1;
+        let f = u16::decode_with(&buffer[10..],limiter)?.1;
+        let g = u16::decode_with(&buffer[12..],limiter)?.1;
+        let h = u16::decode_with(&buffer[14..],limiter)?.1;
+        Ok((16,Ipv6Addr::new(a,b,c,d,e,f,g,h)))
     }
 
     fn encode(&self,buffer: &mut Vec<u8>) -> usize {
@@ -60,16 +74,19 @@ impl Codec for Ipv6Addr {
     }   
 }
 
+impl FixedSize for Ipv6Addr {
+    const SIZE: usize = 16;
+}
+
 impl Codec for IpAddr {
-    fn decode(buffer: &[u8]) -> Option<(usize,Self)> {
-        match buffer[0] {
-            0 => if let Some((_,result)) = Ipv4Addr::decode(&buffer[1..]) {
-                Some((5,IpAddr::V4(result)))
-            } else { None }
-            1 => if let Some((_,result)) = Ipv6Addr::decode(&buffer[1..]) {
-                Some((17,IpAddr::V6(result)))
-            } else { None }
-            _ => None,
+    const MAX_SIZE: Option<usize> = Some(17);
+
+    fn decode_with(buffer: &[u8],limiter: &mut Limiter) -> Result<(usize,Self)> {
+        let (_,tag) = u8::decode_with(buffer,limiter)?;
+        match tag {
+            0 => Ok((5,IpAddr::V4(Ipv4Addr::decode_with(&buffer[1..],limiter)?.1))),
+            1 => Ok((17,IpAddr::V6(Ipv6Addr::decode_with(&buffer[1..],limiter)?.1))),
+            _ => Err(Error::InvalidTag(tag as u32)),
         }
     }
 
@@ -97,13 +114,14 @@ impl Codec for IpAddr {
 }
 
 impl Codec for SocketAddrV4 {
-    fn decode(buffer: &[u8]) -> Option<(usize,Self)> {
-        if let Some((_,port)) = u16::decode(&buffer[4..]) {
-            Some((6,SocketAddrV4::new(Ipv4Addr::new(buffer[0],buffer[1],buffer[2],buffer[3]),port)))
-        }
-        else {
-            None
+    const FIXED_SIZE: Option<usize> = Some(6);
+
+    fn decode_with(buffer: &[u8],limiter: &mut Limiter) -> Result<(usize,Self)> {
+        if buffer.len() < 6 {
+            return Err(Error::Truncated);
         }
+        let (_,port) = u16::decode_with(&buffer[4..],limiter)?;
+        Ok((6,SocketAddrV4::new(Ipv4Addr::new(buffer[0],buffer[1],buffer[2],buffer[3]),port)))
     }
 
     fn encode(&self,buffer: &mut Vec<u8>) -> usize {
@@ -121,18 +139,27 @@ impl Codec for SocketAddrV4 {
     }   
 }
 
+impl FixedSize for SocketAddrV4 {
+    const SIZE: usize = 6;
+}
+
 impl Codec for SocketAddrV6 {
-    fn decode(buffer: &[u8]) -> Option<(usize,Self)> {
-        let a = { if let Some((_,r)) = u16::decode(&buffer[0..]) { r } else { return None; } };
-        let b = { if let Some((_,r)) = u16::decode(&buffer[2..]) { r } else { return None; } };
-        let c = { if let Some((_,r)) = u16::decode(&buffer[4..]) { r } else { return None; } };
-        let d = { if let Some((_,r)) = u16::decode(&buffer[6..]) { r } else { return None; } };
-        let e = { if let Some((_,r)) = u16::decode(&buffer[8..]) { r } else { return None; } };
-        let f = { if let Some((_,r)) = u16::decode(&buffer[10..]) { r } else { return None; } };
-        let g = { if let Some((_,r)) = u16::decode(&buffer[12..]) { r } else { return None; } };
-        let h = { if let Some((_,r)) = u16::decode(&buffer[14..]) { r } else { return None; } };
-        let port = { if let Some((_,r)) = u16::decode(&buffer[16..]) { r } else { return None; } };
-        Some((18,SocketAddrV6::new(Ipv6Addr::new(a,b,c,d,e,f,g,h),port,0,0)))
+    const FIXED_SIZE: Option<usize> = Some(18);
+
+    fn decode_with(buffer: &[u8],limiter: &mut Limiter) -> Result<(usize,Self)> {
+        if buffer.len() < 18 {
+            return Err(Error::Truncated);
+        }
+        let a = u16::decode_with(&buffer[0..],limiter)?.1;
+        let b = u16::decode_with(&buffer[2..],limiter)?.1;
+        let c = u16::decode_with(&buffer[4..],limiter)?.1;
+        let d = u16::decode_with(&buffer[6..],limiter)?.1;
+        let e = u16::decode_with(&buffer[8..],limiter)?.1;
+        let f = u16::decode_with(&buffer[10..],limiter)?.1;
+        let g = u16::decode_with(&buffer[12..],limiter)?.1;
+        let h = u16::decode_with(&buffer[14..],limiter)?.1;
+        let port = u16::decode_with(&buffer[16..],limiter)?.1;
+        Ok((18,SocketAddrV6::new(Ipv6Addr::new(a,b,c,d,e,f,g,h),port,0,0)))
     }
 
     fn encode(&self,buffer: &mut Vec<u8>) -> usize {
@@ -154,16 +181,19 @@ impl Codec for SocketAddrV6 {
     }   
 }
 
+impl FixedSize for SocketAddrV6 {
+    const SIZE: usize = 18;
+}
+
 impl Codec for SocketAddr {
-    fn decode(buffer: &[u8]) -> Option<(usize,Self)> {
-        match buffer[0] {
-            0 => if let Some((_,result)) = SocketAddrV4::decode(&buffer[1..]) {
-                Some((7,SocketAddr::V4(result)))
-            } else { None }
-            1 => if let Some((_,result)) = SocketAddrV6::decode(&buffer[1..]) {
-                Some((19,SocketAddr::V6(result)))
-            } else { None }
-            _ => None,
+    const MAX_SIZE: Option<usize> = Some(19);
+
+    fn decode_with(buffer: &[u8],limiter: &mut Limiter) -> Result<(usize,Self)> {
+        let (_,tag) = u8::decode_with(buffer,limiter)?;
+        match tag {
+            0 => Ok((7,SocketAddr::V4(SocketAddrV4::decode_with(&buffer[1..],limiter)?.1))),
+            1 => Ok((19,SocketAddr::V6(SocketAddrV6::decode_with(&buffer[1..],limiter)?.1))),
+            _ => Err(Error::InvalidTag(tag as u32)),
         }
     }
 
@@ -184,8 +214,8 @@ impl Codec for SocketAddr {
 
     fn size(&self) -> usize {
         match self {
-            SocketAddr::V4(_) => 5,
-            SocketAddr::V6(_) => 17,
+            SocketAddr::V4(_) => 7,
+            SocketAddr::V6(_) => 19,
         }
     }   
 }
@@ -208,7 +238,7 @@ mod tests {
         let source = Ipv4Addr::new(192,168,1,255);
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
-        if let Some((_,target)) = Ipv4Addr::decode(&buffer) {
+        if let Ok((_,target)) = Ipv4Addr::decode(&buffer) {
             assert_eq!(source,target)
         }
         else {
@@ -221,7 +251,7 @@ mod tests {
         let source = Ipv6Addr::new(0xFDEC,0xC0BB,0xC329,0,0,0,0,1);
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
-        if let Some((_,target)) = Ipv6Addr::decode(&buffer) {
+        if let Ok((_,target)) = Ipv6Addr::decode(&buffer) {
             assert_eq!(source,target)
         }
         else {
@@ -234,7 +264,7 @@ mod tests {
         let source = IpAddr::V4(Ipv4Addr::new(192,168,1,255));
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
-        if let Some((_,target)) = IpAddr::decode(&buffer) {
+        if let Ok((_,target)) = IpAddr::decode(&buffer) {
             assert_eq!(source,target)
         }
         else {
@@ -247,7 +277,7 @@ mod tests {
         let source = SocketAddrV4::new(Ipv4Addr::new(192,168,1,255),8080);
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
-        if let Some((_,target)) = SocketAddrV4::decode(&buffer) {
+        if let Ok((_,target)) = SocketAddrV4::decode(&buffer) {
             assert_eq!(source,target)
         }
         else {
@@ -260,7 +290,7 @@ mod tests {
         let source = SocketAddrV6::new(Ipv6Addr::new(0xFDEC,0xC0BB,0xC329,0,0,0,0,1),8080,0,0);
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
-        if let Some((_,target)) = SocketAddrV6::decode(&buffer) {
+        if let Ok((_,target)) = SocketAddrV6::decode(&buffer) {
             assert_eq!(source,target)
         }
         else {
@@ -273,7 +303,7 @@ mod tests {
         let source = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(192,168,1,255),8080));
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
-        if let Some((_,target)) = SocketAddr::decode(&buffer) {
+        if let Ok((_,target)) = SocketAddr::decode(&buffer) {
             assert_eq!(source,target)
         }
         else {
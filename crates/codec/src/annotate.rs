@@ -0,0 +1,160 @@
+// Echidna - Codec
+
+use {
+    crate::*,
+    std::{
+        any,
+        ops::Range,
+    },
+};
+
+/// Byte range of an encoding, and the field path that produced it.
+#[derive(Clone,Debug,PartialEq)]
+pub struct Annotation {
+    pub range: Range<usize>,
+    pub path: String,
+}
+
+/// Walks an encoding with `Codec::annotate_with`, collecting annotations.
+pub struct Annotator<'a> {
+    buffer: &'a [u8],
+    pos: usize,
+    limiter: Limiter,
+    annotations: Vec<Annotation>,
+}
+
+impl<'a> Annotator<'a> {
+
+    pub fn new(buffer: &'a [u8],limits: &DecodeLimits) -> Annotator<'a> {
+        Annotator {
+            buffer: buffer,
+            pos: 0,
+            limiter: Limiter::new(limits),
+            annotations: Vec::new(),
+        }
+    }
+
+    /// Offset of the next byte to annotate.
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// The bytes not annotated yet.
+    pub fn rest(&self) -> &'a [u8] {
+        self.buffer.get(self.pos..).unwrap_or(&[])
+    }
+
+    pub fn limiter(&mut self) -> &mut Limiter {
+        &mut self.limiter
+    }
+
+    pub fn annotations(&self) -> &[Annotation] {
+        &self.annotations
+    }
+
+    /// Annotate the next `len` bytes as `path`.
+    pub fn leaf(&mut self,path: &str,len: usize) -> Result<()> {
+        if self.rest().len() < len {
+            return Err(Error::Truncated);
+        }
+        self.annotations.push(Annotation {
+            range: self.pos..self.pos + len,
+            path: path.to_string(),
+        });
+        self.pos += len;
+        Ok(())
+    }
+
+    /// Annotate the `u32` length prefix of collection `path` as `path[len]`.
+    pub fn length(&mut self,path: &str) -> Result<usize> {
+        let (_,len) = u32::decode_with(self.rest(),&mut self.limiter)?;
+        self.leaf(&format!("{}[len]",path),4)?;
+        Ok(len as usize)
+    }
+
+    /// Annotate `len` elements of collection `path` with `item` as
+    /// `path[0]`, `path[1]`, etc. Elements that are one range each, like
+    /// the bytes of a `Vec<u8>`, are merged into one `path[0..len]`.
+    pub fn items<F: FnMut(&mut Annotator<'a>,&str) -> Result<()>>(&mut self,path: &str,len: usize,mut item: F) -> Result<()> {
+        let first = self.annotations.len();
+        let start = self.pos;
+        let mut leaves = true;
+        for i in 0..len {
+            let before = self.annotations.len();
+            let item_path = format!("{}[{}]",path,i);
+            item(self,&item_path)?;
+            leaves &= (self.annotations.len() == before + 1) && (self.annotations[before].path == item_path);
+        }
+        if leaves && (len > 1) {
+            self.annotations.truncate(first);
+            self.annotations.push(Annotation {
+                range: start..self.pos,
+                path: format!("{}[0..{}]",path,len),
+            });
+        }
+        Ok(())
+    }
+}
+
+// `data::protocol::Chunk` -> `Chunk`, `alloc::vec::Vec<u8>` -> `Vec<u8>`
+fn short_type_name<T>() -> String {
+    let mut r = String::new();
+    let mut segment = String::new();
+    for c in any::type_name::<T>().chars() {
+        if c.is_alphanumeric() || (c == '_') || (c == ':') {
+            segment.push(c);
+        }
+        else {
+            r += segment.rsplit("::").next().unwrap();
+            segment.clear();
+            r.push(c);
+        }
+    }
+    r += segment.rsplit("::").next().unwrap();
+    r
+}
+
+/// Print every byte range of `buffer`, decoded as `T`, next to the field
+/// path that produced it.
+///
+/// If the bytes don't decode as `T`, the output stops with the error at
+/// the offset where it happened, which is usually where two peers started
+/// to disagree about the layout.
+pub fn annotate<T: Codec>(buffer: &[u8]) -> String {
+    let mut annotator = Annotator::new(buffer,&DecodeLimits::default());
+    let result = T::annotate_with(&mut annotator,&short_type_name::<T>());
+    let width = annotator.annotations().iter().map(|a| a.path.len()).max().unwrap_or(0);
+    let mut r = String::new();
+    for a in annotator.annotations() {
+        r += &format!("0x{:04X}..0x{:04X}  {:width$} ",a.range.start,a.range.end,a.path,width = width);
+        for b in buffer[a.range.clone()].iter().take(16) {
+            r += &format!(" {:02X}",b);
+        }
+        if a.range.len() > 16 {
+            r += " ..";
+        }
+        r += "\n";
+    }
+    match result {
+        Ok(()) => if annotator.position() < buffer.len() {
+            r += &format!("0x{:04X}..0x{:04X}  {} trailing bytes\n",annotator.position(),buffer.len(),buffer.len() - annotator.position());
+        },
+        Err(error) => {
+            r += &format!("0x{:04X}  error: {}\n",annotator.position(),error);
+        },
+    }
+    r
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_short_type_name() {
+        assert_eq!(short_type_name::<u8>(),"u8");
+        assert_eq!(short_type_name::<Vec<String>>(),"Vec<String>");
+        assert_eq!(short_type_name::<std::collections::HashMap<u16,Vec<u8>>>(),"HashMap<u16, Vec<u8>>");
+    }
+}
@@ -0,0 +1,223 @@
+// Echidna - Codec
+
+use {
+    crate::*,
+    std::mem,
+};
+
+/// Field types that `#[codec(bits = N)]` can pack into shared bytes.
+///
+/// Values are stored in their lowest `N` bits. Values that don't fit
+/// panic in debug builds, release builds drop the bits above. Signed
+/// integers are sign-extended when decoding, so a field with `bits = 4`
+/// holds `-8..=7`.
+pub trait Packed: Sized {
+
+    /// The value as bits, only the lowest `n` of which end up on the wire.
+    fn to_bits(&self) -> u64;
+
+    /// Value from the lowest `n` bits of `bits`.
+    fn from_bits(bits: u64,n: u32) -> Self;
+}
+
+fn mask(n: u32) -> u64 {
+    if n >= 64 { !0 } else { (1u64 << n) - 1 }
+}
+
+impl Packed for bool {
+    fn to_bits(&self) -> u64 {
+        *self as u64
+    }
+
+    fn from_bits(bits: u64,n: u32) -> Self {
+        (bits & mask(n)) != 0
+    }
+}
+
+macro_rules! packed_unsigned {
+    ($($t:ty),*) => {
+        $(
+            impl Packed for $t {
+                fn to_bits(&self) -> u64 {
+                    *self as u64
+                }
+
+                fn from_bits(bits: u64,n: u32) -> Self {
+                    (bits & mask(n)) as $t
+                }
+            }
+        )*
+    };
+}
+
+packed_unsigned!(u8,u16,u32,u64);
+
+macro_rules! packed_signed {
+    ($($t:ty),*) => {
+        $(
+            impl Packed for $t {
+                fn to_bits(&self) -> u64 {
+                    *self as i64 as u64
+                }
+
+                fn from_bits(bits: u64,n: u32) -> Self {
+                    let shift = 64 - n.clamp(1,64);
+                    (((bits << shift) as i64) >> shift) as $t
+                }
+            }
+        )*
+    };
+}
+
+packed_signed!(i8,i16,i32,i64);
+
+/// The lowest `n` bits of `value`, for a field with `#[codec(bits = N)]`.
+///
+/// Panics in debug builds if `value` doesn't fit in `n` bits.
+pub fn pack_bits<T: Packed>(value: &T,n: u32) -> u64 {
+    let bits = value.to_bits() & mask(n);
+    debug_assert!(T::from_bits(bits,n).to_bits() == value.to_bits(),"value doesn't fit in {} bits",n);
+    bits
+}
+
+/// Read the `len` byte little-endian group of packed fields at the start of `buffer`.
+pub fn decode_packed(buffer: &[u8],len: usize) -> Result<u64> {
+    let bytes = buffer.get(..len).ok_or(Error::Truncated)?;
+    let mut bits = 0u64;
+    for (i,byte) in bytes.iter().enumerate() {
+        bits |= (*byte as u64) << (8 * i);
+    }
+    Ok(bits)
+}
+
+/// Write `bits` as a `len` byte little-endian group of packed fields.
+///
+/// Returns the number of bytes appended to the buffer. Panics in debug
+/// builds if `bits` doesn't fit in `len` bytes.
+pub fn encode_packed(bits: u64,len: usize,buffer: &mut Vec<u8>) -> usize {
+    debug_assert!((len >= 8) || ((bits >> (8 * len)) == 0),"bits don't fit in {} bytes",len);
+    buffer.extend_from_slice(&bits.to_le_bytes()[..len]);
+    len
+}
+
+/// `Vec<bool>` that goes over the wire as a bitset.
+///
+/// The `u32` number of bits is followed by the bits themselves, 8 per
+/// byte, first bit in the lowest bit of the first byte. A field of type
+/// `Vec<bool>` with `#[codec(bits)]` is encoded like this.
+#[derive(Clone,Debug,Default,PartialEq,Eq)]
+pub struct Bitset(pub Vec<bool>);
+
+impl Bitset {
+
+    /// Encode `bits` as a bitset onto the end of `buffer`.
+    ///
+    /// Returns the number of bytes appended to the buffer.
+    pub fn encode_bools(bits: &[bool],buffer: &mut Vec<u8>) -> usize {
+        (bits.len() as u32).encode(buffer);
+        for chunk in bits.chunks(8) {
+            let mut byte = 0u8;
+            for (i,bit) in chunk.iter().enumerate() {
+                if *bit {
+                    byte |= 1 << i;
+                }
+            }
+            buffer.push(byte);
+        }
+        Self::size_bools(bits)
+    }
+
+    /// Size of `bits` encoded as a bitset.
+    pub fn size_bools(bits: &[bool]) -> usize {
+        4 + bits.len().div_ceil(8)
+    }
+
+    fn decode_len(buffer: &[u8],limiter: &mut Limiter) -> Result<(usize,usize)> {
+        let (_,len) = u32::decode_with(buffer,limiter)?;
+        let len = len as usize;
+        let bytes = len.div_ceil(8);
+        if buffer.len() - 4 < bytes {
+            return Err(Error::Truncated);
+        }
+        Ok((len,bytes))
+    }
+}
+
+impl Codec for Bitset {
+    fn decode_with(buffer: &[u8],limiter: &mut Limiter) -> Result<(usize,Self)> {
+        let (len,bytes) = Self::decode_len(buffer,limiter)?;
+        limiter.collection(len,mem::size_of::<bool>())?;
+        let bits = (0..len).map(|i| (buffer[4 + i / 8] & (1 << (i % 8))) != 0).collect();
+        Ok((4 + bytes,Bitset(bits)))
+    }
+
+    fn decode_into_with(&mut self,buffer: &[u8],limiter: &mut Limiter) -> Result<usize> {
+        let (len,bytes) = Self::decode_len(buffer,limiter)?;
+        limiter.collection(len,mem::size_of::<bool>())?;
+        self.0.clear();
+        self.0.extend((0..len).map(|i| (buffer[4 + i / 8] & (1 << (i % 8))) != 0));
+        Ok(4 + bytes)
+    }
+
+    fn skip_with(buffer: &[u8],limiter: &mut Limiter) -> Result<usize> {
+        let (len,bytes) = Self::decode_len(buffer,limiter)?;
+        limiter.collection(len,mem::size_of::<bool>())?;
+        Ok(4 + bytes)
+    }
+
+    fn annotate_with(annotator: &mut Annotator,path: &str) -> Result<()> {
+        let len = annotator.length(path)?;
+        let bytes = len.div_ceil(8);
+        annotator.limiter().collection(len,mem::size_of::<bool>())?;
+        if bytes > 0 {
+            annotator.leaf(&format!("{}[0..{}]",path,len),bytes)?;
+        }
+        Ok(())
+    }
+
+    fn encode(&self,buffer: &mut Vec<u8>) -> usize {
+        Self::encode_bools(&self.0,buffer)
+    }
+
+    fn size(&self) -> usize {
+        Self::size_bools(&self.0)
+    }
+}
+
+impl From<Vec<bool>> for Bitset {
+    fn from(bits: Vec<bool>) -> Self {
+        Bitset(bits)
+    }
+}
+
+impl From<Bitset> for Vec<bool> {
+    fn from(bitset: Bitset) -> Self {
+        bitset.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn test_packed() {
+        assert_eq!(i8::from_bits((-3i8).to_bits() & 0xF,4),-3);
+        assert_eq!(i8::from_bits(7,4),7);
+        assert_eq!(u8::from_bits(0x1FF,8),0xFF);
+        assert!(bool::from_bits(0b10,2));
+        assert_eq!(decode_packed(&[0x34,0x12,0xFF],2),Ok(0x1234));
+        assert_eq!(decode_packed(&[0x34],2),Err(Error::Truncated));
+    }
+
+    #[test]
+    fn test_bitset() {
+        let bitset = Bitset(vec![true,false,true,true,false,false,false,false,true]);
+        let buffer = bitset.encode_to_vec();
+        assert_eq!(buffer,vec![9,0,0,0,0b00001101,0b00000001]);
+        assert_eq!(Bitset::decode(&buffer),Ok((6,bitset)));
+        assert_eq!(Bitset::decode(&buffer[..5]),Err(Error::Truncated));
+        assert_eq!(Bitset(Vec::new()).encode_to_vec(),vec![0,0,0,0]);
+    }
+}
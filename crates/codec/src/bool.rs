@@ -3,8 +3,13 @@
 use crate::*;
 
 impl Codec for bool {
-    fn decode(buffer: &[u8]) -> Option<(usize,Self)> {
-        Some((1,buffer[0] != 0))
+    const FIXED_SIZE: Option<usize> = Some(1);
+
+    fn decode_with(buffer: &[u8],_: &mut Limiter) -> Result<(usize,Self)> {
+        if buffer.is_empty() {
+            return Err(Error::Truncated);
+        }
+        Ok((1,buffer[0] != 0))
     }
 
     fn encode(&self,buffer: &mut Vec<u8>) -> usize {
@@ -17,6 +22,10 @@ impl Codec for bool {
     }
 }
 
+impl FixedSize for bool {
+    const SIZE: usize = 1;
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -27,7 +36,7 @@ mod tests {
         let source = true;
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
-        if let Some((_,target)) = bool::decode(&buffer) {
+        if let Ok((_,target)) = bool::decode(&buffer) {
             assert_eq!(source,target)
         }
         else {
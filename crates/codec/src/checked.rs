@@ -0,0 +1,173 @@
+// Echidna - Codec
+
+use {
+    crate::*,
+    std::ops::{
+        Deref,
+        DerefMut,
+    },
+};
+
+// table for the reflected Castagnoli polynomial
+const CRC32C_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut k = 0;
+        while k < 8 {
+            crc = if (crc & 1) != 0 { (crc >> 1) ^ 0x82F63B78 } else { crc >> 1 };
+            k += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// CRC-32C (Castagnoli) of `data`.
+pub fn crc32c(data: &[u8]) -> u32 {
+    !crc32c_update(0xFFFFFFFF,data)
+}
+
+fn crc32c_update(mut crc: u32,data: &[u8]) -> u32 {
+    for b in data {
+        crc = CRC32C_TABLE[((crc ^ (*b as u32)) & 0xFF) as usize] ^ (crc >> 8);
+    }
+    crc
+}
+
+/// Value that is followed by a CRC-32C over its encoding on the wire.
+///
+/// Decoding checks the CRC before decoding the value, and fails with
+/// `Error::ChecksumMismatch` if the bytes were corrupted on the way.
+#[derive(Clone,Copy,Debug,Default,PartialEq,Eq,Hash)]
+pub struct Checked<T>(pub T);
+
+impl<T> Deref for Checked<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Checked<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T> From<T> for Checked<T> {
+    fn from(value: T) -> Self {
+        Checked(value)
+    }
+}
+
+// find the end of the value with `skip`, and check the CRC after it
+fn verify<T: Codec>(buffer: &[u8]) -> Result<usize> {
+    let len = T::skip(buffer)?;
+    let (_,crc) = u32::decode(&buffer[len..])?;
+    if crc != crc32c(&buffer[..len]) {
+        return Err(Error::ChecksumMismatch);
+    }
+    Ok(len)
+}
+
+impl<T: Codec> Codec for Checked<T> {
+
+    const FIXED_SIZE: Option<usize> = match T::FIXED_SIZE {
+        Some(n) => Some(n + 4),
+        None => None,
+    };
+
+    const MAX_SIZE: Option<usize> = match T::MAX_SIZE {
+        Some(n) => Some(n + 4),
+        None => None,
+    };
+
+    fn decode_with(buffer: &[u8],limiter: &mut Limiter) -> Result<(usize,Self)> {
+        let len = verify::<T>(buffer)?;
+        let (_,value) = T::decode_with(&buffer[..len],limiter)?;
+        Ok((len + 4,Checked(value)))
+    }
+
+    fn decode_into_with(&mut self,buffer: &[u8],limiter: &mut Limiter) -> Result<usize> {
+        let len = verify::<T>(buffer)?;
+        self.0.decode_into_with(&buffer[..len],limiter)?;
+        Ok(len + 4)
+    }
+
+    fn skip_with(buffer: &[u8],limiter: &mut Limiter) -> Result<usize> {
+        let len = verify::<T>(buffer)?;
+        T::skip_with(&buffer[..len],limiter)?;
+        Ok(len + 4)
+    }
+
+    fn annotate_with(annotator: &mut Annotator,path: &str) -> Result<()> {
+        T::annotate_with(annotator,path)?;
+        annotator.leaf(&format!("{}[crc32c]",path),4)
+    }
+
+    fn encode(&self,buffer: &mut Vec<u8>) -> usize {
+        let start = buffer.len();
+        let len = self.0.encode(buffer);
+        crc32c(&buffer[start..]).encode(buffer);
+        len + 4
+    }
+
+    fn encode_canonical(&self,buffer: &mut Vec<u8>) -> usize {
+        let start = buffer.len();
+        let len = self.0.encode_canonical(buffer);
+        crc32c(&buffer[start..]).encode(buffer);
+        len + 4
+    }
+
+    // the CRC runs over the parts, wherever they are
+    fn encode_gather<'a>(&'a self,gather: &mut Gather<'a>) -> usize {
+        let start = gather.len();
+        let len = self.0.encode_gather(gather);
+        let crc = gather.parts_from(start).iter().fold(0xFFFFFFFF,|crc,part| crc32c_update(crc,part));
+        (!crc).encode(gather.buffer());
+        len + 4
+    }
+
+    fn size(&self) -> usize {
+        self.0.size() + 4
+    }
+}
+
+impl<T: FixedSize> FixedSize for Checked<T> {
+    const SIZE: usize = T::SIZE + 4;
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn test_crc32c() {
+        assert_eq!(crc32c(b""),0);
+        assert_eq!(crc32c(b"123456789"),0xE3069283);
+        assert_eq!(crc32c(&[0u8; 32]),0x8A9136AA);
+    }
+
+    #[test]
+    fn test_checked() {
+        let source = Checked(vec![1u8,2,3]);
+        let buffer = source.encode_to_vec();
+        assert_eq!(buffer.len(),source.size());
+        assert_eq!(Checked::<Vec<u8>>::decode(&buffer),Ok((buffer.len(),source)));
+        for bit in 0..buffer.len() * 8 {
+            let mut flipped = buffer.clone();
+            flipped[bit / 8] ^= 1 << (bit % 8);
+            match Checked::<Vec<u8>>::decode(&flipped) {
+                // a flipped length can make the value run past the end of the buffer
+                Err(Error::Truncated) => assert!(bit < 32),
+                result => assert_eq!(result,Err(Error::ChecksumMismatch)),
+            }
+        }
+        assert_eq!(<Checked<u32> as FixedSize>::SIZE,8);
+    }
+}
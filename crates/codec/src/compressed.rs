@@ -0,0 +1,369 @@
+// Echidna - Codec
+
+use {
+    crate::*,
+    std::{
+        fmt,
+        ops::{
+            Deref,
+            DerefMut,
+        },
+        sync::OnceLock,
+    },
+};
+
+const STORED: u8 = 0;
+const LZ: u8 = 1;
+
+const HASH_BITS: u32 = 12;
+const MIN_MATCH: usize = 4;
+const MAX_OFFSET: usize = 65535;
+
+// length in a token nibble, with 255-byte continuation bytes if it doesn't fit
+fn write_length(out: &mut Vec<u8>,mut len: usize) {
+    while len >= 255 {
+        out.push(255);
+        len -= 255;
+    }
+    out.push(len as u8);
+}
+
+// one sequence: literals, followed by a match unless this is the last one
+fn write_sequence(out: &mut Vec<u8>,literals: &[u8],matched: Option<(usize,usize)>) {
+    let lit_nibble = literals.len().min(15) as u8;
+    let match_nibble = matched.map(|(_,len)| (len - MIN_MATCH).min(15) as u8).unwrap_or(0);
+    out.push((lit_nibble << 4) | match_nibble);
+    if literals.len() >= 15 {
+        write_length(out,literals.len() - 15);
+    }
+    out.extend_from_slice(literals);
+    if let Some((offset,len)) = matched {
+        out.extend_from_slice(&(offset as u16).to_le_bytes());
+        if len - MIN_MATCH >= 15 {
+            write_length(out,len - MIN_MATCH - 15);
+        }
+    }
+}
+
+/// Compress `input` with a fast LZ77 compressor (LZ4-style sequences of
+/// literals and back references).
+pub fn lz_compress(input: &[u8]) -> Vec<u8> {
+    let mut out = Vec::<u8>::with_capacity(input.len() / 2 + 16);
+    let mut table = vec![0usize; 1 << HASH_BITS];
+    let mut anchor = 0usize;
+    let mut i = 0usize;
+    while i + MIN_MATCH <= input.len() {
+        let seq = u32::from_le_bytes([input[i],input[i + 1],input[i + 2],input[i + 3]]);
+        let hash = (seq.wrapping_mul(2654435761) >> (32 - HASH_BITS)) as usize;
+        // positions are stored plus one, so 0 means empty
+        let candidate = table[hash];
+        table[hash] = i + 1;
+        if candidate > 0 {
+            let c = candidate - 1;
+            if (i - c <= MAX_OFFSET) && (input[c..c + MIN_MATCH] == input[i..i + MIN_MATCH]) {
+                let mut len = MIN_MATCH;
+                while (i + len < input.len()) && (input[c + len] == input[i + len]) {
+                    len += 1;
+                }
+                write_sequence(&mut out,&input[anchor..i],Some((i - c,len)));
+                i += len;
+                anchor = i;
+                continue;
+            }
+        }
+        i += 1;
+    }
+    write_sequence(&mut out,&input[anchor..],None);
+    out
+}
+
+fn read_length(input: &[u8],ip: &mut usize,nibble: usize) -> Result<usize> {
+    let mut len = nibble;
+    if nibble == 15 {
+        loop {
+            let b = *input.get(*ip).ok_or(Error::InvalidCompression)?;
+            *ip += 1;
+            len += b as usize;
+            if b != 255 {
+                break;
+            }
+        }
+    }
+    Ok(len)
+}
+
+/// Decompress the output of `lz_compress`, which must produce exactly
+/// `raw_len` bytes.
+pub fn lz_decompress(input: &[u8],raw_len: usize) -> Result<Vec<u8>> {
+    let mut out = Vec::<u8>::with_capacity(raw_len);
+    let mut ip = 0usize;
+    loop {
+        let token = *input.get(ip).ok_or(Error::InvalidCompression)?;
+        ip += 1;
+        let lit_len = read_length(input,&mut ip,(token >> 4) as usize)?;
+        let literals = input.get(ip..ip.saturating_add(lit_len)).ok_or(Error::InvalidCompression)?;
+        if out.len() + lit_len > raw_len {
+            return Err(Error::InvalidCompression);
+        }
+        out.extend_from_slice(literals);
+        ip += lit_len;
+        if ip == input.len() {
+            break;
+        }
+        let offset = u16::from_le_bytes([*input.get(ip).ok_or(Error::InvalidCompression)?,*input.get(ip + 1).ok_or(Error::InvalidCompression)?]) as usize;
+        ip += 2;
+        let len = read_length(input,&mut ip,(token & 15) as usize)? + MIN_MATCH;
+        if (offset == 0) || (offset > out.len()) || (out.len() + len > raw_len) {
+            return Err(Error::InvalidCompression);
+        }
+        // the match may overlap what it produces, so copy byte by byte
+        let start = out.len() - offset;
+        for k in 0..len {
+            let b = out[start + k];
+            out.push(b);
+        }
+    }
+    if out.len() != raw_len {
+        return Err(Error::InvalidCompression);
+    }
+    Ok(out)
+}
+
+/// Value that is LZ-compressed on the wire.
+///
+/// Encodings shorter than `THRESHOLD`, or that don't get smaller, are
+/// stored as they are. The compressed bytes are kept until the value is
+/// changed through `DerefMut`, so `size()`, `encode()` and `ratio()` only
+/// compress once.
+///
+/// On the wire this is a `u8` method (0 stored, 1 compressed) and the `u32`
+/// length of the encoding of the value, followed by that encoding, or by a
+/// `u32` length and the compressed bytes.
+pub struct Compressed<T> {
+    value: T,
+    packed: OnceLock<Vec<u8>>,
+}
+
+impl<T> Compressed<T> {
+
+    /// Encodings shorter than this are never compressed.
+    pub const THRESHOLD: usize = 128;
+
+    pub fn new(value: T) -> Self {
+        Compressed {
+            value: value,
+            packed: OnceLock::new(),
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T: Codec> Compressed<T> {
+
+    fn pack(&self,canonical: bool) -> Vec<u8> {
+        let mut raw = Vec::<u8>::with_capacity(self.value.size());
+        if canonical {
+            self.value.encode_canonical(&mut raw);
+        }
+        else {
+            self.value.encode(&mut raw);
+        }
+        let mut r = Vec::<u8>::new();
+        if raw.len() >= Self::THRESHOLD {
+            let lz = lz_compress(&raw);
+            if lz.len() + 4 < raw.len() {
+                LZ.encode(&mut r);
+                (raw.len() as u32).encode(&mut r);
+                (lz.len() as u32).encode(&mut r);
+                r.extend_from_slice(&lz);
+                return r;
+            }
+        }
+        STORED.encode(&mut r);
+        (raw.len() as u32).encode(&mut r);
+        r.extend_from_slice(&raw);
+        r
+    }
+
+    fn packed(&self) -> &[u8] {
+        self.packed.get_or_init(|| self.pack(false))
+    }
+
+    /// Size of the encoded value divided by the size on the wire, 1.0 or
+    /// slightly less if it is stored as it is.
+    pub fn ratio(&self) -> f64 {
+        (self.value.size() as f64) / (self.packed().len() as f64)
+    }
+}
+
+impl<T> Deref for Compressed<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+// the value may change, so forget the compressed bytes
+impl<T> DerefMut for Compressed<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.packed = OnceLock::new();
+        &mut self.value
+    }
+}
+
+impl<T> From<T> for Compressed<T> {
+    fn from(value: T) -> Self {
+        Compressed::new(value)
+    }
+}
+
+impl<T: Clone> Clone for Compressed<T> {
+    fn clone(&self) -> Self {
+        Compressed {
+            value: self.value.clone(),
+            packed: self.packed.clone(),
+        }
+    }
+}
+
+impl<T: Default> Default for Compressed<T> {
+    fn default() -> Self {
+        Compressed::new(T::default())
+    }
+}
+
+impl<T: PartialEq> PartialEq for Compressed<T> {
+    fn eq(&self,other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T: Eq> Eq for Compressed<T> { }
+
+impl<T: fmt::Debug> fmt::Debug for Compressed<T> {
+    fn fmt(&self,f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Compressed").field(&self.value).finish()
+    }
+}
+
+// header of the encoding: method, length of the encoded value, and where the value or the compressed bytes start
+fn header(buffer: &[u8],limiter: &mut Limiter) -> Result<(u8,usize,usize)> {
+    let (_,method) = u8::decode_with(buffer,limiter)?;
+    let (_,raw_len) = u32::decode_with(&buffer[1..],limiter)?;
+    match method {
+        STORED | LZ => Ok((method,raw_len as usize,5)),
+        _ => Err(Error::InvalidTag(method as u32)),
+    }
+}
+
+// the encoded value, decompressed if needed, and the total length of the encoding
+fn unpack<'a>(buffer: &'a [u8],limiter: &mut Limiter) -> Result<(std::borrow::Cow<'a,[u8]>,usize)> {
+    let (method,raw_len,ofs) = header(buffer,limiter)?;
+    if method == STORED {
+        let raw = buffer.get(ofs..ofs + raw_len).ok_or(Error::Truncated)?;
+        Ok((std::borrow::Cow::Borrowed(raw),ofs + raw_len))
+    }
+    else {
+        let (_,lz_len) = u32::decode_with(&buffer[ofs..],limiter)?;
+        let lz = buffer.get(ofs + 4..ofs + 4 + lz_len as usize).ok_or(Error::Truncated)?;
+        limiter.alloc(raw_len)?;
+        Ok((std::borrow::Cow::Owned(lz_decompress(lz,raw_len)?),ofs + 4 + lz_len as usize))
+    }
+}
+
+impl<T: Codec> Codec for Compressed<T> {
+
+    const MAX_SIZE: Option<usize> = match T::MAX_SIZE {
+        Some(n) => Some(n + 5),
+        None => None,
+    };
+
+    fn decode_with(buffer: &[u8],limiter: &mut Limiter) -> Result<(usize,Self)> {
+        let (raw,len) = unpack(buffer,limiter)?;
+        let (l,value) = T::decode_with(&raw,limiter)?;
+        if l != raw.len() {
+            return Err(Error::InvalidCompression);
+        }
+        Ok((len,Compressed::new(value)))
+    }
+
+    fn skip_with(buffer: &[u8],limiter: &mut Limiter) -> Result<usize> {
+        let (raw,len) = unpack(buffer,limiter)?;
+        if T::skip_with(&raw,limiter)? != raw.len() {
+            return Err(Error::InvalidCompression);
+        }
+        Ok(len)
+    }
+
+    fn annotate_with(annotator: &mut Annotator,path: &str) -> Result<()> {
+        let (method,raw_len,_) = header(annotator.rest(),annotator.limiter())?;
+        annotator.leaf(&format!("{}[method]",path),1)?;
+        annotator.leaf(&format!("{}[len]",path),4)?;
+        if method == STORED {
+            let start = annotator.position();
+            T::annotate_with(annotator,path)?;
+            if annotator.position() - start != raw_len {
+                return Err(Error::InvalidCompression);
+            }
+            Ok(())
+        }
+        else {
+            let lz_len = annotator.length(path)?;
+            annotator.leaf(&format!("{}[lz]",path),lz_len)
+        }
+    }
+
+    fn encode(&self,buffer: &mut Vec<u8>) -> usize {
+        let packed = self.packed();
+        buffer.extend_from_slice(packed);
+        packed.len()
+    }
+
+    fn encode_canonical(&self,buffer: &mut Vec<u8>) -> usize {
+        let packed = self.pack(true);
+        buffer.extend_from_slice(&packed);
+        packed.len()
+    }
+
+    fn size(&self) -> usize {
+        self.packed().len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn test_lz() {
+        let mut data = Vec::<u8>::new();
+        for i in 0..10000u32 {
+            data.push((i / 7) as u8);
+        }
+        let inputs: [&[u8]; 6] = [b"",b"a",b"abcabcabcabcabcabc",&[0u8; 1000],&data,b"no repeats here"];
+        for input in inputs.iter() {
+            let lz = lz_compress(input);
+            assert_eq!(lz_decompress(&lz,input.len()),Ok(input.to_vec()));
+        }
+        assert!(lz_compress(&data).len() * 4 < data.len());
+        assert_eq!(lz_decompress(&lz_compress(&data),data.len() - 1),Err(Error::InvalidCompression));
+        assert_eq!(lz_decompress(&[0x0F,0x01,0x00],20),Err(Error::InvalidCompression));
+    }
+
+    #[test]
+    fn test_compressed() {
+        let small = Compressed::new(vec![1u8,2,3]);
+        assert_eq!(small.encode_to_vec(),vec![0,7,0,0,0,3,0,0,0,1,2,3]);
+        let large = Compressed::new(vec![0u16; 1000]);
+        let buffer = large.encode_to_vec();
+        assert_eq!(buffer[0],1);
+        assert!(large.ratio() > 20.0);
+        assert_eq!(Compressed::<Vec<u16>>::decode(&buffer),Ok((buffer.len(),large)));
+        assert_eq!(Compressed::<Vec<u16>>::skip(&buffer),Ok(buffer.len()));
+    }
+}
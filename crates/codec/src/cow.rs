@@ -0,0 +1,123 @@
+// Echidna - Codec
+
+use {
+    crate::*,
+    std::borrow::Cow,
+};
+
+impl<'a> Codec for Cow<'a,str> {
+    fn decode_with(buffer: &[u8],limiter: &mut Limiter) -> Result<(usize,Self)> {
+        let (l,s) = String::decode_with(buffer,limiter)?;
+        Ok((l,Cow::Owned(s)))
+    }
+
+    fn skip_with(buffer: &[u8],limiter: &mut Limiter) -> Result<usize> {
+        String::skip_with(buffer,limiter)
+    }
+
+    fn annotate_with(annotator: &mut Annotator,path: &str) -> Result<()> {
+        String::annotate_with(annotator,path)
+    }
+
+    fn encode(&self,buffer: &mut Vec<u8>) -> usize {
+        let slice = self.as_bytes();
+        (slice.len() as u32).encode(buffer);
+        buffer.extend_from_slice(slice);
+        4 + slice.len()
+    }
+
+    fn encode_gather<'b>(&'b self,gather: &mut Gather<'b>) -> usize {
+        (self.len() as u32).encode(gather.buffer());
+        4 + gather.bytes(self.as_bytes())
+    }
+
+    fn size(&self) -> usize {
+        4 + self.len()
+    }
+}
+
+impl<'a,T: Codec + Clone> Codec for Cow<'a,[T]> {
+    fn decode_with(buffer: &[u8],limiter: &mut Limiter) -> Result<(usize,Self)> {
+        let (l,v) = Vec::<T>::decode_with(buffer,limiter)?;
+        Ok((l,Cow::Owned(v)))
+    }
+
+    fn skip_with(buffer: &[u8],limiter: &mut Limiter) -> Result<usize> {
+        Vec::<T>::skip_with(buffer,limiter)
+    }
+
+    fn annotate_with(annotator: &mut Annotator,path: &str) -> Result<()> {
+        Vec::<T>::annotate_with(annotator,path)
+    }
+
+    fn encode(&self,buffer: &mut Vec<u8>) -> usize {
+        (self.len() as u32).encode(buffer);
+        let mut len = 4;
+        for item in self.iter() {
+            len += item.encode(buffer);
+        }
+        len
+    }
+
+    fn encode_canonical(&self,buffer: &mut Vec<u8>) -> usize {
+        (self.len() as u32).encode(buffer);
+        let mut len = 4;
+        for item in self.iter() {
+            len += item.encode_canonical(buffer);
+        }
+        len
+    }
+
+    fn encode_gather<'b>(&'b self,gather: &mut Gather<'b>) -> usize {
+        (self.len() as u32).encode(gather.buffer());
+        if let Some(bytes) = T::slice_as_bytes(self) {
+            return 4 + gather.bytes(bytes);
+        }
+        let mut len = 4;
+        for item in self.iter() {
+            len += item.encode_gather(gather);
+        }
+        len
+    }
+
+    fn size(&self) -> usize {
+        let mut len = 4;
+        for item in self.iter() {
+            len += item.size();
+        }
+        len
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::Codec;
+    use std::borrow::Cow;
+
+    #[test]
+    fn test_cow_str() {
+        let source: Cow<str> = Cow::Borrowed("Hello, World!");
+        let mut buffer = Vec::<u8>::new();
+        source.encode(&mut buffer);
+        if let Ok((_,target)) = Cow::<str>::decode(&buffer) {
+            assert_eq!(source,target)
+        }
+        else {
+            assert!(false)
+        }
+    }
+
+    #[test]
+    fn test_cow_slice() {
+        let source: Cow<[u16]> = Cow::Borrowed(&[1,2,3]);
+        let mut buffer = Vec::<u8>::new();
+        source.encode(&mut buffer);
+        if let Ok((_,target)) = Cow::<[u16]>::decode(&buffer) {
+            assert_eq!(source,target)
+        }
+        else {
+            assert!(false)
+        }
+    }
+}
@@ -0,0 +1,147 @@
+// Echidna - Codec
+
+//! Encoding values as differences against a reference value.
+//!
+//! `#[codec(delta)]` derives `Delta` for a struct: a bitmask with one bit
+//! per field that differs from the reference, followed by those fields.
+//! Fields are compared with `PartialEq` and encoded whole, unchanged fields
+//! are cloned from the reference when decoding.
+//!
+//! `DeltaEncoder` and `DeltaDecoder` turn this into a stream of frames,
+//! where every so many frames a keyframe carries the full value. Deltas
+//! are against the last keyframe, so a lost delta doesn't break the ones
+//! after it, and late joiners can start at the next keyframe.
+
+use crate::*;
+
+/// Types that encode as a difference against a reference value.
+pub trait Delta: Codec {
+
+    /// Encode the fields of `self` that differ from `reference` onto the
+    /// end of `buffer`.
+    ///
+    /// Returns the number of bytes appended to the buffer.
+    fn encode_delta(&self,reference: &Self,buffer: &mut Vec<u8>) -> usize;
+
+    /// Decode a delta against `reference` from `buffer`, within the default
+    /// `DecodeLimits`.
+    fn decode_delta(reference: &Self,buffer: &[u8]) -> Result<(usize,Self)> {
+        Self::decode_delta_limited(reference,buffer,&DecodeLimits::default())
+    }
+
+    /// Decode a delta against `reference` from `buffer`, within `limits`.
+    fn decode_delta_limited(reference: &Self,buffer: &[u8],limits: &DecodeLimits) -> Result<(usize,Self)> {
+        Self::decode_delta_with(reference,buffer,&mut Limiter::new(limits))
+    }
+
+    /// Decode a delta against `reference` from `buffer`, charging `limiter`.
+    fn decode_delta_with(reference: &Self,buffer: &[u8],limiter: &mut Limiter) -> Result<(usize,Self)>;
+
+    /// Find the end of the delta at the start of `buffer`, without the
+    /// reference.
+    fn skip_delta_with(buffer: &[u8],limiter: &mut Limiter) -> Result<usize>;
+}
+
+const KEYFRAME: u8 = 0;
+const DELTA: u8 = 1;
+
+/// Writes a stream of keyframes and deltas.
+///
+/// Every frame starts with a `u8` kind (0 keyframe, 1 delta) and the `u32`
+/// number of the keyframe it belongs to.
+pub struct DeltaEncoder<T> {
+    interval: usize,
+    keyframe: Option<T>,
+    number: u32,
+    since: usize,
+}
+
+impl<T: Delta + Clone> DeltaEncoder<T> {
+
+    /// New encoder that emits a keyframe every `interval` frames.
+    pub fn new(interval: usize) -> DeltaEncoder<T> {
+        DeltaEncoder {
+            interval: interval.max(1),
+            keyframe: None,
+            number: 0,
+            since: 0,
+        }
+    }
+
+    /// Make the next frame a keyframe, for instance when someone new subscribes.
+    pub fn force_keyframe(&mut self) {
+        self.keyframe = None;
+    }
+
+    /// Encode the next frame for `value` onto the end of `buffer`.
+    ///
+    /// Returns the number of bytes appended to the buffer.
+    pub fn encode(&mut self,value: &T,buffer: &mut Vec<u8>) -> usize {
+        match &self.keyframe {
+            Some(keyframe) if self.since < self.interval => {
+                self.since += 1;
+                DELTA.encode(buffer) + self.number.encode(buffer) + value.encode_delta(keyframe,buffer)
+            },
+            _ => {
+                self.number = self.number.wrapping_add(1);
+                self.since = 1;
+                self.keyframe = Some(value.clone());
+                KEYFRAME.encode(buffer) + self.number.encode(buffer) + value.encode(buffer)
+            },
+        }
+    }
+}
+
+/// Reads a stream written by `DeltaEncoder`.
+pub struct DeltaDecoder<T> {
+    limits: DecodeLimits,
+    keyframe: Option<(u32,T)>,
+}
+
+impl<T: Delta + Clone> DeltaDecoder<T> {
+
+    pub fn new() -> DeltaDecoder<T> {
+        DeltaDecoder::with_limits(&DecodeLimits::default())
+    }
+
+    pub fn with_limits(limits: &DecodeLimits) -> DeltaDecoder<T> {
+        DeltaDecoder {
+            limits: *limits,
+            keyframe: None,
+        }
+    }
+
+    /// Decode the next frame from `buffer`.
+    ///
+    /// Returns the number of bytes decoded, and the value, or `None` if
+    /// this is a delta against a keyframe that was never received.
+    pub fn decode(&mut self,buffer: &[u8]) -> Result<(usize,Option<T>)> {
+        let mut limiter = Limiter::new(&self.limits);
+        let (_,kind) = u8::decode_with(buffer,&mut limiter)?;
+        let (_,number) = u32::decode_with(&buffer[1..],&mut limiter)?;
+        match kind {
+            KEYFRAME => {
+                let (l,value) = T::decode_with(&buffer[5..],&mut limiter)?;
+                self.keyframe = Some((number,value.clone()));
+                Ok((5 + l,Some(value)))
+            },
+            DELTA => match &self.keyframe {
+                Some((n,keyframe)) if *n == number => {
+                    let (l,value) = T::decode_delta_with(keyframe,&buffer[5..],&mut limiter)?;
+                    Ok((5 + l,Some(value)))
+                },
+                _ => {
+                    let l = T::skip_delta_with(&buffer[5..],&mut limiter)?;
+                    Ok((5 + l,None))
+                },
+            },
+            _ => Err(Error::InvalidTag(kind as u32)),
+        }
+    }
+}
+
+impl<T: Delta + Clone> Default for DeltaDecoder<T> {
+    fn default() -> Self {
+        DeltaDecoder::new()
+    }
+}
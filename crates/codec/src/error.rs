@@ -0,0 +1,47 @@
+// Echidna - Codec
+
+use std::fmt;
+
+/// Reasons for a decode to fail.
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub enum Error {
+
+    /// The buffer ends before the encoded value does.
+    Truncated,
+
+    /// Unknown enum variant or address family.
+    InvalidTag(u32),
+
+    /// A length prefix exceeds `DecodeLimits::max_len` or `DecodeLimits::max_string_bytes`.
+    TooLong(usize),
+
+    /// Decoding would allocate more than `DecodeLimits::max_alloc` bytes in total.
+    TooLarge(usize),
+
+    /// Values are nested deeper than `DecodeLimits::max_depth`.
+    TooDeep,
+
+    /// The CRC of a `Checked` value doesn't match its bytes.
+    ChecksumMismatch,
+
+    /// A `Compressed` value doesn't decompress to what its header says.
+    InvalidCompression,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self,f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Truncated => write!(f,"buffer truncated"),
+            Error::InvalidTag(tag) => write!(f,"invalid tag {}",tag),
+            Error::TooLong(len) => write!(f,"length {} exceeds limit",len),
+            Error::TooLarge(bytes) => write!(f,"allocating {} bytes exceeds limit",bytes),
+            Error::TooDeep => write!(f,"nesting exceeds limit"),
+            Error::ChecksumMismatch => write!(f,"checksum mismatch"),
+            Error::InvalidCompression => write!(f,"invalid compressed data"),
+        }
+    }
+}
+
+impl std::error::Error for Error { }
+
+pub type Result<T> = std::result::Result<T,Error>;
@@ -3,13 +3,11 @@
 use crate::*;
 
 impl Codec for f32 {
-    fn decode(buffer: &[u8]) -> Option<(usize,Self)> {
-        if let Some(a) = u32::decode(buffer) {
-            Some((4,f32::from_bits(a.1)))
-        }
-        else {
-            None
-        }
+    const FIXED_SIZE: Option<usize> = Some(4);
+
+    fn decode_with(buffer: &[u8],limiter: &mut Limiter) -> Result<(usize,Self)> {
+        let (_,a) = u32::decode_with(buffer,limiter)?;
+        Ok((4,f32::from_bits(a)))
     }
 
     fn encode(&self,buffer: &mut Vec<u8>) -> usize {
@@ -17,19 +15,26 @@ impl Codec for f32 {
         a.encode(buffer)
     }
 
+    fn encode_canonical(&self,buffer: &mut Vec<u8>) -> usize {
+        let a = if self.is_nan() { 0x7FC0_0000 } else { self.to_bits() };
+        a.encode(buffer)
+    }
+
     fn size(&self) -> usize {
         4
     }
 }
 
+impl FixedSize for f32 {
+    const SIZE: usize = 4;
+}
+
 impl Codec for f64 {
-    fn decode(buffer: &[u8]) -> Option<(usize,Self)> {
-        if let Some(a) = u64::decode(buffer) {
-            Some((8,f64::from_bits(a.1)))
-        }
-        else {
-            None
-        }
+    const FIXED_SIZE: Option<usize> = Some(8);
+
+    fn decode_with(buffer: &[u8],limiter: &mut Limiter) -> Result<(usize,Self)> {
+        let (_,a) = u64::decode_with(buffer,limiter)?;
+        Ok((8,f64::from_bits(a)))
     }
 
     fn encode(&self,buffer: &mut Vec<u8>) -> usize {
@@ -37,11 +42,20 @@ impl Codec for f64 {
         a.encode(buffer)
     }
 
+    fn encode_canonical(&self,buffer: &mut Vec<u8>) -> usize {
+        let a = if self.is_nan() { 0x7FF8_0000_0000_0000 } else { self.to_bits() };
+        a.encode(buffer)
+    }
+
     fn size(&self) -> usize {
         8
     }
 }
 
+impl FixedSize for f64 {
+    const SIZE: usize = 8;
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -52,7 +66,7 @@ mod tests {
         let source: f32 = 1.23456;
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
-        if let Some((_,target)) = f32::decode(&buffer) {
+        if let Ok((_,target)) = f32::decode(&buffer) {
             assert_eq!(source,target)
         }
         else {
@@ -65,11 +79,25 @@ mod tests {
         let source: f64 = 1.23456;
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
-        if let Some((_,target)) = f64::decode(&buffer) {
+        if let Ok((_,target)) = f64::decode(&buffer) {
             assert_eq!(source,target)
         }
         else {
             assert!(false)
         }
     }
+
+    #[test]
+    fn test_nan_canonical() {
+        let mut a = Vec::<u8>::new();
+        let mut b = Vec::<u8>::new();
+        f32::from_bits(0x7FC0_1234).encode_canonical(&mut a);
+        (-f32::NAN).encode_canonical(&mut b);
+        assert_eq!(a,b);
+        let mut a = Vec::<u8>::new();
+        let mut b = Vec::<u8>::new();
+        f64::from_bits(0x7FF8_0000_0000_1234).encode_canonical(&mut a);
+        (-f64::NAN).encode_canonical(&mut b);
+        assert_eq!(a,b);
+    }
 }
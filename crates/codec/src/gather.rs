@@ -0,0 +1,126 @@
+// Echidna - Codec
+
+use std::io::IoSlice;
+
+/// Part of a `Gather`, see `Gather::parts`.
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub enum GatherPart<'a> {
+    /// Range of the scratch buffer, see `Gather::scratch`.
+    Buffer(usize,usize),
+    /// Bytes referenced where they are.
+    Slice(&'a [u8]),
+}
+
+/// Encoded value as a list of slices, for `send_vectored` or `sendmsg`.
+///
+/// Filled by `Codec::encode_gather`. Length prefixes, tags and other small
+/// parts go into a scratch buffer, and byte sequences of at least
+/// `threshold` bytes are referenced where they are, so a large payload is
+/// never copied before it reaches the socket.
+pub struct Gather<'a> {
+    buffer: Vec<u8>,
+    parts: Vec<GatherPart<'a>>,
+    start: usize,
+    referenced: usize,
+    threshold: usize,
+}
+
+impl<'a> Gather<'a> {
+
+    /// New gather list that references byte sequences of 256 bytes or more.
+    pub fn new() -> Gather<'a> {
+        Gather::with_threshold(256)
+    }
+
+    /// New gather list that references byte sequences of `threshold` bytes
+    /// or more, and copies smaller ones.
+    pub fn with_threshold(threshold: usize) -> Gather<'a> {
+        Gather {
+            buffer: Vec::new(),
+            parts: Vec::new(),
+            start: 0,
+            referenced: 0,
+            threshold: threshold,
+        }
+    }
+
+    /// Scratch buffer to encode the small parts onto.
+    pub fn buffer(&mut self) -> &mut Vec<u8> {
+        &mut self.buffer
+    }
+
+    /// Append `bytes`, referencing them if they are large enough.
+    ///
+    /// Returns the number of bytes appended.
+    pub fn bytes(&mut self,bytes: &'a [u8]) -> usize {
+        if bytes.len() < self.threshold {
+            self.buffer.extend_from_slice(bytes);
+        }
+        else {
+            if self.buffer.len() > self.start {
+                self.parts.push(GatherPart::Buffer(self.start,self.buffer.len()));
+                self.start = self.buffer.len();
+            }
+            self.parts.push(GatherPart::Slice(bytes));
+            self.referenced += bytes.len();
+        }
+        bytes.len()
+    }
+
+    /// Total number of bytes appended so far.
+    pub fn len(&self) -> usize {
+        self.buffer.len() + self.referenced
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The scratch buffer, with the small parts in order.
+    pub fn scratch(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    /// The parts in order, so the scratch buffer ranges can be told apart
+    /// from the referenced bytes without comparing addresses.
+    pub fn parts(&self) -> Vec<GatherPart<'a>> {
+        let mut r = self.parts.clone();
+        if self.buffer.len() > self.start {
+            r.push(GatherPart::Buffer(self.start,self.buffer.len()));
+        }
+        r
+    }
+
+    /// The parts in order, starting at byte `pos` of the encoding.
+    pub fn parts_from(&self,pos: usize) -> Vec<&[u8]> {
+        let mut r = Vec::<&[u8]>::new();
+        let mut ofs = 0usize;
+        for part in self.parts() {
+            let slice = match part {
+                GatherPart::Buffer(start,end) => &self.buffer[start..end],
+                GatherPart::Slice(slice) => slice,
+            };
+            if !slice.is_empty() && (ofs + slice.len() > pos) {
+                r.push(&slice[pos.saturating_sub(ofs)..]);
+            }
+            ofs += slice.len();
+        }
+        r
+    }
+
+    /// Slices to hand to `send_vectored`, in order.
+    pub fn slices(&self) -> Vec<IoSlice<'_>> {
+        self.parts_from(0).into_iter().map(IoSlice::new).collect()
+    }
+
+    /// Everything copied into one buffer, the same bytes `encode` gives.
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.parts_from(0).concat()
+    }
+}
+
+impl<'a> Default for Gather<'a> {
+    fn default() -> Self {
+        Gather::new()
+    }
+}
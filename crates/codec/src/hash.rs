@@ -0,0 +1,118 @@
+// Echidna - Codec
+
+use crate::*;
+
+const K: [u32; 64] = [
+    0x428A2F98,0x71374491,0xB5C0FBCF,0xE9B5DBA5,0x3956C25B,0x59F111F1,0x923F82A4,0xAB1C5ED5,
+    0xD807AA98,0x12835B01,0x243185BE,0x550C7DC3,0x72BE5D74,0x80DEB1FE,0x9BDC06A7,0xC19BF174,
+    0xE49B69C1,0xEFBE4786,0x0FC19DC6,0x240CA1CC,0x2DE92C6F,0x4A7484AA,0x5CB0A9DC,0x76F988DA,
+    0x983E5152,0xA831C66D,0xB00327C8,0xBF597FC7,0xC6E00BF3,0xD5A79147,0x06CA6351,0x14292967,
+    0x27B70A85,0x2E1B2138,0x4D2C6DFC,0x53380D13,0x650A7354,0x766A0ABB,0x81C2C92E,0x92722C85,
+    0xA2BFE8A1,0xA81A664B,0xC24B8B70,0xC76C51A3,0xD192E819,0xD6990624,0xF40E3585,0x106AA070,
+    0x19A4C116,0x1E376C08,0x2748774C,0x34B0BCB5,0x391C0CB3,0x4ED8AA4A,0x5B9CCA4F,0x682E6FF3,
+    0x748F82EE,0x78A5636F,0x84C87814,0x8CC70208,0x90BEFFFA,0xA4506CEB,0xBEF9A3F7,0xC67178F2,
+];
+
+const H: [u32; 8] = [
+    0x6A09E667,0xBB67AE85,0x3C6EF372,0xA54FF53A,0x510E527F,0x9B05688C,0x1F83D9AB,0x5BE0CD19,
+];
+
+fn compress(state: &mut [u32; 8],block: &[u8]) {
+    let mut w = [0u32; 64];
+    for i in 0..16 {
+        w[i] = u32::from_be_bytes([block[i * 4],block[i * 4 + 1],block[i * 4 + 2],block[i * 4 + 3]]);
+    }
+    for i in 16..64 {
+        let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+        let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+        w[i] = w[i - 16].wrapping_add(s0).wrapping_add(w[i - 7]).wrapping_add(s1);
+    }
+    let mut v = *state;
+    for i in 0..64 {
+        let s1 = v[4].rotate_right(6) ^ v[4].rotate_right(11) ^ v[4].rotate_right(25);
+        let ch = (v[4] & v[5]) ^ (!v[4] & v[6]);
+        let t1 = v[7].wrapping_add(s1).wrapping_add(ch).wrapping_add(K[i]).wrapping_add(w[i]);
+        let s0 = v[0].rotate_right(2) ^ v[0].rotate_right(13) ^ v[0].rotate_right(22);
+        let maj = (v[0] & v[1]) ^ (v[0] & v[2]) ^ (v[1] & v[2]);
+        let t2 = s0.wrapping_add(maj);
+        v[7] = v[6];
+        v[6] = v[5];
+        v[5] = v[4];
+        v[4] = v[3].wrapping_add(t1);
+        v[3] = v[2];
+        v[2] = v[1];
+        v[1] = v[0];
+        v[0] = t1.wrapping_add(t2);
+    }
+    for i in 0..8 {
+        state[i] = state[i].wrapping_add(v[i]);
+    }
+}
+
+/// SHA-256 digest of `data`.
+pub fn sha256(data: &[u8]) -> [u8; 32] {
+    let mut state = H;
+    let mut blocks = data.chunks_exact(64);
+    for block in &mut blocks {
+        compress(&mut state,block);
+    }
+    let rest = blocks.remainder();
+    let mut tail = [0u8; 128];
+    tail[..rest.len()].copy_from_slice(rest);
+    tail[rest.len()] = 0x80;
+    let n = if rest.len() < 56 { 64 } else { 128 };
+    tail[n - 8..n].copy_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+    for block in tail[..n].chunks_exact(64) {
+        compress(&mut state,block);
+    }
+    let mut r = [0u8; 32];
+    for i in 0..8 {
+        r[i * 4..i * 4 + 4].copy_from_slice(&state[i].to_be_bytes());
+    }
+    r
+}
+
+/// Content hash of `value`, the SHA-256 digest of its canonical encoding.
+/// 
+/// Equal values always hash the same, regardless of map ordering or NaN
+/// payloads, so this can be used as the key for content-addressed storage.
+pub fn content_hash<T: Codec>(value: &T) -> [u8; 32] {
+    let mut buffer = Vec::<u8>::with_capacity(value.size());
+    value.encode_canonical(&mut buffer);
+    sha256(&buffer)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use {
+        crate::*,
+        std::collections::HashMap,
+    };
+
+    fn hex(digest: &[u8; 32]) -> String {
+        digest.iter().map(|b| format!("{:02x}",b)).collect()
+    }
+
+    #[test]
+    fn test_sha256() {
+        assert_eq!(hex(&sha256(b"")),"e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+        assert_eq!(hex(&sha256(b"abc")),"ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        assert_eq!(hex(&sha256(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq")),"248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1");
+    }
+
+    #[test]
+    fn test_content_hash() {
+        let mut a = HashMap::<String,f32>::new();
+        let mut b = HashMap::<String,f32>::new();
+        for i in 0..20 {
+            a.insert(i.to_string(),i as f32);
+            b.insert((19 - i).to_string(),(19 - i) as f32);
+        }
+        a.insert("nan".to_string(),f32::from_bits(0x7FC0_0001));
+        b.insert("nan".to_string(),f32::from_bits(0xFFC0_0000));
+        assert_eq!(content_hash(&a),content_hash(&b));
+        b.insert("0".to_string(),1.0);
+        assert_ne!(content_hash(&a),content_hash(&b));
+    }
+}
@@ -1,40 +1,89 @@
 // Echidna - Codec
 
 use {
-    crate::*,
+    crate::{
+        *,
+        sorted::encode_sorted,
+    },
     std::{
         collections::HashMap,
         hash::Hash,
+        mem,
     },
 };
 
 impl<K: Codec + Eq + Hash,V: Codec> Codec for HashMap<K,V> {
-    fn decode(buffer: &[u8]) -> Option<(usize,Self)> {
-        if let Some((_,len)) = u32::decode(buffer) {
-            let mut r = HashMap::<K,V>::new();
-            let mut ofs = 4usize;
-            for _ in 0..len {
-                let key = if let Some((l,key)) = K::decode(&buffer[ofs..]) {
-                    ofs += l;
-                    key
-                }
-                else {
-                    return None;
-                };
-                let value = if let Some((l,value)) = V::decode(&buffer[ofs..]) {
+    fn decode_with(buffer: &[u8],limiter: &mut Limiter) -> Result<(usize,Self)> {
+        let (_,len) = u32::decode_with(buffer,limiter)?;
+        let len = len as usize;
+        limiter.collection(len,mem::size_of::<(K,V)>())?;
+        limiter.enter()?;
+        let mut r = HashMap::<K,V>::with_capacity(capacity(len,buffer.len() - 4,K::FIXED_SIZE.unwrap_or(0) + V::FIXED_SIZE.unwrap_or(0)));
+        let mut ofs = 4usize;
+        for _ in 0..len {
+            let (l,key) = K::decode_with(&buffer[ofs..],limiter)?;
+            ofs += l;
+            let (l,value) = V::decode_with(&buffer[ofs..],limiter)?;
+            ofs += l;
+            r.insert(key,value);
+        }
+        limiter.leave();
+        Ok((ofs,r))
+    }
+
+    fn decode_into_with(&mut self,buffer: &[u8],limiter: &mut Limiter) -> Result<usize> {
+        let (_,len) = u32::decode_with(buffer,limiter)?;
+        let len = len as usize;
+        limiter.collection(len,mem::size_of::<(K,V)>())?;
+        limiter.enter()?;
+        // values of keys that are still there are decoded in place, the others are dropped
+        let mut old = mem::take(self);
+        self.reserve(capacity(len,buffer.len() - 4,K::FIXED_SIZE.unwrap_or(0) + V::FIXED_SIZE.unwrap_or(0)));
+        let mut ofs = 4usize;
+        for _ in 0..len {
+            let (l,key) = K::decode_with(&buffer[ofs..],limiter)?;
+            ofs += l;
+            let value = match old.remove(&key) {
+                Some(mut value) => {
+                    ofs += value.decode_into_with(&buffer[ofs..],limiter)?;
+                    value
+                },
+                None => {
+                    let (l,value) = V::decode_with(&buffer[ofs..],limiter)?;
                     ofs += l;
                     value
-                }
-                else {
-                    return None;
-                };
-                r.insert(key,value);
-            }
-            Some((ofs,r))
+                },
+            };
+            self.insert(key,value);
         }
-        else {
-            None
+        limiter.leave();
+        Ok(ofs)
+    }
+
+    fn skip_with(buffer: &[u8],limiter: &mut Limiter) -> Result<usize> {
+        let (_,len) = u32::decode_with(buffer,limiter)?;
+        let len = len as usize;
+        limiter.collection(len,mem::size_of::<(K,V)>())?;
+        limiter.enter()?;
+        let mut ofs = 4usize;
+        for _ in 0..len {
+            ofs += K::skip_with(&buffer[ofs..],limiter)?;
+            ofs += V::skip_with(&buffer[ofs..],limiter)?;
+        }
+        limiter.leave();
+        Ok(ofs)
+    }
+
+    fn annotate_with(annotator: &mut Annotator,path: &str) -> Result<()> {
+        let len = annotator.length(path)?;
+        annotator.limiter().collection(len,mem::size_of::<(K,V)>())?;
+        annotator.limiter().enter()?;
+        for i in 0..len {
+            K::annotate_with(annotator,&format!("{}[{}].key",path,i))?;
+            V::annotate_with(annotator,&format!("{}[{}].value",path,i))?;
         }
+        annotator.limiter().leave();
+        Ok(())
     }
 
     fn encode(&self,buffer: &mut Vec<u8>) -> usize {
@@ -47,6 +96,18 @@ impl<K: Codec + Eq + Hash,V: Codec> Codec for HashMap<K,V> {
         len
     }
 
+    fn encode_canonical(&self,buffer: &mut Vec<u8>) -> usize {
+        let mut entries = Vec::<(Vec<u8>,Vec<u8>)>::with_capacity(self.len());
+        for (key,value) in self {
+            let mut k = Vec::<u8>::new();
+            key.encode_canonical(&mut k);
+            let mut v = Vec::<u8>::new();
+            value.encode_canonical(&mut v);
+            entries.push((k,v));
+        }
+        encode_sorted(entries,buffer)
+    }
+
     fn size(&self) -> usize {
         let mut len = 4;
         for (key,value) in self {
@@ -71,11 +132,33 @@ mod tests {
         source.insert(2,-1);
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
-        if let Some((_,target)) = HashMap::<u16,i32>::decode(&buffer) {
+        if let Ok((_,target)) = HashMap::<u16,i32>::decode(&buffer) {
             assert_eq!(source,target)
         }
         else {
             assert!(false)
         }
     }
+
+    #[test]
+    fn test_hashmap_canonical() {
+        let mut a: HashMap<u16,String> = HashMap::new();
+        let mut b: HashMap<u16,String> = HashMap::new();
+        for i in 0..100 {
+            a.insert(i,i.to_string());
+            b.insert(99 - i,(99 - i).to_string());
+        }
+        let mut buffer_a = Vec::<u8>::new();
+        let mut buffer_b = Vec::<u8>::new();
+        let len = a.encode_canonical(&mut buffer_a);
+        b.encode_canonical(&mut buffer_b);
+        assert_eq!(len,buffer_a.len());
+        assert_eq!(buffer_a,buffer_b);
+        if let Ok((_,target)) = HashMap::<u16,String>::decode(&buffer_a) {
+            assert_eq!(a,target)
+        }
+        else {
+            assert!(false)
+        }
+    }
 }
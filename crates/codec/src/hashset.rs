@@ -0,0 +1,128 @@
+// Echidna - Codec
+
+use {
+    crate::{
+        *,
+        sorted::encode_sorted,
+    },
+    std::{
+        collections::HashSet,
+        hash::Hash,
+        mem,
+    },
+};
+
+impl<T: Codec + Eq + Hash> Codec for HashSet<T> {
+    fn decode_with(buffer: &[u8],limiter: &mut Limiter) -> Result<(usize,Self)> {
+        let (_,len) = u32::decode_with(buffer,limiter)?;
+        let len = len as usize;
+        limiter.collection(len,mem::size_of::<T>())?;
+        limiter.enter()?;
+        let mut r = HashSet::<T>::with_capacity(capacity(len,buffer.len() - 4,T::FIXED_SIZE.unwrap_or(1)));
+        let mut ofs = 4usize;
+        for _ in 0..len {
+            let (l,item) = T::decode_with(&buffer[ofs..],limiter)?;
+            ofs += l;
+            r.insert(item);
+        }
+        limiter.leave();
+        Ok((ofs,r))
+    }
+
+    fn decode_into_with(&mut self,buffer: &[u8],limiter: &mut Limiter) -> Result<usize> {
+        let (_,len) = u32::decode_with(buffer,limiter)?;
+        let len = len as usize;
+        limiter.collection(len,mem::size_of::<T>())?;
+        limiter.enter()?;
+        self.clear();
+        self.reserve(capacity(len,buffer.len() - 4,T::FIXED_SIZE.unwrap_or(1)));
+        let mut ofs = 4usize;
+        for _ in 0..len {
+            let (l,item) = T::decode_with(&buffer[ofs..],limiter)?;
+            ofs += l;
+            self.insert(item);
+        }
+        limiter.leave();
+        Ok(ofs)
+    }
+
+    fn skip_with(buffer: &[u8],limiter: &mut Limiter) -> Result<usize> {
+        let (_,len) = u32::decode_with(buffer,limiter)?;
+        let len = len as usize;
+        limiter.collection(len,mem::size_of::<T>())?;
+        limiter.enter()?;
+        let mut ofs = 4usize;
+        for _ in 0..len {
+            ofs += T::skip_with(&buffer[ofs..],limiter)?;
+        }
+        limiter.leave();
+        Ok(ofs)
+    }
+
+    fn annotate_with(annotator: &mut Annotator,path: &str) -> Result<()> {
+        let len = annotator.length(path)?;
+        annotator.limiter().collection(len,mem::size_of::<T>())?;
+        annotator.limiter().enter()?;
+        annotator.items(path,len,|annotator,path| T::annotate_with(annotator,path))?;
+        annotator.limiter().leave();
+        Ok(())
+    }
+
+    fn encode(&self,buffer: &mut Vec<u8>) -> usize {
+        (self.len() as u32).encode(buffer);
+        let mut len = 4;
+        for item in self {
+            len += item.encode(buffer);
+        }
+        len
+    }
+
+    fn encode_canonical(&self,buffer: &mut Vec<u8>) -> usize {
+        let mut entries = Vec::<(Vec<u8>,Vec<u8>)>::with_capacity(self.len());
+        for item in self {
+            let mut k = Vec::<u8>::new();
+            item.encode_canonical(&mut k);
+            entries.push((k,Vec::new()));
+        }
+        encode_sorted(entries,buffer)
+    }
+
+    fn size(&self) -> usize {
+        let mut len = 4;
+        for item in self {
+            len += item.size();
+        }
+        len
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::Codec;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_hashset() {
+        let source: HashSet<u32> = [3,1,4,1,5,9,2,6].iter().cloned().collect();
+        let mut buffer = Vec::<u8>::new();
+        source.encode(&mut buffer);
+        if let Ok((_,target)) = HashSet::<u32>::decode(&buffer) {
+            assert_eq!(source,target)
+        }
+        else {
+            assert!(false)
+        }
+    }
+
+    #[test]
+    fn test_hashset_canonical() {
+        let a: HashSet<String> = (0..50).map(|i| i.to_string()).collect();
+        let b: HashSet<String> = (0..50).rev().map(|i| i.to_string()).collect();
+        let mut buffer_a = Vec::<u8>::new();
+        let mut buffer_b = Vec::<u8>::new();
+        a.encode_canonical(&mut buffer_a);
+        b.encode_canonical(&mut buffer_b);
+        assert_eq!(buffer_a,buffer_b);
+    }
+}
@@ -0,0 +1,97 @@
+// Echidna - Codec
+
+use {
+    crate::*,
+    std::marker::PhantomData,
+};
+
+/// Result of feeding bytes to an `IncrementalDecoder`.
+#[derive(Clone,Debug,PartialEq)]
+pub enum Progress<T> {
+
+    /// The value isn't complete yet, and needs at least this many more
+    /// bytes: all of them for a fixed size type, and 1 otherwise.
+    NeedMore(usize),
+
+    /// The value is complete, with the number of bytes of the last fed
+    /// slice that belong to it. The rest of that slice starts the next value.
+    Done(T,usize),
+}
+
+/// Decoder for values that arrive in pieces, like from a TCP stream or a
+/// serial link, without an outer length prefix.
+///
+/// Every `feed` runs `Codec::skip_with` over the bytes so far, which fails
+/// with `Error::Truncated` until the value is complete, so it takes exactly
+/// what `T::decode` takes. `T` is decoded once, when all of its bytes are
+/// there. Length prefixes are checked against the limits and tags are
+/// checked as soon as they arrive, so a corrupted one fails instead of
+/// waiting forever. Since every `feed` skips over the value from its
+/// start, a large value is best fed in large pieces.
+pub struct IncrementalDecoder<T> {
+    limits: DecodeLimits,
+    buffer: Vec<u8>,
+    phantom: PhantomData<T>,
+}
+
+impl<T: Codec> IncrementalDecoder<T> {
+
+    pub fn new() -> IncrementalDecoder<T> {
+        IncrementalDecoder::with_limits(&DecodeLimits::default())
+    }
+
+    pub fn with_limits(limits: &DecodeLimits) -> IncrementalDecoder<T> {
+        IncrementalDecoder {
+            limits: *limits,
+            buffer: Vec::new(),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Drop everything fed so far, and start at a new value.
+    pub fn reset(&mut self) {
+        self.buffer.clear();
+    }
+
+    /// Number of bytes fed for the current value so far.
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Feed the next bytes of the stream.
+    ///
+    /// Bytes after the end of the value are not kept, feed them again for
+    /// the next value. On error, the decoder is reset.
+    pub fn feed(&mut self,data: &[u8]) -> Result<Progress<T>> {
+        let start = self.buffer.len();
+        if start > 0 {
+            self.buffer.extend_from_slice(data);
+        }
+
+        // while nothing is buffered, a value that is complete in `data` is decoded without copying it
+        let bytes = if start == 0 { data } else { &self.buffer[..] };
+        let result = T::skip_with(bytes,&mut Limiter::new(&self.limits)).and_then(|end| Ok((end,T::decode_limited(&bytes[..end],&self.limits)?.1)));
+        match result {
+            Ok((end,value)) => {
+                self.reset();
+                Ok(Progress::Done(value,end - start))
+            },
+            Err(Error::Truncated) => {
+                if start == 0 {
+                    self.buffer.extend_from_slice(data);
+                }
+                Ok(Progress::NeedMore(T::FIXED_SIZE.map(|size| size - self.buffer.len()).unwrap_or(1)))
+            },
+            Err(error) => {
+                self.reset();
+                Err(error)
+            },
+        }
+    }
+}
+
+impl<T: Codec> Default for IncrementalDecoder<T> {
+    fn default() -> Self {
+        IncrementalDecoder::new()
+    }
+}
@@ -0,0 +1,836 @@
+// Echidna - Codec
+
+//! Human-readable JSON text format, without `serde`.
+//!
+//! Derive it with `#[codec(json)]` next to `#[derive(Codec)]`. Structs
+//! become objects, tuple structs arrays, and enum variants `"Variant"` or
+//! `{"Variant": ...}`. `Vec<u8>` becomes a base64 string, `HashMap` an
+//! object if its keys are strings or numbers, and addresses strings like
+//! `"10.0.0.1:7331"`.
+
+use {
+    crate::DecodeLimits,
+    std::{
+        borrow::Cow,
+        collections::{
+            HashMap,
+            HashSet,
+        },
+        fmt,
+        hash::Hash,
+        net::{
+            Ipv4Addr,
+            Ipv6Addr,
+            IpAddr,
+            SocketAddrV4,
+            SocketAddrV6,
+            SocketAddr,
+        },
+        str::FromStr,
+    },
+};
+
+/// JSON value.
+///
+/// Numbers are kept as their text, so 64-bit integers survive exactly.
+/// Object members keep their order.
+#[derive(Clone,Debug,PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String,Json)>),
+}
+
+/// Errors from parsing JSON text, or converting `Json` into a type.
+#[derive(Clone,Debug,PartialEq)]
+pub enum JsonError {
+
+    /// The text is not valid JSON.
+    Syntax { line: usize, column: usize, message: String, },
+
+    /// The JSON value doesn't have the expected shape.
+    Expected(String),
+
+    /// A struct field is missing from the object.
+    MissingField(String),
+
+    /// An enum variant name is not known.
+    UnknownVariant(String),
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self,f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonError::Syntax { line,column,message } => write!(f,"{}:{}: {}",line,column,message),
+            JsonError::Expected(what) => write!(f,"expected {}",what),
+            JsonError::MissingField(name) => write!(f,"missing field `{}`",name),
+            JsonError::UnknownVariant(name) => write!(f,"unknown variant `{}`",name),
+        }
+    }
+}
+
+impl std::error::Error for JsonError { }
+
+fn write_string(f: &mut fmt::Formatter<'_>,s: &str) -> fmt::Result {
+    write!(f,"\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f,"\\\"")?,
+            '\\' => write!(f,"\\\\")?,
+            '\n' => write!(f,"\\n")?,
+            '\r' => write!(f,"\\r")?,
+            '\t' => write!(f,"\\t")?,
+            c if (c as u32) < 0x20 => write!(f,"\\u{:04x}",c as u32)?,
+            c => write!(f,"{}",c)?,
+        }
+    }
+    write!(f,"\"")
+}
+
+impl Json {
+
+    /// Parse JSON text.
+    pub fn parse(text: &str) -> Result<Json,JsonError> {
+        Json::parse_limited(text,&DecodeLimits::default())
+    }
+
+    /// Parse JSON text, with arrays and objects nested no deeper than
+    /// `limits.max_depth`.
+    pub fn parse_limited(text: &str,limits: &DecodeLimits) -> Result<Json,JsonError> {
+        let mut parser = Parser { chars: text.chars().collect(), pos: 0, depth: 0, max_depth: limits.max_depth, };
+        let value = parser.parse_value()?;
+        parser.skip_whitespace();
+        if parser.pos < parser.chars.len() {
+            return Err(parser.error("trailing characters after value"));
+        }
+        Ok(value)
+    }
+
+    /// Indented text, for config files and printing.
+    pub fn pretty(&self) -> String {
+        let mut r = String::new();
+        self.write_pretty(&mut r,0);
+        r
+    }
+
+    fn write_pretty(&self,r: &mut String,indent: usize) {
+        match self {
+            Json::Array(items) if !items.is_empty() => {
+                *r += "[\n";
+                for i in 0..items.len() {
+                    *r += &"    ".repeat(indent + 1);
+                    items[i].write_pretty(r,indent + 1);
+                    *r += if i + 1 < items.len() { ",\n" } else { "\n" };
+                }
+                *r += &"    ".repeat(indent);
+                *r += "]";
+            },
+            Json::Object(members) if !members.is_empty() => {
+                *r += "{\n";
+                for i in 0..members.len() {
+                    *r += &"    ".repeat(indent + 1);
+                    *r += &Json::String(members[i].0.clone()).to_string();
+                    *r += ": ";
+                    members[i].1.write_pretty(r,indent + 1);
+                    *r += if i + 1 < members.len() { ",\n" } else { "\n" };
+                }
+                *r += &"    ".repeat(indent);
+                *r += "}";
+            },
+            _ => *r += &self.to_string(),
+        }
+    }
+
+    /// Value of member `name`, if this is an object that has one.
+    pub fn get(&self,name: &str) -> Option<&Json> {
+        if let Json::Object(members) = self {
+            members.iter().find(|(n,_)| n == name).map(|(_,value)| value)
+        }
+        else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for Json {
+    fn fmt(&self,f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Json::Null => write!(f,"null"),
+            Json::Bool(b) => write!(f,"{}",b),
+            Json::Number(n) => write!(f,"{}",n),
+            Json::String(s) => write_string(f,s),
+            Json::Array(items) => {
+                write!(f,"[")?;
+                for (i,item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f,",")?;
+                    }
+                    write!(f,"{}",item)?;
+                }
+                write!(f,"]")
+            },
+            Json::Object(members) => {
+                write!(f,"{{")?;
+                for (i,(name,value)) in members.iter().enumerate() {
+                    if i > 0 {
+                        write!(f,",")?;
+                    }
+                    write_string(f,name)?;
+                    write!(f,":{}",value)?;
+                }
+                write!(f,"}}")
+            },
+        }
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    depth: usize,
+    max_depth: usize,
+}
+
+impl Parser {
+
+    fn error(&self,message: &str) -> JsonError {
+        let mut line = 1;
+        let mut column = 1;
+        for c in &self.chars[..self.pos.min(self.chars.len())] {
+            if *c == '\n' {
+                line += 1;
+                column = 1;
+            }
+            else {
+                column += 1;
+            }
+        }
+        JsonError::Syntax { line: line, column: column, message: message.to_string(), }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).cloned()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() {
+                self.pos += 1;
+            }
+            else {
+                break;
+            }
+        }
+    }
+
+    fn expect(&mut self,c: char) -> Result<(),JsonError> {
+        self.skip_whitespace();
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            Ok(())
+        }
+        else {
+            Err(self.error(&format!("`{}` expected",c)))
+        }
+    }
+
+    fn parse_keyword(&mut self,keyword: &str,value: Json) -> Result<Json,JsonError> {
+        for k in keyword.chars() {
+            if self.peek() != Some(k) {
+                return Err(self.error(&format!("`{}` expected",keyword)));
+            }
+            self.pos += 1;
+        }
+        Ok(value)
+    }
+
+    fn parse_string(&mut self) -> Result<String,JsonError> {
+        self.expect('"')?;
+        let mut r = String::new();
+        loop {
+            match self.peek() {
+                None => return Err(self.error("unterminated string")),
+                Some('"') => {
+                    self.pos += 1;
+                    return Ok(r);
+                },
+                Some('\\') => {
+                    self.pos += 1;
+                    let c = match self.peek() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => {
+                            let code = self.parse_hex4()?;
+                            // surrogate pairs encode one character in two escapes
+                            let code = if (0xD800..0xDC00).contains(&code) {
+                                self.pos += 1;
+                                if (self.peek() != Some('\\')) || (self.chars.get(self.pos + 1) != Some(&'u')) {
+                                    return Err(self.error("low surrogate expected"));
+                                }
+                                self.pos += 1;
+                                let low = self.parse_hex4()?;
+                                if !(0xDC00..0xE000).contains(&low) {
+                                    return Err(self.error("low surrogate expected"));
+                                }
+                                0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00)
+                            }
+                            else {
+                                code
+                            };
+                            match std::char::from_u32(code) {
+                                Some(c) => c,
+                                None => return Err(self.error("invalid unicode escape")),
+                            }
+                        },
+                        _ => return Err(self.error("invalid escape")),
+                    };
+                    self.pos += 1;
+                    r.push(c);
+                },
+                Some(c) => {
+                    self.pos += 1;
+                    r.push(c);
+                },
+            }
+        }
+    }
+
+    // 4 hex digits after `u`, leaves `pos` at the last digit
+    fn parse_hex4(&mut self) -> Result<u32,JsonError> {
+        let mut code = 0u32;
+        for _ in 0..4 {
+            self.pos += 1;
+            match self.peek().and_then(|c| c.to_digit(16)) {
+                Some(d) => code = (code << 4) | d,
+                None => return Err(self.error("hex digit expected")),
+            }
+        }
+        Ok(code)
+    }
+
+    fn parse_number(&mut self) -> Result<Json,JsonError> {
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if c.is_ascii_digit() || (c == '-') || (c == '+') || (c == '.') || (c == 'e') || (c == 'E') {
+                self.pos += 1;
+            }
+            else {
+                break;
+            }
+        }
+        let text: String = self.chars[start..self.pos].iter().collect();
+        if text.parse::<f64>().is_err() {
+            self.pos = start;
+            return Err(self.error("invalid number"));
+        }
+        Ok(Json::Number(text))
+    }
+
+    // `parse` for an array or object, one level deeper
+    fn nested(&mut self,parse: fn(&mut Parser) -> Result<Json,JsonError>) -> Result<Json,JsonError> {
+        if self.depth >= self.max_depth {
+            return Err(self.error("nested too deeply"));
+        }
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn parse_array(&mut self) -> Result<Json,JsonError> {
+        self.pos += 1;
+        let mut items = Vec::<Json>::new();
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.pos += 1;
+            return Ok(Json::Array(items));
+        }
+        loop {
+            items.push(self.parse_value()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(']') => {
+                    self.pos += 1;
+                    return Ok(Json::Array(items));
+                },
+                _ => return Err(self.error("`,` or `]` expected")),
+            }
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<Json,JsonError> {
+        self.pos += 1;
+        let mut members = Vec::<(String,Json)>::new();
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.pos += 1;
+            return Ok(Json::Object(members));
+        }
+        loop {
+            self.skip_whitespace();
+            let name = self.parse_string()?;
+            self.expect(':')?;
+            let value = self.parse_value()?;
+            members.push((name,value));
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some('}') => {
+                    self.pos += 1;
+                    return Ok(Json::Object(members));
+                },
+                _ => return Err(self.error("`,` or `}` expected")),
+            }
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Json,JsonError> {
+        self.skip_whitespace();
+        match self.peek() {
+            None => Err(self.error("value expected")),
+            Some('n') => self.parse_keyword("null",Json::Null),
+            Some('t') => self.parse_keyword("true",Json::Bool(true)),
+            Some('f') => self.parse_keyword("false",Json::Bool(false)),
+            Some('"') => Ok(Json::String(self.parse_string()?)),
+            Some('[') => self.nested(Parser::parse_array),
+            Some('{') => self.nested(Parser::parse_object),
+            Some(c) if c.is_ascii_digit() || (c == '-') => self.parse_number(),
+            Some(c) => Err(self.error(&format!("unexpected `{}`",c))),
+        }
+    }
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Standard base64 with padding.
+pub fn base64_encode(data: &[u8]) -> String {
+    let mut r = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [chunk[0],*chunk.get(1).unwrap_or(&0),*chunk.get(2).unwrap_or(&0)];
+        let n = ((b[0] as u32) << 16) | ((b[1] as u32) << 8) | (b[2] as u32);
+        r.push(BASE64[(n >> 18) as usize & 63] as char);
+        r.push(BASE64[(n >> 12) as usize & 63] as char);
+        r.push(if chunk.len() > 1 { BASE64[(n >> 6) as usize & 63] as char } else { '=' });
+        r.push(if chunk.len() > 2 { BASE64[n as usize & 63] as char } else { '=' });
+    }
+    r
+}
+
+/// Decode standard base64, with or without padding.
+pub fn base64_decode(text: &str) -> Option<Vec<u8>> {
+    let text = text.trim_end_matches('=');
+    let mut r = Vec::<u8>::with_capacity(text.len() * 3 / 4);
+    let mut n = 0u32;
+    let mut bits = 0;
+    for c in text.bytes() {
+        let d = BASE64.iter().position(|b| *b == c)? as u32;
+        n = (n << 6) | d;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            r.push((n >> bits) as u8);
+        }
+    }
+    Some(r)
+}
+
+/// Types that convert to JSON.
+pub trait ToJson {
+    fn to_json(&self) -> Json;
+
+    /// JSON for a sequence of these, an array unless overridden.
+    ///
+    /// `u8` overrides this, so byte vectors become base64 strings.
+    fn to_json_seq(items: &[Self]) -> Json where Self: Sized {
+        Json::Array(items.iter().map(|item| item.to_json()).collect())
+    }
+}
+
+/// Types that convert from JSON.
+pub trait FromJson: Sized {
+    fn from_json(json: &Json) -> Result<Self,JsonError>;
+
+    /// Sequence of these from JSON, an array unless overridden.
+    fn from_json_seq(json: &Json) -> Result<Vec<Self>,JsonError> {
+        if let Json::Array(items) = json {
+            items.iter().map(|item| Self::from_json(item)).collect()
+        }
+        else {
+            Err(JsonError::Expected("array".to_string()))
+        }
+    }
+}
+
+/// Compact JSON text of `value`.
+pub fn to_json_string<T: ToJson>(value: &T) -> String {
+    value.to_json().to_string()
+}
+
+/// Parse `T` from JSON text.
+pub fn from_json_str<T: FromJson>(text: &str) -> Result<T,JsonError> {
+    T::from_json(&Json::parse(text)?)
+}
+
+/// Member `name` of a struct object, for derived implementations.
+pub fn field<'a>(members: &'a [(String,Json)],name: &str) -> Result<&'a Json,JsonError> {
+    members.iter().find(|(n,_)| n == name).map(|(_,value)| value).ok_or_else(|| JsonError::MissingField(name.to_string()))
+}
+
+impl ToJson for bool {
+    fn to_json(&self) -> Json {
+        Json::Bool(*self)
+    }
+}
+
+impl FromJson for bool {
+    fn from_json(json: &Json) -> Result<Self,JsonError> {
+        if let Json::Bool(b) = json {
+            Ok(*b)
+        }
+        else {
+            Err(JsonError::Expected("boolean".to_string()))
+        }
+    }
+}
+
+// numbers are also accepted as strings, which is what they are as object keys
+fn number_text(json: &Json) -> Option<&str> {
+    match json {
+        Json::Number(n) => Some(n),
+        Json::String(s) => Some(s),
+        _ => None,
+    }
+}
+
+macro_rules! json_int {
+    ($($t:ty),*) => {
+        $(
+            impl ToJson for $t {
+                fn to_json(&self) -> Json {
+                    Json::Number(self.to_string())
+                }
+            }
+
+            impl FromJson for $t {
+                fn from_json(json: &Json) -> Result<Self,JsonError> {
+                    number_text(json).and_then(|n| n.parse::<$t>().ok()).ok_or_else(|| JsonError::Expected(stringify!($t).to_string()))
+                }
+            }
+        )*
+    };
+}
+
+json_int!(i8,u16,i16,u32,i32,u64,i64);
+
+impl ToJson for u8 {
+    fn to_json(&self) -> Json {
+        Json::Number(self.to_string())
+    }
+
+    fn to_json_seq(items: &[Self]) -> Json {
+        Json::String(base64_encode(items))
+    }
+}
+
+impl FromJson for u8 {
+    fn from_json(json: &Json) -> Result<Self,JsonError> {
+        number_text(json).and_then(|n| n.parse::<u8>().ok()).ok_or_else(|| JsonError::Expected("u8".to_string()))
+    }
+
+    fn from_json_seq(json: &Json) -> Result<Vec<Self>,JsonError> {
+        match json {
+            Json::String(s) => base64_decode(s).ok_or_else(|| JsonError::Expected("base64".to_string())),
+            Json::Array(items) => items.iter().map(u8::from_json).collect(),
+            _ => Err(JsonError::Expected("base64 string".to_string())),
+        }
+    }
+}
+
+// JSON has no NaN or infinities, so those become strings
+macro_rules! json_float {
+    ($($t:ty),*) => {
+        $(
+            impl ToJson for $t {
+                fn to_json(&self) -> Json {
+                    if self.is_finite() {
+                        Json::Number(format!("{:?}",self))
+                    }
+                    else {
+                        Json::String(self.to_string())
+                    }
+                }
+            }
+
+            impl FromJson for $t {
+                fn from_json(json: &Json) -> Result<Self,JsonError> {
+                    number_text(json).and_then(|n| n.parse::<$t>().ok()).ok_or_else(|| JsonError::Expected(stringify!($t).to_string()))
+                }
+            }
+        )*
+    };
+}
+
+json_float!(f32,f64);
+
+impl ToJson for String {
+    fn to_json(&self) -> Json {
+        Json::String(self.clone())
+    }
+}
+
+impl FromJson for String {
+    fn from_json(json: &Json) -> Result<Self,JsonError> {
+        if let Json::String(s) = json {
+            Ok(s.clone())
+        }
+        else {
+            Err(JsonError::Expected("string".to_string()))
+        }
+    }
+}
+
+impl<'a> ToJson for Cow<'a,str> {
+    fn to_json(&self) -> Json {
+        Json::String(self.to_string())
+    }
+}
+
+impl<'a> FromJson for Cow<'a,str> {
+    fn from_json(json: &Json) -> Result<Self,JsonError> {
+        Ok(Cow::Owned(String::from_json(json)?))
+    }
+}
+
+// `None` is `null`, so `Option<Option<T>>` can't tell `Some(None)` from `None`
+impl<T: ToJson> ToJson for Option<T> {
+    fn to_json(&self) -> Json {
+        match self {
+            Some(value) => value.to_json(),
+            None => Json::Null,
+        }
+    }
+}
+
+impl<T: FromJson> FromJson for Option<T> {
+    fn from_json(json: &Json) -> Result<Self,JsonError> {
+        match json {
+            Json::Null => Ok(None),
+            _ => Ok(Some(T::from_json(json)?)),
+        }
+    }
+}
+
+impl<T: ToJson> ToJson for Vec<T> {
+    fn to_json(&self) -> Json {
+        T::to_json_seq(self)
+    }
+}
+
+impl<T: FromJson> FromJson for Vec<T> {
+    fn from_json(json: &Json) -> Result<Self,JsonError> {
+        T::from_json_seq(json)
+    }
+}
+
+impl<'a,T: ToJson + Clone> ToJson for Cow<'a,[T]> {
+    fn to_json(&self) -> Json {
+        T::to_json_seq(self)
+    }
+}
+
+impl<'a,T: FromJson + Clone> FromJson for Cow<'a,[T]> {
+    fn from_json(json: &Json) -> Result<Self,JsonError> {
+        Ok(Cow::Owned(T::from_json_seq(json)?))
+    }
+}
+
+impl<T: ToJson> ToJson for HashSet<T> {
+    fn to_json(&self) -> Json {
+        Json::Array(self.iter().map(|item| item.to_json()).collect())
+    }
+}
+
+impl<T: FromJson + Eq + Hash> FromJson for HashSet<T> {
+    fn from_json(json: &Json) -> Result<Self,JsonError> {
+        if let Json::Array(items) = json {
+            items.iter().map(|item| T::from_json(item)).collect()
+        }
+        else {
+            Err(JsonError::Expected("array".to_string()))
+        }
+    }
+}
+
+// an object if all keys are strings or numbers, otherwise an array of `[key,value]` pairs
+impl<K: ToJson,V: ToJson> ToJson for HashMap<K,V> {
+    fn to_json(&self) -> Json {
+        let mut members = Vec::<(String,Json)>::new();
+        for (key,value) in self {
+            match key.to_json() {
+                Json::String(s) | Json::Number(s) => members.push((s,value.to_json())),
+                _ => return Json::Array(self.iter().map(|(key,value)| Json::Array(vec![key.to_json(),value.to_json()])).collect()),
+            }
+        }
+        Json::Object(members)
+    }
+}
+
+impl<K: FromJson + Eq + Hash,V: FromJson> FromJson for HashMap<K,V> {
+    fn from_json(json: &Json) -> Result<Self,JsonError> {
+        let mut r = HashMap::<K,V>::new();
+        match json {
+            Json::Object(members) => {
+                for (key,value) in members {
+                    r.insert(K::from_json(&Json::String(key.clone()))?,V::from_json(value)?);
+                }
+            },
+            Json::Array(pairs) => {
+                for pair in pairs {
+                    match pair {
+                        Json::Array(kv) if kv.len() == 2 => {
+                            r.insert(K::from_json(&kv[0])?,V::from_json(&kv[1])?);
+                        },
+                        _ => return Err(JsonError::Expected("`[key,value]` pair".to_string())),
+                    }
+                }
+            },
+            _ => return Err(JsonError::Expected("object".to_string())),
+        }
+        Ok(r)
+    }
+}
+
+// the CRC is only on the wire
+impl<T: ToJson> ToJson for crate::Checked<T> {
+    fn to_json(&self) -> Json {
+        self.0.to_json()
+    }
+}
+
+impl<T: FromJson> FromJson for crate::Checked<T> {
+    fn from_json(json: &Json) -> Result<Self,JsonError> {
+        Ok(crate::Checked(T::from_json(json)?))
+    }
+}
+
+// quantized floats are plain numbers in JSON
+macro_rules! json_half {
+    ($($t:ident),*) => {
+        $(
+            impl ToJson for crate::$t {
+                fn to_json(&self) -> Json {
+                    self.0.to_json()
+                }
+            }
+
+            impl FromJson for crate::$t {
+                fn from_json(json: &Json) -> Result<Self,JsonError> {
+                    Ok(crate::$t(f32::from_json(json)?))
+                }
+            }
+        )*
+    };
+}
+
+json_half!(F16,BF16);
+
+impl<T: ToJson,const MIN: i64,const MAX: i64,const BITS: u32> ToJson for crate::Quantized<T,MIN,MAX,BITS> {
+    fn to_json(&self) -> Json {
+        self.0.to_json()
+    }
+}
+
+impl<T: FromJson,const MIN: i64,const MAX: i64,const BITS: u32> FromJson for crate::Quantized<T,MIN,MAX,BITS> {
+    fn from_json(json: &Json) -> Result<Self,JsonError> {
+        Ok(crate::Quantized(T::from_json(json)?))
+    }
+}
+
+// a bitset is an array of booleans, like the `Vec<bool>` it holds
+impl ToJson for crate::Bitset {
+    fn to_json(&self) -> Json {
+        self.0.to_json()
+    }
+}
+
+impl FromJson for crate::Bitset {
+    fn from_json(json: &Json) -> Result<Self,JsonError> {
+        Ok(crate::Bitset(Vec::<bool>::from_json(json)?))
+    }
+}
+
+impl<T: ToJson> ToJson for crate::Compressed<T> {
+    fn to_json(&self) -> Json {
+        (**self).to_json()
+    }
+}
+
+impl<T: FromJson> FromJson for crate::Compressed<T> {
+    fn from_json(json: &Json) -> Result<Self,JsonError> {
+        Ok(crate::Compressed::new(T::from_json(json)?))
+    }
+}
+
+macro_rules! json_addr {
+    ($($t:ty),*) => {
+        $(
+            impl ToJson for $t {
+                fn to_json(&self) -> Json {
+                    Json::String(self.to_string())
+                }
+            }
+
+            impl FromJson for $t {
+                fn from_json(json: &Json) -> Result<Self,JsonError> {
+                    if let Json::String(s) = json {
+                        <$t>::from_str(s).map_err(|_| JsonError::Expected(stringify!($t).to_string()))
+                    }
+                    else {
+                        Err(JsonError::Expected(stringify!($t).to_string()))
+                    }
+                }
+            }
+        )*
+    };
+}
+
+json_addr!(Ipv4Addr,Ipv6Addr,IpAddr,SocketAddrV4,SocketAddrV6,SocketAddr);
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let json = Json::parse(" { \"a\": [1, -2.5e3, true, null], \"b\": \"x\\n\\u00e9\\ud83d\\ude00\" } ").unwrap();
+        assert_eq!(json.get("a"),Some(&Json::Array(vec![Json::Number("1".to_string()),Json::Number("-2.5e3".to_string()),Json::Bool(true),Json::Null])));
+        assert_eq!(json.get("b"),Some(&Json::String("x\né😀".to_string())));
+        assert_eq!(Json::parse(&json.to_string()),Ok(json.clone()));
+        assert_eq!(Json::parse(&json.pretty()),Ok(json));
+    }
+
+    #[test]
+    fn test_syntax_error() {
+        assert_eq!(Json::parse("{\n  \"a\": [1,\n  2 }"),Err(JsonError::Syntax { line: 3, column: 5, message: "`,` or `]` expected".to_string(), }));
+    }
+
+    #[test]
+    fn test_base64() {
+        for data in [&b""[..],b"f",b"fo",b"foo",b"foob",b"fooba",b"foobar"].iter() {
+            assert_eq!(base64_decode(&base64_encode(data)),Some(data.to_vec()));
+        }
+        assert_eq!(base64_encode(b"foobar"),"Zm9vYmFy");
+        assert_eq!(base64_encode(b"fooba"),"Zm9vYmE=");
+    }
+}
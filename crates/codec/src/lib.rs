@@ -7,25 +7,174 @@
 
 pub trait Codec where Self: Sized {
 
-    /// Decode from `buffer` into new object.
+    /// Size of the encoded version of every object of this type, if they
+    /// all have the same size.
+    /// 
+    /// This is `None` for anything with a length prefix, or enum variants
+    /// of different sizes.
+    const FIXED_SIZE: Option<usize> = None;
+
+    /// Maximum size of the encoded version of any object of this type, if
+    /// there is one.
+    /// 
+    /// This is `None` for anything with a length prefix.
+    const MAX_SIZE: Option<usize> = Self::FIXED_SIZE;
+
+    /// Decode from `buffer` into new object, within the default `DecodeLimits`.
     /// 
     /// If successful, returns the number of bytes decoded and the newly
-    /// created object. If not successful, returns `None`.
-    fn decode(buffer: &[u8]) -> Option<(usize,Self)>;
+    /// created object.
+    fn decode(buffer: &[u8]) -> Result<(usize,Self)> {
+        Self::decode_limited(buffer,&DecodeLimits::default())
+    }
+
+    /// Decode from `buffer` into new object, within `limits`.
+    fn decode_limited(buffer: &[u8],limits: &DecodeLimits) -> Result<(usize,Self)> {
+        Self::decode_with(buffer,&mut Limiter::new(limits))
+    }
+
+    /// Decode from `buffer` into new object, charging `limiter` for
+    /// everything that gets allocated.
+    /// 
+    /// This is what implementations provide, and what they call on their
+    /// parts. Length prefixes must be checked with `limiter` before
+    /// allocating anything for them.
+    fn decode_with(buffer: &[u8],limiter: &mut Limiter) -> Result<(usize,Self)>;
+
+    /// Decode from `buffer` into `self`, within the default `DecodeLimits`,
+    /// reusing the allocations `self` already has.
+    /// 
+    /// If successful, returns the number of bytes decoded. On error, `self`
+    /// is left in a valid but unspecified state.
+    fn decode_into(&mut self,buffer: &[u8]) -> Result<usize> {
+        self.decode_into_limited(buffer,&DecodeLimits::default())
+    }
+
+    /// Decode from `buffer` into `self`, within `limits`.
+    fn decode_into_limited(&mut self,buffer: &[u8],limits: &DecodeLimits) -> Result<usize> {
+        self.decode_into_with(buffer,&mut Limiter::new(limits))
+    }
+
+    /// Decode from `buffer` into `self`, charging `limiter` exactly like
+    /// `decode_with` would.
+    /// 
+    /// The default decodes a new object and replaces `self` with it. Types
+    /// that own allocations override this to reuse them.
+    fn decode_into_with(&mut self,buffer: &[u8],limiter: &mut Limiter) -> Result<usize> {
+        let (l,value) = Self::decode_with(buffer,limiter)?;
+        *self = value;
+        Ok(l)
+    }
+
+    /// Find the end of the value encoded at the start of `buffer`, without
+    /// decoding it.
+    /// 
+    /// Returns the number of bytes the encoded value occupies. Nothing is
+    /// allocated, so only the nesting depth is limited.
+    fn skip(buffer: &[u8]) -> Result<usize> {
+        Self::skip_with(buffer,&mut Limiter::new(&DecodeLimits::unbounded()))
+    }
+
+    /// Check that `buffer` starts with a value that `decode` would accept,
+    /// without decoding it.
+    fn validate(buffer: &[u8]) -> Result<()> {
+        Self::validate_limited(buffer,&DecodeLimits::default())
+    }
+
+    /// Check that `buffer` starts with a value that `decode_limited` would
+    /// accept with `limits`, without decoding it.
+    fn validate_limited(buffer: &[u8],limits: &DecodeLimits) -> Result<()> {
+        Self::skip_with(buffer,&mut Limiter::new(limits)).map(|_| ())
+    }
+
+    /// Walk over the value encoded at the start of `buffer`, charging
+    /// `limiter` exactly like `decode_with` would, but without allocating.
+    /// 
+    /// The default decodes the value and drops it, which is fine for types
+    /// that don't allocate. Types with length prefixes override this.
+    fn skip_with(buffer: &[u8],limiter: &mut Limiter) -> Result<usize> {
+        Self::decode_with(buffer,limiter).map(|(l,_)| l)
+    }
+
+    /// Annotate the value encoded at `annotator.rest()` as `path`, see
+    /// `annotate`.
+    /// 
+    /// The default annotates the whole value as one range. Derived
+    /// implementations and collections annotate each field or element.
+    fn annotate_with(annotator: &mut Annotator,path: &str) -> Result<()> {
+        let len = Self::skip_with(annotator.rest(),annotator.limiter())?;
+        annotator.leaf(path,len)
+    }
 
     /// Encode `self` onto the end of `buffer`.
     /// 
     /// Returns the number of bytes appended to the buffer.
     fn encode(&self,buffer: &mut Vec<u8>) -> usize;
 
+    /// Encode `self` into a new buffer of exactly `size()` bytes.
+    fn encode_to_vec(&self) -> Vec<u8> {
+        let size = self.size();
+        let mut buffer = Vec::<u8>::with_capacity(size);
+        let len = self.encode(&mut buffer);
+        debug_assert_eq!(len,size,"encode() and size() disagree");
+        debug_assert_eq!(buffer.len(),size,"encode() wrote a different number of bytes than size()");
+        buffer
+    }
+
+    /// Encode `self` onto the end of `buffer`, in canonical form.
+    /// 
+    /// Equal values always encode into the same bytes: map and set entries
+    /// are sorted by their encoded key, and NaN floats are written as the
+    /// one canonical quiet NaN. The result decodes like any other encoding.
+    /// 
+    /// Returns the number of bytes appended to the buffer.
+    fn encode_canonical(&self,buffer: &mut Vec<u8>) -> usize {
+        self.encode(buffer)
+    }
+
+    /// Encode `self` onto the end of `gather`, referencing large byte
+    /// sequences in place instead of copying them.
+    /// 
+    /// Returns the number of bytes appended. The default encodes into the
+    /// scratch buffer. Byte sequences, and the types that can contain
+    /// them, override this.
+    fn encode_gather<'a>(&'a self,gather: &mut Gather<'a>) -> usize {
+        self.encode(gather.buffer())
+    }
+
+    /// `items` as bytes, if `Self` is `u8`, so `Vec<u8>` and `Cow<[u8]>`
+    /// can be referenced by `encode_gather`.
+    #[doc(hidden)]
+    fn slice_as_bytes(_items: &[Self]) -> Option<&[u8]> {
+        None
+    }
+
     /// Calculate the size of the encoded version of the object.
     /// 
     /// Returns the number of bytes this would encode into.
     fn size(&self) -> usize;
 }
 
+/// Marker for types that always encode to the same number of bytes.
+/// 
+/// Derived when all fields are primitive, packed or quantized and all enum
+/// variants have the same size, or with `#[codec(fixed_size)]`. `SIZE` is
+/// known at compile time, so buffers and datagrams can be sized without
+/// looking at the value.
+pub trait FixedSize: Codec {
+
+    /// Size of the encoded version of every object of this type.
+    const SIZE: usize;
+}
+
 pub use macros::*;
 
+mod error;
+pub use error::*;
+
+mod limits;
+pub use limits::*;
+
 mod bool;
 pub use crate::bool::*;
 
@@ -44,14 +193,63 @@ pub use ui64::*;
 mod float;
 pub use float::*;
 
+mod quantize;
+pub use quantize::*;
+
+mod bits;
+pub use bits::*;
+
 mod string;
 pub use string::*;
 
 mod vec;
 pub use vec::*;
 
+mod option;
+pub use option::*;
+
 mod hashmap;
 pub use hashmap::*;
 
+mod hashset;
+pub use hashset::*;
+
+mod sorted;
+
 mod addrs;
-pub use addrs::*;
\ No newline at end of file
+pub use addrs::*;
+
+mod cow;
+pub use cow::*;
+
+mod hash;
+pub use hash::*;
+
+mod annotate;
+pub use annotate::*;
+
+mod checked;
+pub use checked::*;
+
+mod compressed;
+pub use compressed::*;
+
+mod incremental;
+pub use incremental::*;
+
+mod seq;
+pub use seq::*;
+
+mod gather;
+pub use gather::*;
+
+mod message;
+pub use message::*;
+
+pub mod testing;
+
+pub mod json;
+
+pub mod delta;
+
+pub mod schema;
@@ -0,0 +1,140 @@
+// Echidna - Codec
+
+use crate::*;
+
+/// Resource limits for decoding untrusted buffers.
+/// 
+/// Length prefixes come straight from the wire, so they are checked against
+/// these limits before anything is allocated.
+#[derive(Clone,Copy,Debug,PartialEq,Eq)]
+pub struct DecodeLimits {
+
+    /// Maximum number of elements in a single `Vec` or `HashMap`.
+    pub max_len: usize,
+
+    /// Maximum number of bytes in a single `String`.
+    pub max_string_bytes: usize,
+
+    /// Maximum number of bytes allocated over the entire decode.
+    pub max_alloc: usize,
+
+    /// Maximum nesting depth of collections and derived types.
+    pub max_depth: usize,
+}
+
+impl Default for DecodeLimits {
+    fn default() -> DecodeLimits {
+        DecodeLimits {
+            max_len: 16 * 1024 * 1024,
+            max_string_bytes: 1024 * 1024,
+            max_alloc: 64 * 1024 * 1024,
+            max_depth: 64,
+        }
+    }
+}
+
+impl DecodeLimits {
+
+    /// No limits on lengths or allocation, only on nesting depth, which
+    /// protects the stack. Used for `skip`, which doesn't allocate.
+    pub fn unbounded() -> DecodeLimits {
+        DecodeLimits {
+            max_len: usize::MAX,
+            max_string_bytes: usize::MAX,
+            max_alloc: usize::MAX,
+            ..DecodeLimits::default()
+        }
+    }
+}
+
+/// Running account of one decode against its `DecodeLimits`.
+pub struct Limiter {
+    limits: DecodeLimits,
+    allocated: usize,
+    depth: usize,
+}
+
+impl Limiter {
+
+    pub fn new(limits: &DecodeLimits) -> Limiter {
+        Limiter {
+            limits: *limits,
+            allocated: 0,
+            depth: 0,
+        }
+    }
+
+    pub fn limits(&self) -> &DecodeLimits {
+        &self.limits
+    }
+
+    /// Charge `bytes` against the total allocation.
+    pub fn alloc(&mut self,bytes: usize) -> Result<()> {
+        let allocated = self.allocated.saturating_add(bytes);
+        if allocated > self.limits.max_alloc {
+            return Err(Error::TooLarge(allocated));
+        }
+        self.allocated = allocated;
+        Ok(())
+    }
+
+    /// Check a collection of `len` elements of `item_size` bytes each, before allocating it.
+    pub fn collection(&mut self,len: usize,item_size: usize) -> Result<()> {
+        if len > self.limits.max_len {
+            return Err(Error::TooLong(len));
+        }
+        self.alloc(len.saturating_mul(item_size))
+    }
+
+    /// Check a string of `len` bytes, before allocating it.
+    pub fn string(&mut self,len: usize) -> Result<()> {
+        if len > self.limits.max_string_bytes {
+            return Err(Error::TooLong(len));
+        }
+        self.alloc(len)
+    }
+
+    /// Step into a nested value.
+    pub fn enter(&mut self) -> Result<()> {
+        if self.depth >= self.limits.max_depth {
+            return Err(Error::TooDeep);
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// Step out of a nested value again.
+    pub fn leave(&mut self) {
+        self.depth -= 1;
+    }
+}
+
+/// Number of elements of a collection of `len` to allocate room for up
+/// front, with `remaining` bytes left after its length prefix and at least
+/// `item_size` bytes per element.
+///
+/// A short buffer with a large length prefix doesn't get more than it can
+/// hold; the collection grows past this as elements are actually decoded.
+pub(crate) fn capacity(len: usize,remaining: usize,item_size: usize) -> usize {
+    len.min(remaining / item_size.max(1))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn test_limits() {
+        let limits = DecodeLimits { max_len: 4, max_string_bytes: 8, max_alloc: 16, max_depth: 1, };
+        let mut limiter = Limiter::new(&limits);
+        assert_eq!(limiter.collection(5,1),Err(Error::TooLong(5)));
+        assert_eq!(limiter.string(9),Err(Error::TooLong(9)));
+        assert_eq!(limiter.collection(4,4),Ok(()));
+        assert_eq!(limiter.string(1),Err(Error::TooLarge(17)));
+        assert_eq!(limiter.enter(),Ok(()));
+        assert_eq!(limiter.enter(),Err(Error::TooDeep));
+        limiter.leave();
+        assert_eq!(limiter.enter(),Ok(()));
+    }
+}
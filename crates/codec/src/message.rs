@@ -0,0 +1,38 @@
+// Echidna - Codec
+
+use crate::{
+    *,
+    schema::*,
+};
+
+/// Type sent on a topic, with what tools need to know about it.
+///
+/// `#[derive(Message)]` implements this on a type that also has
+/// `#[derive(Codec)]` and `#[codec(schema)]`. `#[codec(name = "...")]`,
+/// `#[codec(topic = "...")]` and `#[codec(version = N)]` override the
+/// defaults: the module path and the type, `/` and the type in snake case,
+/// and 1.
+pub trait Message: Codec + Describe {
+
+    /// Fully qualified type name, like `sensors::Scan`.
+    const NAME: &'static str;
+
+    /// Topic the type is published on, unless told otherwise.
+    const TOPIC: &'static str;
+
+    /// Version of the type, raised by hand when its meaning changes.
+    const VERSION: u32;
+
+    /// Schema of the type and every type it refers to.
+    fn schema() -> Schema {
+        Schema::of::<Self>()
+    }
+
+    /// Fingerprint of the schema, which changes whenever the encoding does,
+    /// see `Schema::fingerprint`.
+    fn fingerprint() -> u64 {
+        let mut schema = Schema::default();
+        let ty = Self::describe(&mut schema);
+        schema.fingerprint(&ty)
+    }
+}
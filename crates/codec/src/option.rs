@@ -0,0 +1,112 @@
+// Echidna - Codec
+
+use crate::*;
+
+// a `u8` tag, 0 for `None` and 1 for `Some`, followed by the value
+impl<T: Codec> Codec for Option<T> {
+    const MAX_SIZE: Option<usize> = match T::MAX_SIZE {
+        Some(n) => Some(1 + n),
+        None => None,
+    };
+
+    fn decode_with(buffer: &[u8],limiter: &mut Limiter) -> Result<(usize,Self)> {
+        let (_,tag) = u8::decode_with(buffer,limiter)?;
+        match tag {
+            0 => Ok((1,None)),
+            1 => {
+                let (l,value) = T::decode_with(&buffer[1..],limiter)?;
+                Ok((1 + l,Some(value)))
+            },
+            _ => Err(Error::InvalidTag(tag as u32)),
+        }
+    }
+
+    fn decode_into_with(&mut self,buffer: &[u8],limiter: &mut Limiter) -> Result<usize> {
+        let (_,tag) = u8::decode_with(buffer,limiter)?;
+        match (tag,self.as_mut()) {
+            (0,_) => {
+                *self = None;
+                Ok(1)
+            },
+            (1,Some(value)) => Ok(1 + value.decode_into_with(&buffer[1..],limiter)?),
+            (1,None) => {
+                let (l,value) = T::decode_with(&buffer[1..],limiter)?;
+                *self = Some(value);
+                Ok(1 + l)
+            },
+            _ => Err(Error::InvalidTag(tag as u32)),
+        }
+    }
+
+    fn skip_with(buffer: &[u8],limiter: &mut Limiter) -> Result<usize> {
+        let (_,tag) = u8::decode_with(buffer,limiter)?;
+        match tag {
+            0 => Ok(1),
+            1 => Ok(1 + T::skip_with(&buffer[1..],limiter)?),
+            _ => Err(Error::InvalidTag(tag as u32)),
+        }
+    }
+
+    fn annotate_with(annotator: &mut Annotator,path: &str) -> Result<()> {
+        let (_,tag) = u8::decode_with(annotator.rest(),annotator.limiter())?;
+        match tag {
+            0 => annotator.leaf(&format!("{}[None]",path),1),
+            1 => {
+                annotator.leaf(&format!("{}[Some]",path),1)?;
+                T::annotate_with(annotator,path)
+            },
+            _ => Err(Error::InvalidTag(tag as u32)),
+        }
+    }
+
+    fn encode(&self,buffer: &mut Vec<u8>) -> usize {
+        match self {
+            None => 0u8.encode(buffer),
+            Some(value) => 1u8.encode(buffer) + value.encode(buffer),
+        }
+    }
+
+    fn encode_canonical(&self,buffer: &mut Vec<u8>) -> usize {
+        match self {
+            None => 0u8.encode(buffer),
+            Some(value) => 1u8.encode(buffer) + value.encode_canonical(buffer),
+        }
+    }
+
+    fn encode_gather<'a>(&'a self,gather: &mut Gather<'a>) -> usize {
+        match self {
+            None => 0u8.encode(gather.buffer()),
+            Some(value) => 1u8.encode(gather.buffer()) + value.encode_gather(gather),
+        }
+    }
+
+    fn size(&self) -> usize {
+        match self {
+            None => 1,
+            Some(value) => 1 + value.size(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn test_option() {
+        assert_eq!(Some(5u16).encode_to_vec(),vec![1,5,0]);
+        assert_eq!(None::<u16>.encode_to_vec(),vec![0]);
+        assert_eq!(Option::<u16>::decode(&[1,5,0]),Ok((3,Some(5))));
+        assert_eq!(Option::<u16>::decode(&[0]),Ok((1,None)));
+        assert_eq!(Option::<u16>::decode(&[2]),Err(Error::InvalidTag(2)));
+        assert_eq!(Option::<u16>::decode(&[1,5]),Err(Error::Truncated));
+        assert_eq!(Option::<u16>::MAX_SIZE,Some(3));
+        assert_eq!(Option::<String>::MAX_SIZE,None);
+        let mut value = Some("ab".to_string());
+        assert_eq!(value.decode_into(&[1,1,0,0,0,b'c']),Ok(6));
+        assert_eq!(value,Some("c".to_string()));
+        assert_eq!(value.decode_into(&[0]),Ok(1));
+        assert_eq!(value,None);
+    }
+}
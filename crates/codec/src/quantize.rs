@@ -0,0 +1,295 @@
+// Echidna - Codec
+
+use crate::*;
+
+/// IEEE 754 half precision bits nearest to `value`, ties to even.
+pub fn f32_to_f16(value: f32) -> u16 {
+    let x = value.to_bits();
+    let sign = ((x >> 16) & 0x8000) as u16;
+    let exp = ((x >> 23) & 0xFF) as i32;
+    let man = x & 0x7FFFFF;
+    if exp == 0xFF {
+        // infinity, or NaN with the quiet bit set
+        return sign | 0x7C00 | if man != 0 { 0x0200 | (man >> 13) as u16 } else { 0 };
+    }
+    let e = exp - 127 + 15;
+    if e >= 0x1F {
+        return sign | 0x7C00;
+    }
+    let (half,rem,halfway) = if e <= 0 {
+        // subnormal, or too small for anything but zero
+        if e < -10 {
+            return sign;
+        }
+        let m = man | 0x800000;
+        let shift = (14 - e) as u32;
+        (m >> shift,m & ((1 << shift) - 1),1 << (shift - 1))
+    }
+    else {
+        (((e as u32) << 10) | (man >> 13),man & 0x1FFF,0x1000)
+    };
+    // rounding up may carry into the exponent, which is still correct
+    if (rem > halfway) || ((rem == halfway) && ((half & 1) != 0)) {
+        sign | (half + 1) as u16
+    }
+    else {
+        sign | half as u16
+    }
+}
+
+/// Value of IEEE 754 half precision `bits`, exactly.
+pub fn f16_to_f32(bits: u16) -> f32 {
+    let sign = ((bits & 0x8000) as u32) << 16;
+    let exp = ((bits >> 10) & 0x1F) as u32;
+    let man = (bits & 0x3FF) as u32;
+    let x = if exp == 0 {
+        if man == 0 {
+            sign
+        }
+        else {
+            // subnormal, normalize it
+            let mut e = 127 - 15 + 1;
+            let mut m = man;
+            while (m & 0x400) == 0 {
+                m <<= 1;
+                e -= 1;
+            }
+            sign | (e << 23) | ((m & 0x3FF) << 13)
+        }
+    }
+    else if exp == 0x1F {
+        sign | 0x7F800000 | (man << 13)
+    }
+    else {
+        sign | ((exp + 127 - 15) << 23) | (man << 13)
+    };
+    f32::from_bits(x)
+}
+
+/// bfloat16 bits nearest to `value`, ties to even.
+pub fn f32_to_bf16(value: f32) -> u16 {
+    let x = value.to_bits();
+    if value.is_nan() {
+        return ((x >> 16) | 0x0040) as u16;
+    }
+    ((x + 0x7FFF + ((x >> 16) & 1)) >> 16) as u16
+}
+
+/// Value of bfloat16 `bits`, exactly.
+pub fn bf16_to_f32(bits: u16) -> f32 {
+    f32::from_bits((bits as u32) << 16)
+}
+
+/// `f32` that goes over the wire as IEEE 754 half precision, 2 bytes.
+///
+/// Half precision has 11 significant bits and a range of about ±65504,
+/// which suits normals, confidences and the like.
+#[derive(Clone,Copy,Debug,Default,PartialEq,PartialOrd)]
+pub struct F16(pub f32);
+
+/// `f32` that goes over the wire as bfloat16, 2 bytes.
+///
+/// bfloat16 keeps the full `f32` range, with 8 significant bits.
+#[derive(Clone,Copy,Debug,Default,PartialEq,PartialOrd)]
+pub struct BF16(pub f32);
+
+macro_rules! codec_half {
+    ($t:ident,$to:ident,$from:ident,$nan:expr) => {
+        impl Codec for $t {
+
+            const FIXED_SIZE: Option<usize> = Some(2);
+
+            fn decode_with(buffer: &[u8],limiter: &mut Limiter) -> Result<(usize,Self)> {
+                let (l,bits) = u16::decode_with(buffer,limiter)?;
+                Ok((l,$t($from(bits))))
+            }
+
+            fn encode(&self,buffer: &mut Vec<u8>) -> usize {
+                $to(self.0).encode(buffer)
+            }
+
+            fn encode_canonical(&self,buffer: &mut Vec<u8>) -> usize {
+                if self.0.is_nan() { ($nan as u16).encode(buffer) } else { self.encode(buffer) }
+            }
+
+            fn size(&self) -> usize {
+                2
+            }
+        }
+
+        impl FixedSize for $t {
+            const SIZE: usize = 2;
+        }
+
+        impl From<f32> for $t {
+            fn from(value: f32) -> Self {
+                $t(value)
+            }
+        }
+
+        impl From<f64> for $t {
+            fn from(value: f64) -> Self {
+                $t(value as f32)
+            }
+        }
+
+        impl From<$t> for f32 {
+            fn from(value: $t) -> Self {
+                value.0
+            }
+        }
+
+        impl From<$t> for f64 {
+            fn from(value: $t) -> Self {
+                value.0 as f64
+            }
+        }
+    };
+}
+
+codec_half!(F16,f32_to_f16,f16_to_f32,0x7E00);
+codec_half!(BF16,f32_to_bf16,bf16_to_f32,0x7FC0);
+
+/// Float that goes over the wire as a `BITS`-bit integer, evenly spread
+/// over `MIN..=MAX`.
+///
+/// `T` is `f32` or `f64`. The bounds are integers, since floats can't be
+/// const generic parameters; negative ones need braces, like
+/// `Quantized<f32,{ -1 },1,10>`. Values are rounded to the nearest of the
+/// `2^BITS` steps, and clamped to the range; NaN becomes `MIN`. The
+/// encoding takes `BITS` rounded up to whole bytes, at most 4.
+#[derive(Clone,Copy,Debug,Default,PartialEq,PartialOrd)]
+pub struct Quantized<T,const MIN: i64,const MAX: i64,const BITS: u32>(pub T);
+
+impl<T,const MIN: i64,const MAX: i64,const BITS: u32> Quantized<T,MIN,MAX,BITS> {
+
+    /// Number of bytes on the wire.
+    pub const BYTES: usize = {
+        assert!((BITS >= 1) && (BITS <= 32),"Quantized needs 1 to 32 bits");
+        assert!(MIN < MAX,"Quantized needs MIN < MAX");
+        BITS.div_ceil(8) as usize
+    };
+
+    const STEPS: u64 = (1u64 << BITS) - 1;
+
+    fn quantize(value: f64) -> u64 {
+        if value.is_nan() {
+            return 0;
+        }
+        let q = ((value - MIN as f64) / ((MAX - MIN) as f64) * (Self::STEPS as f64)).round();
+        q.max(0.0).min(Self::STEPS as f64) as u64
+    }
+
+    fn dequantize(q: u64) -> f64 {
+        (MIN as f64) + (q.min(Self::STEPS) as f64) * ((MAX - MIN) as f64) / (Self::STEPS as f64)
+    }
+}
+
+macro_rules! codec_quantized {
+    ($($t:ty),*) => {
+        $(
+            impl<const MIN: i64,const MAX: i64,const BITS: u32> Codec for Quantized<$t,MIN,MAX,BITS> {
+
+                const FIXED_SIZE: Option<usize> = Some(Self::BYTES);
+
+                fn decode_with(buffer: &[u8],_limiter: &mut Limiter) -> Result<(usize,Self)> {
+                    let bytes = buffer.get(..Self::BYTES).ok_or(Error::Truncated)?;
+                    let mut q = 0u64;
+                    for i in 0..bytes.len() {
+                        q |= (bytes[i] as u64) << (8 * i);
+                    }
+                    Ok((Self::BYTES,Quantized(Self::dequantize(q) as $t)))
+                }
+
+                fn encode(&self,buffer: &mut Vec<u8>) -> usize {
+                    let q = Self::quantize(self.0 as f64);
+                    buffer.extend_from_slice(&q.to_le_bytes()[..Self::BYTES]);
+                    Self::BYTES
+                }
+
+                fn size(&self) -> usize {
+                    Self::BYTES
+                }
+            }
+
+            impl<const MIN: i64,const MAX: i64,const BITS: u32> FixedSize for Quantized<$t,MIN,MAX,BITS> {
+                const SIZE: usize = Self::BYTES;
+            }
+
+            impl<const MIN: i64,const MAX: i64,const BITS: u32> From<$t> for Quantized<$t,MIN,MAX,BITS> {
+                fn from(value: $t) -> Self {
+                    Quantized(value)
+                }
+            }
+
+            impl<const MIN: i64,const MAX: i64,const BITS: u32> From<Quantized<$t,MIN,MAX,BITS>> for $t {
+                fn from(value: Quantized<$t,MIN,MAX,BITS>) -> Self {
+                    value.0
+                }
+            }
+        )*
+    };
+}
+
+codec_quantized!(f32,f64);
+
+#[cfg(test)]
+mod tests {
+
+    use crate::*;
+
+    #[test]
+    fn test_f16() {
+        let cases: [(f32,u16); 10] = [
+            (0.0,0x0000),
+            (-0.0,0x8000),
+            (1.0,0x3C00),
+            (-2.0,0xC000),
+            (65504.0,0x7BFF),
+            (65520.0,0x7C00),
+            (f32::INFINITY,0x7C00),
+            (2f32.powi(-24),0x0001),
+            (2f32.powi(-14),0x0400),
+            (1.0 / 3.0,0x3555),
+        ];
+        for (value,bits) in cases.iter() {
+            assert_eq!(f32_to_f16(*value),*bits,"{}",value);
+        }
+        // every half value survives the round trip
+        for bits in 0..=0xFFFFu16 {
+            let value = f16_to_f32(bits);
+            if !value.is_nan() {
+                assert_eq!(f32_to_f16(value),bits);
+            }
+            else {
+                assert!(f16_to_f32(f32_to_f16(value)).is_nan());
+            }
+        }
+        // ties go to even
+        assert_eq!(f32_to_f16(1.0 + 1.0 / 2048.0),0x3C00);
+        assert_eq!(f32_to_f16(1.0 + 3.0 / 2048.0),0x3C02);
+    }
+
+    #[test]
+    fn test_bf16() {
+        assert_eq!(f32_to_bf16(1.0),0x3F80);
+        assert_eq!(f32_to_bf16(-3.0e38),0xFF62);
+        assert_eq!(bf16_to_f32(0x3F80),1.0);
+        assert!(bf16_to_f32(f32_to_bf16(f32::NAN)).is_nan());
+        assert_eq!(BF16(1.0).encode_to_vec(),vec![0x80,0x3F]);
+    }
+
+    #[test]
+    fn test_quantized() {
+        type Depth = Quantized<f32,0,10000,16>;
+        assert_eq!(<Depth as FixedSize>::SIZE,2);
+        assert_eq!(Quantized::<f32,0,10000,16>(0.0).encode_to_vec(),vec![0,0]);
+        assert_eq!(Quantized::<f32,0,10000,16>(10000.0).encode_to_vec(),vec![0xFF,0xFF]);
+        assert_eq!(Quantized::<f32,0,10000,16>(20000.0).encode_to_vec(),vec![0xFF,0xFF]);
+        let buffer = Quantized::<f32,{ -1 },1,12>(0.25).encode_to_vec();
+        let (_,value) = Quantized::<f32,{ -1 },1,12>::decode(&buffer).unwrap();
+        assert!((value.0 - 0.25).abs() <= 1.0 / 4095.0);
+        assert_eq!(Quantized::<f64,0,1,24>(0.5).size(),3);
+        assert_eq!(Quantized::<f64,0,1,24>::decode(&[0xFF,0xFF]),Err(Error::Truncated));
+    }
+}
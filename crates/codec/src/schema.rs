@@ -0,0 +1,441 @@
+// Echidna - Codec
+
+//! Language-neutral description of encoded types.
+//!
+//! A `Schema` lists structs and enums with the types of their fields, and
+//! constants. It says everything needed to produce the same bytes as the
+//! derived `Codec` impls, so code generators for other languages work from
+//! it. `codec-build` parses schemas from `.msg` files, and types derived
+//! with `#[codec(schema)]` describe themselves with `Schema::of`.
+
+use {
+    crate::{
+        sha256,
+        Bitset,
+        BF16,
+        Checked,
+        Compressed,
+        F16,
+        Quantized,
+    },
+    std::{
+        borrow::Cow,
+        collections::{
+            HashMap,
+            HashSet,
+        },
+        fmt,
+        net::{
+            Ipv4Addr,
+            Ipv6Addr,
+            IpAddr,
+            SocketAddrV4,
+            SocketAddrV6,
+            SocketAddr,
+        },
+    },
+};
+
+/// Type of a field or constant.
+#[derive(Clone,Debug,PartialEq,Eq)]
+pub enum Type {
+    Bool,
+    U8,
+    I8,
+    U16,
+    I16,
+    U32,
+    I32,
+    U64,
+    I64,
+    F32,
+    F64,
+    String,
+    /// `Vec<T>`, a `u32` length followed by the elements.
+    Vec(Box<Type>),
+    /// `HashSet<T>`, encoded like a `Vec<T>`.
+    Set(Box<Type>),
+    /// `HashMap<K,V>`, a `u32` length followed by keys and values.
+    Map(Box<Type>,Box<Type>),
+    /// `Option<T>`, a `u8` tag, 0 for `None` and 1 for `Some`, followed by the value.
+    Option(Box<Type>),
+    /// A struct or enum defined in the schema.
+    Named(String),
+    /// `F16`, an IEEE 754 half precision float, 2 bytes.
+    F16,
+    /// `BF16`, the upper 2 bytes of an `f32`.
+    BF16,
+    /// `Quantized`, a float spread over `min..=max` as a `bits`-bit
+    /// integer, in `bits` rounded up to whole bytes.
+    Quantized { min: i64, max: i64, bits: u32, },
+    /// `Bitset`, a `u32` number of bits followed by the bits, 8 per byte.
+    Bitset,
+    /// `Ipv4Addr`, 4 bytes.
+    Ipv4,
+    /// `Ipv6Addr`, 8 `u16` segments.
+    Ipv6,
+    /// `IpAddr`, a `u8` tag, 0 for IPv4 and 1 for IPv6, followed by the address.
+    Ip,
+    /// `SocketAddrV4`, the address followed by a `u16` port.
+    SocketV4,
+    /// `SocketAddrV6`, the address followed by a `u16` port.
+    SocketV6,
+    /// `SocketAddr`, a `u8` tag, 0 for IPv4 and 1 for IPv6, followed by the socket address.
+    Socket,
+    /// `Checked<T>`, the value followed by the `u32` CRC-32C of its encoding.
+    Checked(Box<Type>),
+    /// `Compressed<T>`, a `u8` method and the `u32` length of the encoded
+    /// value, followed by the value as is, or LZ-compressed behind a `u32`
+    /// length.
+    Compressed(Box<Type>),
+}
+
+impl Type {
+
+    /// Type from its schema name, like `u32` or `string`, for types without parameters.
+    pub fn primitive(name: &str) -> Option<Type> {
+        Some(match name {
+            "bool" => Type::Bool,
+            "u8" => Type::U8,
+            "i8" => Type::I8,
+            "u16" => Type::U16,
+            "i16" => Type::I16,
+            "u32" => Type::U32,
+            "i32" => Type::I32,
+            "u64" => Type::U64,
+            "i64" => Type::I64,
+            "f32" => Type::F32,
+            "f64" => Type::F64,
+            "string" => Type::String,
+            _ => return None,
+        })
+    }
+
+    /// Whether this is one of the integer types.
+    pub fn is_integer(&self) -> bool {
+        matches!(self,Type::U8 | Type::I8 | Type::U16 | Type::I16 | Type::U32 | Type::I32 | Type::U64 | Type::I64)
+    }
+
+    /// Whether this is `f32` or `f64`.
+    pub fn is_float(&self) -> bool {
+        matches!(self,Type::F32 | Type::F64)
+    }
+
+    /// Whether this only uses the types schema files have, which is what
+    /// every generator supports. Types derived with `#[codec(schema)]` can
+    /// also have the codec types, like `F16` and `Checked<T>`.
+    pub fn is_portable(&self) -> bool {
+        match self {
+            Type::Vec(ty) | Type::Set(ty) | Type::Option(ty) => ty.is_portable(),
+            Type::Map(key,value) => key.is_portable() && value.is_portable(),
+            Type::F16 | Type::BF16 | Type::Quantized { .. } | Type::Bitset |
+            Type::Ipv4 | Type::Ipv6 | Type::Ip | Type::SocketV4 | Type::SocketV6 | Type::Socket |
+            Type::Checked(_) | Type::Compressed(_) => false,
+            _ => true,
+        }
+    }
+}
+
+// schema syntax, `u32`, `f32[]`, `map<string,u8>`, `Pose?`
+impl fmt::Display for Type {
+    fn fmt(&self,f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Type::Bool => write!(f,"bool"),
+            Type::U8 => write!(f,"u8"),
+            Type::I8 => write!(f,"i8"),
+            Type::U16 => write!(f,"u16"),
+            Type::I16 => write!(f,"i16"),
+            Type::U32 => write!(f,"u32"),
+            Type::I32 => write!(f,"i32"),
+            Type::U64 => write!(f,"u64"),
+            Type::I64 => write!(f,"i64"),
+            Type::F32 => write!(f,"f32"),
+            Type::F64 => write!(f,"f64"),
+            Type::String => write!(f,"string"),
+            Type::Vec(ty) => write!(f,"{}[]",ty),
+            Type::Set(ty) => write!(f,"set<{}>",ty),
+            Type::Map(key,value) => write!(f,"map<{},{}>",key,value),
+            Type::Option(ty) => write!(f,"{}?",ty),
+            Type::Named(name) => write!(f,"{}",name),
+            Type::F16 => write!(f,"f16"),
+            Type::BF16 => write!(f,"bf16"),
+            Type::Quantized { min,max,bits, } => write!(f,"quantized<{},{},{}>",min,max,bits),
+            Type::Bitset => write!(f,"bitset"),
+            Type::Ipv4 => write!(f,"ipv4"),
+            Type::Ipv6 => write!(f,"ipv6"),
+            Type::Ip => write!(f,"ip"),
+            Type::SocketV4 => write!(f,"socketv4"),
+            Type::SocketV6 => write!(f,"socketv6"),
+            Type::Socket => write!(f,"socket"),
+            Type::Checked(ty) => write!(f,"checked<{}>",ty),
+            Type::Compressed(ty) => write!(f,"compressed<{}>",ty),
+        }
+    }
+}
+
+/// Value of a constant.
+#[derive(Clone,Debug,PartialEq)]
+pub enum Value {
+    Bool(bool),
+    Int(i128),
+    Float(f64),
+    String(String),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self,f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Bool(b) => write!(f,"{}",b),
+            Value::Int(i) => write!(f,"{}",i),
+            Value::Float(x) => write!(f,"{:?}",x),
+            Value::String(s) => write!(f,"{:?}",s),
+        }
+    }
+}
+
+/// Named constant.
+#[derive(Clone,Debug,PartialEq)]
+pub struct Const {
+    pub doc: Vec<String>,
+    pub name: String,
+    pub ty: Type,
+    pub value: Value,
+}
+
+/// Field of a struct or enum variant. Tuple fields are named `_0`, `_1`, etc.
+#[derive(Clone,Debug,PartialEq)]
+pub struct Field {
+    pub doc: Vec<String>,
+    pub name: String,
+    pub ty: Type,
+}
+
+/// Enum variant, encoded as its `u32` index followed by its fields.
+#[derive(Clone,Debug,PartialEq)]
+pub struct Variant {
+    pub doc: Vec<String>,
+    pub name: String,
+    pub fields: Vec<Field>,
+}
+
+#[derive(Clone,Debug,PartialEq)]
+pub enum ItemKind {
+    /// Fields encoded in order.
+    Struct(Vec<Field>),
+    Enum(Vec<Variant>),
+}
+
+/// Struct or enum, with the constants that belong to it.
+#[derive(Clone,Debug,PartialEq)]
+pub struct Item {
+    pub doc: Vec<String>,
+    pub name: String,
+    pub kind: ItemKind,
+    pub consts: Vec<Const>,
+}
+
+/// Everything defined in one schema file.
+#[derive(Clone,Debug,Default)]
+pub struct Schema {
+    pub consts: Vec<Const>,
+    pub items: Vec<Item>,
+    // item names of the Rust types described so far, by `type_name`
+    names: HashMap<&'static str,String>,
+}
+
+// only what is defined counts, not where it came from
+impl PartialEq for Schema {
+    fn eq(&self,other: &Self) -> bool {
+        (self.consts == other.consts) && (self.items == other.items)
+    }
+}
+
+impl Schema {
+
+    /// Item called `name`.
+    pub fn item(&self,name: &str) -> Option<&Item> {
+        self.items.iter().find(|item| item.name == name)
+    }
+
+    /// Name of the item for the Rust type `T`, and whether that item still
+    /// has to be added. For the `Describe` impls of structs and enums.
+    ///
+    /// The item is called `name`, unless a different type already has an
+    /// item called that, in which case the path of `T` with `_` between
+    /// its parts keeps them apart.
+    pub fn item_name<T: ?Sized>(&mut self,name: &str) -> (String,bool) {
+        let path = std::any::type_name::<T>();
+        if let Some(name) = self.names.get(path) {
+            return (name.clone(),false);
+        }
+        let name = if self.item(name).is_some() { path.replace("::","_") } else { name.to_string() };
+        self.names.insert(path,name.clone());
+        (name,true)
+    }
+
+    /// Schema of `T` and every item it refers to, with `T` first.
+    pub fn of<T: Describe>() -> Schema {
+        let mut schema = Schema::default();
+        T::describe(&mut schema);
+        schema
+    }
+
+    // names of the items `ty` refers to, in the order they are first referred to
+    fn collect_names<'a>(&'a self,ty: &'a Type,names: &mut Vec<&'a str>) {
+        match ty {
+            Type::Vec(ty) | Type::Set(ty) | Type::Option(ty) | Type::Checked(ty) | Type::Compressed(ty) => self.collect_names(ty,names),
+            Type::Map(key,value) => {
+                self.collect_names(key,names);
+                self.collect_names(value,names);
+            },
+            Type::Named(name) if !names.contains(&name.as_str()) => {
+                names.push(name);
+                match self.item(name).map(|item| &item.kind) {
+                    Some(ItemKind::Struct(fields)) => for field in fields {
+                        self.collect_names(&field.ty,names);
+                    },
+                    Some(ItemKind::Enum(variants)) => for field in variants.iter().flat_map(|variant| variant.fields.iter()) {
+                        self.collect_names(&field.ty,names);
+                    },
+                    None => { },
+                }
+            },
+            _ => { },
+        }
+    }
+
+    /// Fingerprint of `ty`, the first 8 bytes of the SHA-256 of it and the
+    /// items it refers to in schema syntax.
+    ///
+    /// Names, field types and order count, documentation and constants
+    /// don't. So a type derived with `#[codec(schema)]` has the same
+    /// fingerprint as the same type in a schema file.
+    pub fn fingerprint(&self,ty: &Type) -> u64 {
+        let mut names = Vec::<&str>::new();
+        self.collect_names(ty,&mut names);
+        let fields = |fields: &[Field]| fields.iter().map(|field| format!("{} {} ",field.ty,field.name)).collect::<String>();
+        let mut text = format!("{}\n",ty);
+        for name in names {
+            match self.item(name).map(|item| &item.kind) {
+                Some(ItemKind::Struct(f)) => text += &format!("struct {} {{ {}}}\n",name,fields(f)),
+                Some(ItemKind::Enum(variants)) => {
+                    text += &format!("enum {} {{ ",name);
+                    for variant in variants {
+                        if !variant.fields.is_empty() {
+                            text += &format!("{} {{ {}}} ",variant.name,fields(&variant.fields));
+                        }
+                        else {
+                            text += &format!("{} ",variant.name);
+                        }
+                    }
+                    text += "}\n";
+                },
+                None => text += &format!("{}\n",name),
+            }
+        }
+        let digest = sha256(text.as_bytes());
+        u64::from_le_bytes([digest[0],digest[1],digest[2],digest[3],digest[4],digest[5],digest[6],digest[7]])
+    }
+}
+
+/// Types that have a schema type. `#[codec(schema)]` implements this for
+/// derived structs and enums.
+pub trait Describe {
+
+    /// Type of `Self`. Structs and enums add their item to `schema` if it
+    /// isn't there yet, followed by the items of their fields.
+    fn describe(schema: &mut Schema) -> Type;
+}
+
+macro_rules! describe_primitive {
+    ($($t:ty => $v:ident,)*) => {
+        $(
+            impl Describe for $t {
+                fn describe(_: &mut Schema) -> Type {
+                    Type::$v
+                }
+            }
+        )*
+    }
+}
+
+describe_primitive! {
+    bool => Bool,
+    u8 => U8,
+    i8 => I8,
+    u16 => U16,
+    i16 => I16,
+    u32 => U32,
+    i32 => I32,
+    u64 => U64,
+    i64 => I64,
+    f32 => F32,
+    f64 => F64,
+    String => String,
+    F16 => F16,
+    BF16 => BF16,
+    Bitset => Bitset,
+    Ipv4Addr => Ipv4,
+    Ipv6Addr => Ipv6,
+    IpAddr => Ip,
+    SocketAddrV4 => SocketV4,
+    SocketAddrV6 => SocketV6,
+    SocketAddr => Socket,
+    str => String,
+}
+
+impl<T: Describe> Describe for Vec<T> {
+    fn describe(schema: &mut Schema) -> Type {
+        Type::Vec(Box::new(T::describe(schema)))
+    }
+}
+
+impl<T: Describe> Describe for HashSet<T> {
+    fn describe(schema: &mut Schema) -> Type {
+        Type::Set(Box::new(T::describe(schema)))
+    }
+}
+
+impl<K: Describe,V: Describe> Describe for HashMap<K,V> {
+    fn describe(schema: &mut Schema) -> Type {
+        let key = K::describe(schema);
+        Type::Map(Box::new(key),Box::new(V::describe(schema)))
+    }
+}
+
+impl<T: Describe> Describe for Option<T> {
+    fn describe(schema: &mut Schema) -> Type {
+        Type::Option(Box::new(T::describe(schema)))
+    }
+}
+
+impl<T: Describe> Describe for Checked<T> {
+    fn describe(schema: &mut Schema) -> Type {
+        Type::Checked(Box::new(T::describe(schema)))
+    }
+}
+
+impl<T: Describe> Describe for Compressed<T> {
+    fn describe(schema: &mut Schema) -> Type {
+        Type::Compressed(Box::new(T::describe(schema)))
+    }
+}
+
+impl<T,const MIN: i64,const MAX: i64,const BITS: u32> Describe for Quantized<T,MIN,MAX,BITS> {
+    fn describe(_: &mut Schema) -> Type {
+        Type::Quantized { min: MIN, max: MAX, bits: BITS, }
+    }
+}
+
+// a slice encodes like a `Vec`, and `Cow<str>` like a `String`
+impl<T: Describe> Describe for [T] {
+    fn describe(schema: &mut Schema) -> Type {
+        Type::Vec(Box::new(T::describe(schema)))
+    }
+}
+
+impl<'a,T: Describe + ToOwned + ?Sized> Describe for Cow<'a,T> {
+    fn describe(schema: &mut Schema) -> Type {
+        T::describe(schema)
+    }
+}
@@ -0,0 +1,149 @@
+// Echidna - Codec
+
+use crate::*;
+
+fn decode_entry<K: Codec,V: Codec>(buffer: &[u8],limiter: &mut Limiter) -> Result<(usize,(K,V))> {
+    let (l,key) = K::decode_with(buffer,limiter)?;
+    let (m,value) = V::decode_with(&buffer[l..],limiter)?;
+    Ok((l + m,(key,value)))
+}
+
+fn skip_entry<K: Codec,V: Codec>(buffer: &[u8],limiter: &mut Limiter) -> Result<usize> {
+    let l = K::skip_with(buffer,limiter)?;
+    Ok(l + V::skip_with(&buffer[l..],limiter)?)
+}
+
+type DecodeFn<T> = fn(&[u8],&mut Limiter) -> Result<(usize,T)>;
+
+type SkipFn = fn(&[u8],&mut Limiter) -> Result<usize>;
+
+/// Iterator over the elements of an encoded sequence, decoding one at a
+/// time.
+///
+/// Works on anything encoded as a `u32` length followed by the elements:
+/// `Vec<T>` and `HashSet<T>` with `new`, `HashMap<K,V>` with `entries`.
+/// Every element is decoded within the limits on its own, and dropped
+/// elements are never kept. An element that fails to decode is returned as
+/// an error, after which the iterator ends, because the elements after it
+/// can't be found anymore. `nth`, and so `skip` and `step_by`, step over
+/// elements without decoding them.
+pub struct SeqIter<'a,T> {
+    buffer: &'a [u8],
+    limits: DecodeLimits,
+    decode: DecodeFn<T>,
+    skip: SkipFn,
+    ofs: usize,
+    left: usize,
+}
+
+impl<'a,T> SeqIter<'a,T> {
+
+    fn with_parts(buffer: &'a [u8],limits: &DecodeLimits,decode: DecodeFn<T>,skip: SkipFn) -> Result<SeqIter<'a,T>> {
+        let mut limiter = Limiter::new(limits);
+        let (_,len) = u32::decode_with(buffer,&mut limiter)?;
+
+        // nothing is allocated, but the length should still be one `decode` accepts
+        limiter.collection(len as usize,0)?;
+        Ok(SeqIter {
+            buffer: buffer,
+            limits: *limits,
+            decode: decode,
+            skip: skip,
+            ofs: 4,
+            left: len as usize,
+        })
+    }
+
+    /// Offset of the next element in the buffer, or the end of the
+    /// sequence after the last one.
+    pub fn position(&self) -> usize {
+        self.ofs
+    }
+
+    /// Number of elements not yet returned.
+    pub fn remaining(&self) -> usize {
+        self.left
+    }
+
+    // step over one element, ending the iterator on error
+    fn step(&mut self) -> Result<()> {
+        match (self.skip)(&self.buffer[self.ofs..],&mut Limiter::new(&self.limits)) {
+            Ok(l) => {
+                self.ofs += l;
+                self.left -= 1;
+                Ok(())
+            },
+            Err(error) => {
+                self.left = 0;
+                Err(error)
+            },
+        }
+    }
+}
+
+impl<'a,T: Codec> SeqIter<'a,T> {
+
+    /// Iterate over the `Vec<T>` or `HashSet<T>` encoded at the start of
+    /// `buffer`, within the default `DecodeLimits`.
+    pub fn new(buffer: &'a [u8]) -> Result<SeqIter<'a,T>> {
+        SeqIter::new_limited(buffer,&DecodeLimits::default())
+    }
+
+    /// Iterate over the `Vec<T>` or `HashSet<T>` encoded at the start of
+    /// `buffer`, decoding every element within `limits`.
+    pub fn new_limited(buffer: &'a [u8],limits: &DecodeLimits) -> Result<SeqIter<'a,T>> {
+        SeqIter::with_parts(buffer,limits,T::decode_with,T::skip_with)
+    }
+}
+
+impl<'a,K: Codec,V: Codec> SeqIter<'a,(K,V)> {
+
+    /// Iterate over the entries of the `HashMap<K,V>` encoded at the start
+    /// of `buffer`, within the default `DecodeLimits`.
+    pub fn entries(buffer: &'a [u8]) -> Result<SeqIter<'a,(K,V)>> {
+        SeqIter::entries_limited(buffer,&DecodeLimits::default())
+    }
+
+    /// Iterate over the entries of the `HashMap<K,V>` encoded at the start
+    /// of `buffer`, decoding every entry within `limits`.
+    pub fn entries_limited(buffer: &'a [u8],limits: &DecodeLimits) -> Result<SeqIter<'a,(K,V)>> {
+        SeqIter::with_parts(buffer,limits,decode_entry::<K,V>,skip_entry::<K,V>)
+    }
+}
+
+impl<'a,T> Iterator for SeqIter<'a,T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Result<T>> {
+        if self.left == 0 {
+            return None;
+        }
+        match (self.decode)(&self.buffer[self.ofs..],&mut Limiter::new(&self.limits)) {
+            Ok((l,item)) => {
+                self.ofs += l;
+                self.left -= 1;
+                Some(Ok(item))
+            },
+            Err(error) => {
+                self.left = 0;
+                Some(Err(error))
+            },
+        }
+    }
+
+    fn nth(&mut self,n: usize) -> Option<Result<T>> {
+        for _ in 0..n {
+            if self.left == 0 {
+                return None;
+            }
+            if let Err(error) = self.step() {
+                return Some(Err(error));
+            }
+        }
+        self.next()
+    }
+
+    fn size_hint(&self) -> (usize,Option<usize>) {
+        (self.left.min(1),Some(self.left))
+    }
+}
@@ -0,0 +1,16 @@
+// Echidna - Codec
+
+use crate::*;
+
+/// Write `entries` sorted by their encoded key, for canonical encoding of maps and sets.
+pub(crate) fn encode_sorted(mut entries: Vec<(Vec<u8>,Vec<u8>)>,buffer: &mut Vec<u8>) -> usize {
+    entries.sort_unstable_by(|a,b| a.0.cmp(&b.0));
+    (entries.len() as u32).encode(buffer);
+    let mut len = 4;
+    for (key,value) in entries {
+        buffer.extend_from_slice(&key);
+        buffer.extend_from_slice(&value);
+        len += key.len() + value.len();
+    }
+    len
+}
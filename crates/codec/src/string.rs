@@ -3,14 +3,43 @@
 use crate::*;
 
 impl Codec for String {
-    fn decode(buffer: &[u8]) -> Option<(usize,Self)> {
-        if let Some((_,len)) = u32::decode(buffer) {
-            let a = String::from_utf8_lossy(&buffer[4..4 + len as usize]);
-            Some((4 + len as usize,a.to_string()))
+    fn decode_with(buffer: &[u8],limiter: &mut Limiter) -> Result<(usize,Self)> {
+        let (_,len) = u32::decode_with(buffer,limiter)?;
+        let len = len as usize;
+        limiter.string(len)?;
+        if buffer.len() - 4 < len {
+            return Err(Error::Truncated);
         }
-        else {
-            None
+        let a = String::from_utf8_lossy(&buffer[4..4 + len]);
+        Ok((4 + len,a.to_string()))
+    }
+
+    fn decode_into_with(&mut self,buffer: &[u8],limiter: &mut Limiter) -> Result<usize> {
+        let (_,len) = u32::decode_with(buffer,limiter)?;
+        let len = len as usize;
+        limiter.string(len)?;
+        if buffer.len() - 4 < len {
+            return Err(Error::Truncated);
+        }
+        self.clear();
+        self.push_str(&String::from_utf8_lossy(&buffer[4..4 + len]));
+        Ok(4 + len)
+    }
+
+    fn skip_with(buffer: &[u8],limiter: &mut Limiter) -> Result<usize> {
+        let (_,len) = u32::decode_with(buffer,limiter)?;
+        let len = len as usize;
+        limiter.string(len)?;
+        if buffer.len() - 4 < len {
+            return Err(Error::Truncated);
         }
+        Ok(4 + len)
+    }
+
+    fn annotate_with(annotator: &mut Annotator,path: &str) -> Result<()> {
+        let len = annotator.length(path)?;
+        annotator.limiter().string(len)?;
+        annotator.leaf(&format!("{}[0..{}]",path,len),len)
     }
 
     fn encode(&self,buffer: &mut Vec<u8>) -> usize {
@@ -18,12 +47,16 @@ impl Codec for String {
         let len = slice.len() as u32;
         len.encode(buffer);
         buffer.extend_from_slice(slice);
-        len as usize
+        4 + slice.len()
+    }
+
+    fn encode_gather<'a>(&'a self,gather: &mut Gather<'a>) -> usize {
+        (self.len() as u32).encode(gather.buffer());
+        4 + gather.bytes(self.as_bytes())
     }
 
     fn size(&self) -> usize {
-        let slice = self.as_bytes();
-        slice.len()
+        4 + self.len()
     }
 }
 
@@ -37,7 +70,7 @@ mod tests {
         let source = "Hello, World!".to_string();
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
-        if let Some((_,target)) = String::decode(&buffer) {
+        if let Ok((_,target)) = String::decode(&buffer) {
             assert_eq!(source,target)
         }
         else {
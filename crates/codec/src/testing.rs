@@ -0,0 +1,411 @@
+// Echidna - Codec
+
+//! Property-based testing of `Codec` implementations.
+//!
+//! Derive `Random` next to `Codec`, and let `roundtrip_check`,
+//! `truncation_check` and `bitflip_check` try many random values:
+//!
+//! ```
+//! use codec::{Codec,testing::*};
+//!
+//! #[derive(Codec,Random,Debug,PartialEq)]
+//! struct Point {
+//!     x: f32,
+//!     y: f32,
+//!     label: String,
+//! }
+//!
+//! roundtrip_check::<Point>(100);
+//! truncation_check::<Point>(100);
+//! bitflip_check::<Point>(100);
+//! ```
+
+use {
+    crate::*,
+    std::{
+        borrow::Cow,
+        collections::{
+            HashMap,
+            HashSet,
+        },
+        fmt::Debug,
+        hash::Hash,
+        net::{
+            Ipv4Addr,
+            Ipv6Addr,
+            IpAddr,
+            SocketAddrV4,
+            SocketAddrV6,
+            SocketAddr,
+        },
+        panic,
+    },
+};
+
+pub use macros::Random;
+
+/// Small deterministic random number generator (SplitMix64).
+///
+/// Also keeps track of how deep the value being generated is nested, so
+/// collections of recursive types stay finite.
+pub struct Rng {
+    state: u64,
+    depth: usize,
+}
+
+impl Rng {
+
+    pub fn new(seed: u64) -> Rng {
+        Rng {
+            state: seed,
+            depth: 0,
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// Random number in `0..n`.
+    pub fn below(&mut self,n: u64) -> u64 {
+        self.next_u64() % n
+    }
+
+    /// Random length for a collection, shrinking as the nesting gets deeper.
+    pub fn gen_len(&mut self) -> usize {
+        if self.depth >= 4 {
+            0
+        }
+        else {
+            self.below(8 >> self.depth) as usize
+        }
+    }
+
+    /// Step into a nested value.
+    pub fn enter(&mut self) {
+        self.depth += 1;
+    }
+
+    /// Step out of a nested value again.
+    pub fn leave(&mut self) {
+        self.depth -= 1;
+    }
+}
+
+/// Types that can generate random instances of themselves.
+pub trait Random: Sized {
+    fn random(rng: &mut Rng) -> Self;
+}
+
+impl Random for bool {
+    fn random(rng: &mut Rng) -> Self {
+        rng.below(2) != 0
+    }
+}
+
+macro_rules! random_int {
+    ($($t:ty),*) => {
+        $(
+            impl Random for $t {
+                fn random(rng: &mut Rng) -> Self {
+                    rng.next_u64() as $t
+                }
+            }
+        )*
+    };
+}
+
+random_int!(u8,i8,u16,i16,u32,i32,u64,i64);
+
+// floats are random bit patterns, except NaN, which never compares equal to itself
+impl Random for f32 {
+    fn random(rng: &mut Rng) -> Self {
+        let value = f32::from_bits(rng.next_u64() as u32);
+        if value.is_nan() { 0.0 } else { value }
+    }
+}
+
+impl Random for f64 {
+    fn random(rng: &mut Rng) -> Self {
+        let value = f64::from_bits(rng.next_u64());
+        if value.is_nan() { 0.0 } else { value }
+    }
+}
+
+// halves are random representable values, so they survive the round trip
+impl Random for F16 {
+    fn random(rng: &mut Rng) -> Self {
+        F16(f16_to_f32(rng.next_u64() as u16 & 0xFBFF))
+    }
+}
+
+impl Random for BF16 {
+    fn random(rng: &mut Rng) -> Self {
+        BF16(bf16_to_f32(rng.next_u64() as u16 & 0xFF7F))
+    }
+}
+
+impl Random for Bitset {
+    fn random(rng: &mut Rng) -> Self {
+        Bitset(Vec::<bool>::random(rng))
+    }
+}
+
+// quantized values are random steps, so they survive the round trip
+macro_rules! random_quantized {
+    ($($t:ty),*) => {
+        $(
+            impl<const MIN: i64,const MAX: i64,const BITS: u32> Random for Quantized<$t,MIN,MAX,BITS> {
+                fn random(rng: &mut Rng) -> Self {
+                    let bytes = rng.next_u64().to_le_bytes();
+                    Self::decode(&bytes).unwrap().1
+                }
+            }
+        )*
+    };
+}
+
+random_quantized!(f32,f64);
+
+impl Random for String {
+    fn random(rng: &mut Rng) -> Self {
+        const CHARS: [char; 8] = ['a','Z','0',' ','é','ß','€','😀'];
+        let len = rng.gen_len();
+        let mut r = String::new();
+        for _ in 0..len {
+            r.push(CHARS[rng.below(CHARS.len() as u64) as usize]);
+        }
+        r
+    }
+}
+
+// `None` once nested deep enough, like empty collections
+impl<T: Random> Random for Option<T> {
+    fn random(rng: &mut Rng) -> Self {
+        if (rng.gen_len() == 0) || (rng.below(2) == 0) {
+            None
+        }
+        else {
+            rng.enter();
+            let value = T::random(rng);
+            rng.leave();
+            Some(value)
+        }
+    }
+}
+
+impl<T: Random> Random for Vec<T> {
+    fn random(rng: &mut Rng) -> Self {
+        let len = rng.gen_len();
+        rng.enter();
+        let mut r = Vec::<T>::with_capacity(len);
+        for _ in 0..len {
+            r.push(T::random(rng));
+        }
+        rng.leave();
+        r
+    }
+}
+
+impl<K: Random + Eq + Hash,V: Random> Random for HashMap<K,V> {
+    fn random(rng: &mut Rng) -> Self {
+        let len = rng.gen_len();
+        rng.enter();
+        let mut r = HashMap::<K,V>::new();
+        for _ in 0..len {
+            let key = K::random(rng);
+            let value = V::random(rng);
+            r.insert(key,value);
+        }
+        rng.leave();
+        r
+    }
+}
+
+impl<T: Random + Eq + Hash> Random for HashSet<T> {
+    fn random(rng: &mut Rng) -> Self {
+        let len = rng.gen_len();
+        rng.enter();
+        let mut r = HashSet::<T>::new();
+        for _ in 0..len {
+            r.insert(T::random(rng));
+        }
+        rng.leave();
+        r
+    }
+}
+
+impl<'a> Random for Cow<'a,str> {
+    fn random(rng: &mut Rng) -> Self {
+        Cow::Owned(String::random(rng))
+    }
+}
+
+impl<'a,T: Random + Clone> Random for Cow<'a,[T]> {
+    fn random(rng: &mut Rng) -> Self {
+        Cow::Owned(Vec::<T>::random(rng))
+    }
+}
+
+impl Random for Ipv4Addr {
+    fn random(rng: &mut Rng) -> Self {
+        Ipv4Addr::from(u32::random(rng))
+    }
+}
+
+impl Random for Ipv6Addr {
+    fn random(rng: &mut Rng) -> Self {
+        Ipv6Addr::from(((u64::random(rng) as u128) << 64) | (u64::random(rng) as u128))
+    }
+}
+
+impl Random for IpAddr {
+    fn random(rng: &mut Rng) -> Self {
+        if bool::random(rng) {
+            IpAddr::V6(Ipv6Addr::random(rng))
+        }
+        else {
+            IpAddr::V4(Ipv4Addr::random(rng))
+        }
+    }
+}
+
+impl Random for SocketAddrV4 {
+    fn random(rng: &mut Rng) -> Self {
+        SocketAddrV4::new(Ipv4Addr::random(rng),u16::random(rng))
+    }
+}
+
+// flow info and scope id are not encoded
+impl Random for SocketAddrV6 {
+    fn random(rng: &mut Rng) -> Self {
+        SocketAddrV6::new(Ipv6Addr::random(rng),u16::random(rng),0,0)
+    }
+}
+
+impl Random for SocketAddr {
+    fn random(rng: &mut Rng) -> Self {
+        if bool::random(rng) {
+            SocketAddr::V6(SocketAddrV6::random(rng))
+        }
+        else {
+            SocketAddr::V4(SocketAddrV4::random(rng))
+        }
+    }
+}
+
+impl<T: Random> Random for Checked<T> {
+    fn random(rng: &mut Rng) -> Self {
+        Checked(T::random(rng))
+    }
+}
+
+// long runs, so some values are large enough to be compressed
+impl<T: Random + Clone> Random for Compressed<Vec<T>> {
+    fn random(rng: &mut Rng) -> Self {
+        let mut value = Vec::<T>::random(rng);
+        if let Some(item) = value.first().cloned() {
+            let len = rng.below(500) as usize;
+            value.resize(len,item);
+        }
+        Compressed::new(value)
+    }
+}
+
+fn hex(buffer: &[u8]) -> String {
+    buffer.iter().map(|b| format!("{:02X}",b)).collect::<Vec<String>>().join(" ")
+}
+
+fn canonical<T: Codec>(value: &T) -> Vec<u8> {
+    let mut buffer = Vec::<u8>::new();
+    value.encode_canonical(&mut buffer);
+    buffer
+}
+
+// decode, turning a panic into a test failure that shows the buffer
+fn decode_unwinding<T: Codec>(buffer: &[u8]) -> Result<(usize,T)> {
+    match panic::catch_unwind(|| T::decode(buffer)) {
+        Ok(result) => result,
+        Err(_) => panic!("decode panicked on [{}]",hex(buffer)),
+    }
+}
+
+/// Encode and decode `iterations` random values of `T`, and check that
+/// they come back the same, and that `size()`, `skip()`, canonical encoding
+/// and `decode_into` agree.
+pub fn roundtrip_check<T: Codec + Random + PartialEq + Debug>(iterations: usize) {
+    let mut rng = Rng::new(0x45434849444E41);
+    for _ in 0..iterations {
+        let value = T::random(&mut rng);
+        let mut buffer = Vec::<u8>::new();
+        let len = value.encode(&mut buffer);
+        assert_eq!(len,buffer.len(),"encode() of {:?} returns wrong length",value);
+        assert_eq!(len,value.size(),"size() of {:?} disagrees with encode()",value);
+        assert_eq!(T::skip(&buffer),Ok(len),"skip() of {:?} disagrees with encode()",value);
+        let mut canonical = Vec::<u8>::new();
+        value.encode_canonical(&mut canonical);
+        assert_eq!(canonical.len(),len,"encode_canonical() of {:?} has wrong length",value);
+        assert_eq!(T::decode(&canonical),Ok((len,T::decode(&buffer).unwrap().1)),"canonical encoding of {:?} decodes differently",value);
+        let mut target = T::random(&mut rng);
+        assert_eq!(target.decode_into(&buffer),Ok(len),"decode_into() of {:?} failed",value);
+        assert_eq!(target,value,"decode_into() of {:?} differs",value);
+        assert_eq!(T::decode(&buffer),Ok((len,value)));
+    }
+}
+
+/// Check that every truncation of the encoding of `iterations` random
+/// values of `T` fails to decode with `Error::Truncated`, without panicking.
+pub fn truncation_check<T: Codec + Random + Debug>(iterations: usize) {
+    let mut rng = Rng::new(0x5452554E43);
+    for _ in 0..iterations {
+        let value = T::random(&mut rng);
+        let mut buffer = Vec::<u8>::new();
+        value.encode(&mut buffer);
+        for len in 0..buffer.len() {
+            match decode_unwinding::<T>(&buffer[..len]) {
+                Err(Error::Truncated) => { },
+                result => panic!("decoding {} of {} bytes of {:?} gave {:?} instead of Error::Truncated",len,buffer.len(),value,result.map(|(l,_)| l)),
+            }
+        }
+    }
+}
+
+/// Flip random bits in the encoding of `iterations` random values of `T`,
+/// and check that decoding never panics, and either fails or gives a value
+/// that encodes to the flipped bytes again.
+///
+/// Decoding accepts some bytes leniently and normalizes them: bools other
+/// than 0 and 1, invalid UTF-8, unused bits of packed fields and the order
+/// of map entries. If the value encodes differently for that reason, its
+/// encoding has to decode to the same value again.
+pub fn bitflip_check<T: Codec + Random>(iterations: usize) {
+    let mut rng = Rng::new(0x464C4950);
+    for _ in 0..iterations {
+        let value = T::random(&mut rng);
+        let mut buffer = Vec::<u8>::new();
+        value.encode(&mut buffer);
+        if buffer.is_empty() {
+            continue;
+        }
+        for _ in 0..8 {
+            let mut flipped = buffer.clone();
+            let bit = rng.below((flipped.len() * 8) as u64) as usize;
+            flipped[bit / 8] ^= 1 << (bit % 8);
+            if let Ok((len,value)) = decode_unwinding::<T>(&flipped) {
+                assert!(len <= flipped.len(),"decode of [{}] claims {} bytes",hex(&flipped),len);
+                let encoded = value.encode_to_vec();
+                assert_eq!(encoded.len(),value.size(),"size() of the decode of [{}] disagrees with encode()",hex(&flipped));
+                if encoded[..] != flipped[..len] {
+                    match T::decode(&encoded) {
+                        Ok((l,again)) if l == encoded.len() => assert_eq!(hex(&canonical(&again)),hex(&canonical(&value)),"decode of [{}] encodes to [{}], which decodes differently",hex(&flipped[..len]),hex(&encoded)),
+                        result => panic!("decode of [{}] encodes to [{}], which decodes to {:?}",hex(&flipped[..len]),hex(&encoded),result.map(|(l,_)| l)),
+                    }
+                }
+            }
+        }
+    }
+}
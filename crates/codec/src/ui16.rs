@@ -3,8 +3,13 @@
 use crate::*;
 
 impl Codec for u16 {
-    fn decode(buffer: &[u8]) -> Option<(usize,Self)> {
-        Some((2,
+    const FIXED_SIZE: Option<usize> = Some(2);
+
+    fn decode_with(buffer: &[u8],_: &mut Limiter) -> Result<(usize,Self)> {
+        if buffer.len() < 2 {
+            return Err(Error::Truncated);
+        }
+        Ok((2,
             (buffer[0] as u16) |
             ((buffer[1] as u16) << 8)
         ))
@@ -21,9 +26,18 @@ impl Codec for u16 {
     }
 }
 
+impl FixedSize for u16 {
+    const SIZE: usize = 2;
+}
+
 impl Codec for i16 {
-    fn decode(buffer: &[u8]) -> Option<(usize,Self)> {
-        Some((2,
+    const FIXED_SIZE: Option<usize> = Some(2);
+
+    fn decode_with(buffer: &[u8],_: &mut Limiter) -> Result<(usize,Self)> {
+        if buffer.len() < 2 {
+            return Err(Error::Truncated);
+        }
+        Ok((2,
             (
                 (buffer[0] as u16) |
                 ((buffer[1] as u16) << 8)
@@ -42,6 +56,10 @@ impl Codec for i16 {
     }
 }
 
+impl FixedSize for i16 {
+    const SIZE: usize = 2;
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -52,7 +70,7 @@ mod tests {
         let source: u16 = 16384;
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
-        if let Some((_,target)) = u16::decode(&buffer) {
+        if let Ok((_,target)) = u16::decode(&buffer) {
             assert_eq!(source,target)
         }
         else {
@@ -65,7 +83,7 @@ mod tests {
         let source: i16 = -4096;
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
-        if let Some((_,target)) = i16::decode(&buffer) {
+        if let Ok((_,target)) = i16::decode(&buffer) {
             assert_eq!(source,target)
         }
         else {
@@ -3,8 +3,13 @@
 use crate::*;
 
 impl Codec for u32 {
-    fn decode(buffer: &[u8]) -> Option<(usize,Self)> {
-        Some((4,
+    const FIXED_SIZE: Option<usize> = Some(4);
+
+    fn decode_with(buffer: &[u8],_: &mut Limiter) -> Result<(usize,Self)> {
+        if buffer.len() < 4 {
+            return Err(Error::Truncated);
+        }
+        Ok((4,
             (buffer[0] as u32) |
             ((buffer[1] as u32) << 8) |
             ((buffer[2] as u32) << 16) |
@@ -25,9 +30,18 @@ impl Codec for u32 {
     }
 }
 
+impl FixedSize for u32 {
+    const SIZE: usize = 4;
+}
+
 impl Codec for i32 {
-    fn decode(buffer: &[u8]) -> Option<(usize,Self)> {
-        Some((4,
+    const FIXED_SIZE: Option<usize> = Some(4);
+
+    fn decode_with(buffer: &[u8],_: &mut Limiter) -> Result<(usize,Self)> {
+        if buffer.len() < 4 {
+            return Err(Error::Truncated);
+        }
+        Ok((4,
             (
                 (buffer[0] as u32) |
                 ((buffer[1] as u32) << 8) |
@@ -50,6 +64,10 @@ impl Codec for i32 {
     }
 }
 
+impl FixedSize for i32 {
+    const SIZE: usize = 4;
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -60,7 +78,7 @@ mod tests {
         let source: u32 = 262144;
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
-        if let Some((_,target)) = u32::decode(&buffer) {
+        if let Ok((_,target)) = u32::decode(&buffer) {
             assert_eq!(source,target)
         }
         else {
@@ -73,7 +91,7 @@ mod tests {
         let source: i32 = -100000;
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
-        if let Some((_,target)) = i32::decode(&buffer) {
+        if let Ok((_,target)) = i32::decode(&buffer) {
             assert_eq!(source,target)
         }
         else {
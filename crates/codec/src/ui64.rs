@@ -3,8 +3,13 @@
 use crate::*;
 
 impl Codec for u64 {
-    fn decode(buffer: &[u8]) -> Option<(usize,Self)> {
-        Some((8,
+    const FIXED_SIZE: Option<usize> = Some(8);
+
+    fn decode_with(buffer: &[u8],_: &mut Limiter) -> Result<(usize,Self)> {
+        if buffer.len() < 8 {
+            return Err(Error::Truncated);
+        }
+        Ok((8,
             (buffer[0] as u64) |
             ((buffer[1] as u64) << 8) |
             ((buffer[2] as u64) << 16) |
@@ -33,9 +38,18 @@ impl Codec for u64 {
     }   
 }
 
+impl FixedSize for u64 {
+    const SIZE: usize = 8;
+}
+
 impl Codec for i64 {
-    fn decode(buffer: &[u8]) -> Option<(usize,Self)> {
-        Some((8,
+    const FIXED_SIZE: Option<usize> = Some(8);
+
+    fn decode_with(buffer: &[u8],_: &mut Limiter) -> Result<(usize,Self)> {
+        if buffer.len() < 8 {
+            return Err(Error::Truncated);
+        }
+        Ok((8,
             (
                 (buffer[0] as u64) |
                 ((buffer[1] as u64) << 8) |
@@ -66,6 +80,10 @@ impl Codec for i64 {
     }   
 }
 
+impl FixedSize for i64 {
+    const SIZE: usize = 8;
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -76,7 +94,7 @@ mod tests {
         let source: u64 = 9999999999;
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
-        if let Some((_,target)) = u64::decode(&buffer) {
+        if let Ok((_,target)) = u64::decode(&buffer) {
             assert_eq!(source,target)
         }
         else {
@@ -89,7 +107,7 @@ mod tests {
         let source: i64 = -1234567890;
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
-        if let Some((_,target)) = i64::decode(&buffer) {
+        if let Ok((_,target)) = i64::decode(&buffer) {
             assert_eq!(source,target)
         }
         else {
@@ -3,8 +3,13 @@
 use crate::*;
 
 impl Codec for u8 {
-    fn decode(buffer: &[u8]) -> Option<(usize,Self)> {
-        Some((1,buffer[0]))
+    const FIXED_SIZE: Option<usize> = Some(1);
+
+    fn decode_with(buffer: &[u8],_: &mut Limiter) -> Result<(usize,Self)> {
+        if buffer.is_empty() {
+            return Err(Error::Truncated);
+        }
+        Ok((1,buffer[0]))
     }
 
     fn encode(&self,buffer: &mut Vec<u8>) -> usize {
@@ -12,14 +17,27 @@ impl Codec for u8 {
         1
     }
 
+    fn slice_as_bytes(items: &[u8]) -> Option<&[u8]> {
+        Some(items)
+    }
+
     fn size(&self) -> usize {
         1
     }
 }
 
+impl FixedSize for u8 {
+    const SIZE: usize = 1;
+}
+
 impl Codec for i8 {
-    fn decode(buffer: &[u8]) -> Option<(usize,Self)> {
-        Some((1,buffer[0] as i8))
+    const FIXED_SIZE: Option<usize> = Some(1);
+
+    fn decode_with(buffer: &[u8],_: &mut Limiter) -> Result<(usize,Self)> {
+        if buffer.is_empty() {
+            return Err(Error::Truncated);
+        }
+        Ok((1,buffer[0] as i8))
     }
 
     fn encode(&self,buffer: &mut Vec<u8>) -> usize {
@@ -32,6 +50,10 @@ impl Codec for i8 {
     }
 }
 
+impl FixedSize for i8 {
+    const SIZE: usize = 1;
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -42,7 +64,7 @@ mod tests {
         let source: u8 = 127;
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
-        if let Some((_,target)) = u8::decode(&buffer) {
+        if let Ok((_,target)) = u8::decode(&buffer) {
             assert_eq!(source,target)
         }
         else {
@@ -55,7 +77,7 @@ mod tests {
         let source: i8 = -100;
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
-        if let Some((_,target)) = i8::decode(&buffer) {
+        if let Ok((_,target)) = i8::decode(&buffer) {
             assert_eq!(source,target)
         }
         else {
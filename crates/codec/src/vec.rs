@@ -1,26 +1,67 @@
 // Echidna - Codec
 
-use crate::*;
+use {
+    crate::*,
+    std::mem,
+};
 
 impl<T: Codec> Codec for Vec<T> {
-    fn decode(buffer: &[u8]) -> Option<(usize,Self)> {
-        if let Some((_,len)) = u32::decode(buffer) {
-            let mut r = Vec::<T>::new();
-            let mut ofs = 4usize;
-            for _ in 0..len {
-                if let Some((l,item)) = T::decode(&buffer[ofs..]) {
-                    ofs += l;
-                    r.push(item);    
-                }
-                else {
-                    return None;
-                }
-            }
-            Some((ofs,r))
+    fn decode_with(buffer: &[u8],limiter: &mut Limiter) -> Result<(usize,Self)> {
+        let (_,len) = u32::decode_with(buffer,limiter)?;
+        let len = len as usize;
+        limiter.collection(len,mem::size_of::<T>())?;
+        limiter.enter()?;
+        let mut r = Vec::<T>::with_capacity(capacity(len,buffer.len() - 4,T::FIXED_SIZE.unwrap_or(1)));
+        let mut ofs = 4usize;
+        for _ in 0..len {
+            let (l,item) = T::decode_with(&buffer[ofs..],limiter)?;
+            ofs += l;
+            r.push(item);
         }
-        else {
-            None
+        limiter.leave();
+        Ok((ofs,r))
+    }
+
+    fn decode_into_with(&mut self,buffer: &[u8],limiter: &mut Limiter) -> Result<usize> {
+        let (_,len) = u32::decode_with(buffer,limiter)?;
+        let len = len as usize;
+        limiter.collection(len,mem::size_of::<T>())?;
+        limiter.enter()?;
+        self.truncate(len);
+        let mut ofs = 4usize;
+        for item in self.iter_mut() {
+            ofs += item.decode_into_with(&buffer[ofs..],limiter)?;
+        }
+        self.reserve(capacity(len - self.len(),buffer.len() - ofs,T::FIXED_SIZE.unwrap_or(1)));
+        for _ in self.len()..len {
+            let (l,item) = T::decode_with(&buffer[ofs..],limiter)?;
+            ofs += l;
+            self.push(item);
+        }
+        limiter.leave();
+        Ok(ofs)
+    }
+
+    fn skip_with(buffer: &[u8],limiter: &mut Limiter) -> Result<usize> {
+        let (_,len) = u32::decode_with(buffer,limiter)?;
+        let len = len as usize;
+        limiter.collection(len,mem::size_of::<T>())?;
+        limiter.enter()?;
+        let mut ofs = 4usize;
+        for _ in 0..len {
+            ofs += T::skip_with(&buffer[ofs..],limiter)?;
         }
+        limiter.leave();
+        Ok(ofs)
+    }
+
+    fn annotate_with(annotator: &mut Annotator,path: &str) -> Result<()> {
+        let len = annotator.length(path)?;
+        annotator.limiter().collection(len,mem::size_of::<T>())?;
+        annotator.limiter().enter()?;
+        annotator.items(path,len,|annotator,path| T::annotate_with(annotator,path))?;
+        annotator.limiter().leave();
+        Ok(())
     }
 
     fn encode(&self,buffer: &mut Vec<u8>) -> usize {
@@ -32,6 +73,27 @@ impl<T: Codec> Codec for Vec<T> {
         len
     }
 
+    fn encode_canonical(&self,buffer: &mut Vec<u8>) -> usize {
+        (self.len() as u32).encode(buffer);
+        let mut len = 4;
+        for item in self {
+            len += item.encode_canonical(buffer);
+        }
+        len
+    }
+
+    fn encode_gather<'a>(&'a self,gather: &mut Gather<'a>) -> usize {
+        (self.len() as u32).encode(gather.buffer());
+        if let Some(bytes) = T::slice_as_bytes(self) {
+            return 4 + gather.bytes(bytes);
+        }
+        let mut len = 4;
+        for item in self {
+            len += item.encode_gather(gather);
+        }
+        len
+    }
+
     fn size(&self) -> usize {
         let mut len = 4;
         for item in self {
@@ -51,7 +113,7 @@ mod tests {
         let source: Vec<i32> = vec![-2,-1,0,1,2];
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
-        if let Some((_,target)) = Vec::<i32>::decode(&buffer) {
+        if let Ok((_,target)) = Vec::<i32>::decode(&buffer) {
             assert_eq!(source,target)
         }
         else {
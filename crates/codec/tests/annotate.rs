@@ -0,0 +1,83 @@
+// Echidna - Codec - tests
+
+use {
+    codec::*,
+    std::collections::HashMap,
+};
+
+#[derive(Codec)]
+#[codec(transparent)]
+struct MessageId(u64);
+
+#[derive(Codec)]
+struct Chunk {
+    ts: u64,
+    id: MessageId,
+    index: u32,
+    data: Vec<u8>,
+}
+
+#[derive(Codec)]
+enum PublisherToSubscriber {
+    Heartbeat(MessageId),
+    Chunk(Chunk),
+}
+
+#[derive(Codec)]
+struct Names {
+    names: Vec<String>,
+    ranks: HashMap<u16,bool>,
+}
+
+fn paths(buffer: &[u8]) -> Vec<(std::ops::Range<usize>,String)> {
+    let mut annotator = Annotator::new(buffer,&DecodeLimits::default());
+    assert_eq!(PublisherToSubscriber::annotate_with(&mut annotator,"PublisherToSubscriber"),Ok(()));
+    annotator.annotations().iter().map(|a| (a.range.clone(),a.path.clone())).collect()
+}
+
+#[test]
+fn test_annotate_enum() {
+    let buffer = PublisherToSubscriber::Chunk(Chunk { ts: 1, id: MessageId(2), index: 3, data: vec![4,5,6], }).encode_to_vec();
+    assert_eq!(paths(&buffer),vec![
+        (0x00..0x04,"PublisherToSubscriber::Chunk".to_string()),
+        (0x04..0x0C,"PublisherToSubscriber::Chunk.0.ts".to_string()),
+        (0x0C..0x14,"PublisherToSubscriber::Chunk.0.id".to_string()),
+        (0x14..0x18,"PublisherToSubscriber::Chunk.0.index".to_string()),
+        (0x18..0x1C,"PublisherToSubscriber::Chunk.0.data[len]".to_string()),
+        (0x1C..0x1F,"PublisherToSubscriber::Chunk.0.data[0..3]".to_string()),
+    ]);
+    let buffer = PublisherToSubscriber::Heartbeat(MessageId(7)).encode_to_vec();
+    assert_eq!(paths(&buffer),vec![
+        (0x00..0x04,"PublisherToSubscriber::Heartbeat".to_string()),
+        (0x04..0x0C,"PublisherToSubscriber::Heartbeat.0".to_string()),
+    ]);
+}
+
+#[test]
+fn test_annotate_collections() {
+    let mut ranks = HashMap::<u16,bool>::new();
+    ranks.insert(5,true);
+    let buffer = Names { names: vec!["ab".to_string(),"c".to_string()], ranks: ranks, }.encode_to_vec();
+    let text = annotate::<Names>(&buffer);
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(),8);
+    assert!(lines[0].starts_with("0x0000..0x0004  Names.names[len]"));
+    assert!(lines[1].starts_with("0x0004..0x0008  Names.names[0][len]"));
+    assert!(lines[2].starts_with("0x0008..0x000A  Names.names[0][0..2]"));
+    assert!(lines[2].ends_with(" 61 62"));
+    assert!(lines[6].starts_with("0x0013..0x0015  Names.ranks[0].key"));
+    assert!(lines[7].starts_with("0x0015..0x0016  Names.ranks[0].value"));
+}
+
+#[test]
+fn test_annotate_errors() {
+    let mut buffer = PublisherToSubscriber::Chunk(Chunk { ts: 1, id: MessageId(2), index: 3, data: vec![4,5,6], }).encode_to_vec();
+    let text = annotate::<PublisherToSubscriber>(&buffer[..0x1E]);
+    assert!(text.contains("0x001D..0x001E  PublisherToSubscriber::Chunk.0.data[1]"),"{}",text);
+    assert!(text.ends_with("0x001E  error: buffer truncated\n"),"{}",text);
+    buffer.push(0xFF);
+    let text = annotate::<PublisherToSubscriber>(&buffer);
+    assert!(text.ends_with("0x001F..0x0020  1 trailing bytes\n"),"{}",text);
+    let text = annotate::<PublisherToSubscriber>(&[9,0,0,0]);
+    assert!(text.starts_with("0x0000  error: "),"{}",text);
+}
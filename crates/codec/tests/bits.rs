@@ -0,0 +1,126 @@
+// Echidna - Codec - tests
+
+use codec::{
+    *,
+    delta::*,
+    testing::*,
+};
+
+#[derive(Codec,Random,Clone,Debug,PartialEq)]
+#[codec(view,delta)]
+struct Flags {
+    id: u32,
+    #[codec(bits = 1)]
+    valid: bool,
+    #[codec(bits = 1)]
+    stale: bool,
+    #[codec(bits = 3)]
+    mode: u8,
+    #[codec(bits = 4)]
+    offset: i8,
+    name: String,
+    #[codec(bits = 1)]
+    last: bool,
+    #[codec(bits)]
+    mask: Vec<bool>,
+}
+
+#[derive(Codec,Random,Clone,Debug,PartialEq)]
+struct Wide(#[codec(bits = 40)] u64,#[codec(bits = 30)] u32,#[codec(bits = 2)] u8);
+
+#[derive(Codec,Random,Clone,Debug,PartialEq)]
+enum Status {
+    Idle,
+    Busy {
+        #[codec(bits = 1)]
+        urgent: bool,
+        #[codec(bits = 7)]
+        level: u8,
+    },
+}
+
+#[test]
+fn test_bits() {
+    let flags = Flags { id: 1, valid: true, stale: false, mode: 5, offset: -3, name: "a".to_string(), last: true, mask: vec![true,false,true], };
+    let buffer = flags.encode_to_vec();
+    // valid, stale and mode fill 5 bits, offset spills into the second byte
+    assert_eq!(&buffer[4..6],&[0b1011_0101,0b1]);
+    assert_eq!(&buffer[11..],&[1,3,0,0,0,0b101]);
+    assert_eq!(flags.size(),17);
+    assert_eq!(Flags::decode(&buffer),Ok((17,flags.clone())));
+    let mut target = Flags { id: 0, valid: false, stale: true, mode: 0, offset: 0, name: String::new(), last: false, mask: vec![false; 10], };
+    assert_eq!(target.decode_into(&buffer),Ok(17));
+    assert_eq!(target,flags);
+    assert_eq!(Flags::skip(&buffer),Ok(17));
+    assert_eq!(Flags::decode(&buffer[..5]),Err(Error::Truncated));
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "value doesn't fit in 3 bits")]
+fn test_bits_out_of_range() {
+    let flags = Flags { id: 1, valid: true, stale: false, mode: 9, offset: 0, name: String::new(), last: true, mask: Vec::new(), };
+    flags.encode_to_vec();
+}
+
+#[test]
+#[cfg(not(debug_assertions))]
+fn test_bits_out_of_range() {
+    // out of range values are cut to their bits
+    let flags = Flags { id: 1, valid: true, stale: false, mode: 9, offset: 8, name: String::new(), last: true, mask: Vec::new(), };
+    let (_,decoded) = Flags::decode(&flags.encode_to_vec()).unwrap();
+    assert_eq!((decoded.mode,decoded.offset),(1,-8));
+}
+
+#[test]
+fn test_bits_size() {
+    assert_eq!(Wide::FIXED_SIZE,Some(5 + 4));
+    assert_eq!(<Wide as FixedSize>::SIZE,9);
+    let wide = Wide(0xFF_FFFF_FFFF,0x3FFF_FFFF,3);
+    assert_eq!(wide.encode_to_vec(),vec![0xFF; 9]);
+    assert_eq!(Wide::decode(&[0xFF; 9]),Ok((9,wide)));
+    assert_eq!(Status::FIXED_SIZE,None);
+    assert_eq!(Status::MAX_SIZE,Some(5));
+    let status = Status::Busy { urgent: true, level: 100, };
+    assert_eq!(status.encode_to_vec(),vec![1,0,0,0,(100 << 1) | 1]);
+    assert_eq!(status.size(),5);
+}
+
+#[test]
+fn test_bits_view() {
+    let flags = Flags { id: 7, valid: false, stale: true, mode: 2, offset: 5, name: "flags".to_string(), last: true, mask: vec![false,true], };
+    let buffer = flags.encode_to_vec();
+    let view = FlagsView::new(&buffer);
+    assert_eq!(view.stale(),Ok(true));
+    assert_eq!(view.mode(),Ok(2));
+    assert_eq!(view.offset(),Ok(5));
+    assert_eq!(view.name(),Ok("flags".to_string()));
+    assert_eq!(view.last(),Ok(true));
+    assert_eq!(view.mask(),Ok(vec![false,true]));
+}
+
+#[test]
+fn test_bits_annotate() {
+    let status = Status::Busy { urgent: false, level: 1, };
+    let text = annotate::<Status>(&status.encode_to_vec());
+    assert!(text.contains("Status::Busy.{urgent,level}"),"{}",text);
+}
+
+#[test]
+fn test_bits_delta() {
+    let reference = Flags { id: 1, valid: true, stale: false, mode: 5, offset: -3, name: "a".to_string(), last: true, mask: vec![true], };
+    let flags = Flags { mask: vec![false,false], ..reference.clone() };
+    let mut buffer = Vec::<u8>::new();
+    flags.encode_delta(&reference,&mut buffer);
+    assert_eq!(Flags::decode_delta(&reference,&buffer).map(|(_,f)| f),Ok(flags));
+}
+
+#[test]
+fn test_bits_conformance() {
+    roundtrip_check::<Flags>(500);
+    roundtrip_check::<Wide>(500);
+    roundtrip_check::<Status>(500);
+    roundtrip_check::<Bitset>(500);
+    truncation_check::<Flags>(200);
+    bitflip_check::<Flags>(200);
+}
@@ -28,7 +28,7 @@ fn test_bunch() {
 
     //dump(&buffer);
 
-    if let Some((_,target)) = PublisherId::decode(&buffer) {
+    if let Ok((_,target)) = PublisherId::decode(&buffer) {
         println!("{}.{}.{}.{}:{}",target.a,target.b,target.c,target.d,target.port);
         assert!(true);
     }
@@ -0,0 +1,60 @@
+// Echidna - Codec - tests
+
+use {
+    codec::*,
+    std::collections::HashMap,
+};
+
+#[derive(Codec,Debug,PartialEq)]
+enum Value {
+    Number(f64),
+    Text(String),
+    List(Vec<Value>),
+}
+
+#[derive(Codec,Debug,PartialEq)]
+struct Record {
+    id: u64,
+    fields: HashMap<String,Value>,
+}
+
+fn record(order: &[usize],nan: f64) -> Record {
+    let entries = [
+        ("a".to_string(),Value::Number(nan)),
+        ("b".to_string(),Value::Text("text".to_string())),
+        ("c".to_string(),Value::List(vec![Value::Number(1.0),Value::Number(nan)])),
+        ("d".to_string(),Value::Number(-0.5)),
+    ];
+    let mut fields = HashMap::<String,Value>::new();
+    for i in order {
+        let (key,value) = &entries[*i];
+        let value = match value {
+            Value::Number(n) => Value::Number(*n),
+            Value::Text(t) => Value::Text(t.clone()),
+            Value::List(l) => Value::List(l.iter().map(|v| if let Value::Number(n) = v { Value::Number(*n) } else { unreachable!() }).collect()),
+        };
+        fields.insert(key.clone(),value);
+    }
+    Record { id: 42, fields: fields, }
+}
+
+#[test]
+fn test_canonical() {
+    let a = record(&[0,1,2,3],f64::NAN);
+    let b = record(&[3,2,1,0],f64::from_bits(0xFFF8_0000_0000_0001));
+    let mut buffer_a = Vec::<u8>::new();
+    let mut buffer_b = Vec::<u8>::new();
+    let len = a.encode_canonical(&mut buffer_a);
+    b.encode_canonical(&mut buffer_b);
+    assert_eq!(len,buffer_a.len());
+    assert_eq!(len,a.size());
+    assert_eq!(buffer_a,buffer_b);
+    assert_eq!(content_hash(&a),content_hash(&b));
+    if let Ok((l,target)) = Record::decode(&buffer_a) {
+        assert_eq!(l,len);
+        assert_eq!(target.fields["b"],Value::Text("text".to_string()));
+    }
+    else {
+        assert!(false);
+    }
+}
@@ -0,0 +1,43 @@
+// Echidna - Codec - tests
+
+use codec::{
+    *,
+    testing::*,
+};
+
+#[derive(Codec,Random,Debug,PartialEq)]
+struct DepthMap {
+    width: u32,
+    height: u32,
+    depth: Compressed<Vec<u16>>,
+    label: String,
+}
+
+#[test]
+fn test_compressed_field() {
+    let mut map = DepthMap { width: 64, height: 64, depth: Compressed::new(vec![1200u16; 64 * 64]), label: "front".to_string(), };
+    let buffer = map.encode_to_vec();
+    assert!(buffer.len() < 1000);
+    assert!(map.depth.ratio() > 10.0);
+    assert_eq!(DepthMap::decode(&buffer),Ok((buffer.len(),DepthMap { width: 64, height: 64, depth: Compressed::new(vec![1200u16; 64 * 64]), label: "front".to_string(), })));
+    // changing the value through DerefMut compresses again
+    map.depth[0] = 7;
+    let buffer = map.encode_to_vec();
+    assert_eq!(DepthMap::decode(&buffer).unwrap().1.depth[0],7);
+}
+
+#[test]
+fn test_compressed_hostile() {
+    // claims 1 GiB of decompressed data
+    let buffer = [1,0,0,0,0x40,3,0,0,0,0xF0,0xFF,0xFF];
+    let limits = DecodeLimits { max_alloc: 1 << 20, ..DecodeLimits::default() };
+    assert_eq!(Compressed::<Vec<u8>>::decode_limited(&buffer,&limits),Err(Error::TooLarge(0x40000000)));
+    assert_eq!(Compressed::<Vec<u8>>::decode(&[2,0,0,0,0]),Err(Error::InvalidTag(2)));
+}
+
+#[test]
+fn test_compressed_roundtrip() {
+    roundtrip_check::<DepthMap>(200);
+    truncation_check::<DepthMap>(20);
+    bitflip_check::<DepthMap>(200);
+}
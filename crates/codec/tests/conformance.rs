@@ -0,0 +1,163 @@
+// Echidna - Codec - tests
+
+use {
+    codec::*,
+    std::{
+        borrow::Cow,
+        collections::{
+            HashMap,
+            HashSet,
+        },
+        fmt::Debug,
+        net::{
+            Ipv4Addr,
+            Ipv6Addr,
+            IpAddr,
+            SocketAddrV4,
+            SocketAddrV6,
+            SocketAddr,
+        },
+    },
+};
+
+// every way of encoding or measuring `value` has to agree
+fn check<T: Codec + PartialEq + Debug>(value: T) {
+    let mut buffer = vec![0xAA; 3];
+    let len = value.encode(&mut buffer);
+    assert_eq!(len,buffer.len() - 3,"encode() of {:?} returns wrong length",value);
+    assert_eq!(len,value.size(),"size() of {:?} disagrees with encode()",value);
+    let mut canonical = Vec::<u8>::new();
+    assert_eq!(value.encode_canonical(&mut canonical),len,"encode_canonical() of {:?} returns wrong length",value);
+    assert_eq!(canonical.len(),len);
+    assert_eq!(value.encode_to_vec(),&buffer[3..]);
+    if let Some(size) = T::FIXED_SIZE {
+        assert_eq!(len,size,"FIXED_SIZE of {:?} is wrong",value);
+    }
+    if let Some(size) = T::MAX_SIZE {
+        assert!(len <= size,"MAX_SIZE of {:?} is too small",value);
+    }
+    assert_eq!(T::skip(&buffer[3..]),Ok(len));
+    assert_eq!(T::decode(&buffer[3..]),Ok((len,value)));
+}
+
+#[derive(Codec,Debug,PartialEq)]
+struct Unit;
+
+#[derive(Codec,Debug,PartialEq)]
+struct Nothing { }
+
+#[derive(Codec,Debug,PartialEq)]
+struct Pair(u8,String);
+
+#[derive(Codec,Debug,PartialEq)]
+#[codec(transparent)]
+struct Name(String);
+
+#[derive(Codec,Debug,PartialEq)]
+struct Generic<T> {
+    items: Vec<T>,
+    count: u32,
+}
+
+#[derive(Codec,Debug,PartialEq)]
+struct Point {
+    x: f32,
+    y: f32,
+}
+
+#[derive(Codec,Debug,PartialEq)]
+enum Shape {
+    Empty,
+    Tuple(i16,Name,SocketAddr),
+    Named { label: String, points: Vec<Point>, },
+    Nested(Generic<Shape>),
+}
+
+#[derive(Codec,Debug,PartialEq)]
+struct Everything {
+    unit: Unit,
+    nothing: Nothing,
+    pair: Pair,
+    shapes: Vec<Shape>,
+    table: HashMap<String,Vec<u16>>,
+    address: IpAddr,
+}
+
+#[test]
+fn test_primitives() {
+    check(true);
+    check(0x12u8);
+    check(-0x12i8);
+    check(0x1234u16);
+    check(-0x1234i16);
+    check(0x12345678u32);
+    check(-0x12345678i32);
+    check(0x123456789ABCDEFu64);
+    check(-0x123456789ABCDEFi64);
+    check(1.5f32);
+    check(-1.5f64);
+}
+
+#[test]
+fn test_strings() {
+    check(String::new());
+    check("Hello, World!".to_string());
+    check("héllo wörld ✓".to_string());
+    check(Cow::<str>::Borrowed(""));
+    check(Cow::<str>::Owned("owned".to_string()));
+}
+
+#[test]
+fn test_collections() {
+    check(Vec::<u8>::new());
+    check(vec![1u32,2,3]);
+    check(vec!["a".to_string(),"bc".to_string()]);
+    check(vec![vec![1u8],vec![],vec![2,3]]);
+    check(Cow::<[u16]>::Borrowed(&[1,2,3]));
+    let mut map = HashMap::<String,Vec<u8>>::new();
+    map.insert("one".to_string(),vec![1]);
+    map.insert("two".to_string(),vec![2,2]);
+    check(map);
+    check(HashMap::<u8,u8>::new());
+    let set: HashSet<String> = vec!["x".to_string(),"yy".to_string()].into_iter().collect();
+    check(set);
+    check(Checked(vec!["a".to_string()]));
+    check(Checked(0x1234u16));
+    check(Some(vec![Some(1u8),None]));
+    check(None::<String>);
+}
+
+#[test]
+fn test_addrs() {
+    check(Ipv4Addr::new(192,168,1,2));
+    check(Ipv6Addr::new(1,2,3,4,5,6,7,8));
+    check(IpAddr::V4(Ipv4Addr::new(10,0,0,1)));
+    check(IpAddr::V6(Ipv6Addr::LOCALHOST));
+    check(SocketAddrV4::new(Ipv4Addr::new(10,0,0,1),7331));
+    check(SocketAddrV6::new(Ipv6Addr::LOCALHOST,7331,0,0));
+    check(SocketAddr::new(IpAddr::V4(Ipv4Addr::new(10,0,0,1)),7331));
+    check(SocketAddr::new(IpAddr::V6(Ipv6Addr::LOCALHOST),7331));
+}
+
+#[test]
+fn test_derived() {
+    check(Unit);
+    check(Nothing { });
+    check(Pair(1,"two".to_string()));
+    check(Name("name".to_string()));
+    check(Generic { items: vec![1u64,2], count: 2, });
+    check(Shape::Empty);
+    check(Shape::Tuple(-1,Name("tuple".to_string()),SocketAddr::new(IpAddr::V6(Ipv6Addr::LOCALHOST),1)));
+    check(Shape::Named { label: "named".to_string(), points: vec![Point { x: 0.0, y: 1.0, },Point { x: 2.0, y: 3.0, }], });
+    check(Shape::Nested(Generic { items: vec![Shape::Empty,Shape::Named { label: String::new(), points: Vec::new(), }], count: 2, }));
+    let mut table = HashMap::<String,Vec<u16>>::new();
+    table.insert("a".to_string(),vec![1,2,3]);
+    check(Everything {
+        unit: Unit,
+        nothing: Nothing { },
+        pair: Pair(7,"seven".to_string()),
+        shapes: vec![Shape::Empty,Shape::Tuple(3,Name(String::new()),SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST),2))],
+        table: table,
+        address: IpAddr::V4(Ipv4Addr::LOCALHOST),
+    });
+}
@@ -0,0 +1,87 @@
+// Echidna - Codec - tests
+
+use {
+    codec::*,
+    std::collections::HashMap,
+};
+
+#[derive(Codec,Debug,PartialEq)]
+struct DepthImage {
+    frame: u32,
+    label: String,
+    depth: Vec<u16>,
+    tags: HashMap<u8,String>,
+}
+
+#[derive(Codec,Debug,PartialEq)]
+enum Message {
+    Heartbeat(u64),
+    Image(DepthImage),
+}
+
+fn image(frame: u32) -> DepthImage {
+    let mut tags = HashMap::<u8,String>::new();
+    tags.insert(1,"near".to_string());
+    DepthImage { frame: frame, label: format!("frame {:04}",frame), depth: vec![frame as u16; 640 * 480], tags: tags, }
+}
+
+#[test]
+fn test_decode_into() {
+    let mut target = DepthImage { frame: 0, label: String::new(), depth: Vec::new(), tags: HashMap::new(), };
+    let mut depth_ptr = None;
+    let mut label_ptr = None;
+    for frame in 1..10 {
+        let source = image(frame);
+        let mut buffer = Vec::<u8>::new();
+        source.encode(&mut buffer);
+        assert_eq!(target.decode_into(&buffer),Ok(buffer.len()));
+        assert_eq!(target,source);
+        // after the first frame, the allocations are reused
+        if let (Some(depth_ptr),Some(label_ptr)) = (depth_ptr,label_ptr) {
+            assert_eq!(target.depth.as_ptr(),depth_ptr);
+            assert_eq!(target.label.as_ptr(),label_ptr);
+        }
+        depth_ptr = Some(target.depth.as_ptr());
+        label_ptr = Some(target.label.as_ptr());
+    }
+}
+
+#[test]
+fn test_decode_into_shrink() {
+    let mut target: Vec<String> = vec!["a".to_string(),"b".to_string(),"c".to_string()];
+    let source: Vec<String> = vec!["x".to_string()];
+    let mut buffer = Vec::<u8>::new();
+    source.encode(&mut buffer);
+    assert_eq!(target.decode_into(&buffer),Ok(buffer.len()));
+    assert_eq!(target,source);
+}
+
+#[test]
+fn test_decode_into_enum() {
+    let mut target = Message::Heartbeat(0);
+    for source in [Message::Image(image(1)),Message::Image(image(2)),Message::Heartbeat(7),Message::Image(image(3))] {
+        let mut buffer = Vec::<u8>::new();
+        source.encode(&mut buffer);
+        assert_eq!(target.decode_into(&buffer),Ok(buffer.len()));
+        assert_eq!(target,source);
+    }
+    assert_eq!(target.decode_into(&[2,0,0,0]),Err(Error::InvalidTag(2)));
+    assert_eq!(target.decode_into(&[1,0,0]),Err(Error::Truncated));
+}
+
+#[test]
+fn test_decode_into_hashmap() {
+    let mut target = HashMap::<u8,Vec<u16>>::new();
+    target.insert(1,vec![1; 1000]);
+    target.insert(2,vec![2; 1000]);
+    let ptr = target[&1].as_ptr();
+    let mut source = HashMap::<u8,Vec<u16>>::new();
+    source.insert(1,vec![3; 1000]);
+    source.insert(3,vec![4; 10]);
+    let mut buffer = Vec::<u8>::new();
+    source.encode(&mut buffer);
+    assert_eq!(target.decode_into(&buffer),Ok(buffer.len()));
+    assert_eq!(target,source);
+    // the value of the key that stayed is decoded in place
+    assert_eq!(target[&1].as_ptr(),ptr);
+}
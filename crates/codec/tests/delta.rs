@@ -0,0 +1,101 @@
+// Echidna - Codec - tests
+
+use codec::{
+    *,
+    delta::*,
+    testing::*,
+};
+
+#[derive(Codec,Random,Clone,Debug,PartialEq)]
+#[codec(delta)]
+struct Pose {
+    ts: u64,
+    x: f32,
+    y: f32,
+    z: f32,
+    frame: String,
+}
+
+#[derive(Codec,Random,Clone,Debug,PartialEq)]
+#[codec(delta)]
+struct JointState(u64,f32,f32,f32,f32,f32,f32,f32,f32,f32);
+
+#[derive(Codec,Clone,Debug,PartialEq)]
+#[codec(delta)]
+struct Empty;
+
+#[test]
+fn test_delta() {
+    let reference = Pose { ts: 1, x: 1.0, y: 2.0, z: 3.0, frame: "map".to_string(), };
+    let pose = Pose { ts: 2, y: 2.5, ..reference.clone() };
+    let mut buffer = Vec::<u8>::new();
+    assert_eq!(pose.encode_delta(&reference,&mut buffer),13);
+    assert_eq!(buffer[0],0b00101);
+    assert_eq!(Pose::decode_delta(&reference,&buffer),Ok((13,pose.clone())));
+    buffer.clear();
+    assert_eq!(reference.encode_delta(&reference,&mut buffer),1);
+    assert_eq!(Pose::decode_delta(&pose,&buffer),Ok((1,pose)));
+    let joints = JointState(1,0.0,0.0,0.0,0.0,0.0,0.0,0.0,0.0,0.5);
+    buffer.clear();
+    assert_eq!(joints.encode_delta(&JointState(0,0.0,0.0,0.0,0.0,0.0,0.0,0.0,0.0,0.0),&mut buffer),14);
+    assert_eq!(&buffer[..2],&[0b00000001,0b00000010]);
+    buffer.clear();
+    assert_eq!(Empty.encode_delta(&Empty,&mut buffer),0);
+    assert_eq!(Empty::decode_delta(&Empty,&buffer),Ok((0,Empty)));
+}
+
+#[test]
+fn test_delta_random() {
+    let mut rng = Rng::new(0x44454C5441);
+    for _ in 0..1000 {
+        let reference = JointState::random(&mut rng);
+        let mut joints = reference.clone();
+        if bool::random(&mut rng) {
+            joints.4 = f32::random(&mut rng);
+        }
+        if bool::random(&mut rng) {
+            joints.0 = u64::random(&mut rng);
+        }
+        let mut buffer = Vec::<u8>::new();
+        let len = joints.encode_delta(&reference,&mut buffer);
+        assert_eq!(len,buffer.len());
+        assert_eq!(JointState::skip_delta_with(&buffer,&mut Limiter::new(&DecodeLimits::default())),Ok(len));
+        assert_eq!(JointState::decode_delta(&reference,&buffer),Ok((len,joints)));
+        for l in 0..len {
+            assert_eq!(JointState::decode_delta(&reference,&buffer[..l]),Err(Error::Truncated));
+        }
+    }
+}
+
+#[test]
+fn test_delta_stream() {
+    let mut encoder = DeltaEncoder::<Pose>::new(4);
+    let mut decoder = DeltaDecoder::<Pose>::new();
+    let mut late = DeltaDecoder::<Pose>::new();
+    let mut pose = Pose { ts: 0, x: 0.0, y: 0.0, z: 0.0, frame: "map".to_string(), };
+    for i in 0..10 {
+        pose.ts = i;
+        pose.x = i as f32;
+        let mut buffer = Vec::<u8>::new();
+        let len = encoder.encode(&pose,&mut buffer);
+        assert_eq!(len,buffer.len());
+        // keyframes every 4 frames
+        assert_eq!(buffer[0],if i % 4 == 0 { 0 } else { 1 });
+        if i % 4 != 0 {
+            assert_eq!(len,5 + 1 + 8 + 4);
+        }
+        // one frame lost, the ones after it still decode
+        if i != 2 {
+            assert_eq!(decoder.decode(&buffer),Ok((len,Some(pose.clone()))));
+        }
+        // late joiner, only gets values after the next keyframe
+        if i >= 5 {
+            assert_eq!(late.decode(&buffer),Ok((len,if i >= 8 { Some(pose.clone()) } else { None })));
+        }
+    }
+    encoder.force_keyframe();
+    let mut buffer = Vec::<u8>::new();
+    encoder.encode(&pose,&mut buffer);
+    assert_eq!(buffer[0],0);
+    assert_eq!(decoder.decode(&[7,0,0,0,0]),Err(Error::InvalidTag(7)));
+}
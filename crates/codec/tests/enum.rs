@@ -3,9 +3,12 @@
 //mod dump;
 //use dump::*;
 
-use codec::Codec;
+use codec::{
+    Codec,
+    testing::*,
+};
 
-#[derive(Codec)]
+#[derive(Codec,Random,Debug,PartialEq)]
 enum MyEnum {
     One,
     Two,
@@ -32,7 +35,7 @@ fn test_enum() {
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
         //dump(&buffer);
-        if let Some((_,target)) = MyEnum::decode(&buffer) {
+        if let Ok((_,target)) = MyEnum::decode(&buffer) {
             dump_myenum(&target);
         }
         else {
@@ -45,7 +48,7 @@ fn test_enum() {
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
         //dump(&buffer);
-        if let Some((_,target)) = MyEnum::decode(&buffer) {
+        if let Ok((_,target)) = MyEnum::decode(&buffer) {
             dump_myenum(&target);
         }
         else {
@@ -58,7 +61,7 @@ fn test_enum() {
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
         //dump(&buffer);
-        if let Some((_,target)) = MyEnum::decode(&buffer) {
+        if let Ok((_,target)) = MyEnum::decode(&buffer) {
             dump_myenum(&target);
         }
         else {
@@ -71,7 +74,7 @@ fn test_enum() {
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
         //dump(&buffer);
-        if let Some((_,target)) = MyEnum::decode(&buffer) {
+        if let Ok((_,target)) = MyEnum::decode(&buffer) {
             dump_myenum(&target);
         }
         else {
@@ -84,7 +87,7 @@ fn test_enum() {
         let mut buffer = Vec::<u8>::new();
         source.encode(&mut buffer);
         //dump(&buffer);
-        if let Some((_,target)) = MyEnum::decode(&buffer) {
+        if let Ok((_,target)) = MyEnum::decode(&buffer) {
             dump_myenum(&target);
         }
         else {
@@ -94,3 +97,10 @@ fn test_enum() {
 
     assert!(true);
 }
+
+#[test]
+fn test_enum_roundtrip() {
+    roundtrip_check::<MyEnum>(100);
+    truncation_check::<MyEnum>(20);
+    bitflip_check::<MyEnum>(100);
+}
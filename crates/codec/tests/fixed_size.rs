@@ -0,0 +1,130 @@
+// Echidna - Codec - tests
+
+use {
+    codec::*,
+    std::{
+        marker::PhantomData,
+        net::{
+            Ipv4Addr,
+            SocketAddr,
+        },
+    },
+};
+
+#[derive(Codec,Clone,Copy,Debug,PartialEq)]
+#[codec(transparent)]
+struct MessageId(u64);
+
+#[derive(Codec,Debug,PartialEq)]
+#[codec(fixed_size)]
+struct Ack {
+    id: MessageId,
+    index: u32,
+    last: bool,
+}
+
+#[derive(Codec,Debug,PartialEq)]
+enum Control {
+    Ack(MessageId,u32),
+    NAck(MessageId,u32,u32),
+    Reset,
+}
+
+#[derive(Codec,Debug,PartialEq)]
+enum Direction {
+    Up,
+    Down,
+}
+
+#[derive(Codec,Debug,PartialEq)]
+struct Announce {
+    address: SocketAddr,
+    control: Control,
+}
+
+#[derive(Codec,Debug,PartialEq)]
+struct Named {
+    id: MessageId,
+    name: String,
+}
+
+#[derive(Codec,Debug,PartialEq)]
+enum Sample {
+    Mono(i16),
+    Stereo(#[codec(bits = 8)] i8,#[codec(bits = 8)] i8),
+}
+
+#[derive(Codec,Debug,PartialEq)]
+#[codec(fixed_size)]
+enum Event {
+    Ack(Ack),
+    Move { id: MessageId, dx: i16, dy: i16, last: bool, },
+}
+
+// a type that shadows a primitive by name still gets its own size
+mod shadow {
+
+    use codec::*;
+
+    #[allow(non_camel_case_types)]
+    #[derive(Codec,Debug,PartialEq)]
+    pub struct u8(pub u16);
+
+    #[derive(Codec,Debug,PartialEq)]
+    pub struct Level {
+        pub value: u8,
+    }
+}
+
+// `Probe::<T>::FIXED` is the inherent `true` if `T: FixedSize`, and falls
+// back to the trait's `false` otherwise
+trait NotFixedSize {
+    const FIXED: bool = false;
+}
+
+impl<T> NotFixedSize for Probe<T> { }
+
+struct Probe<T>(PhantomData<T>);
+
+impl<T: FixedSize> Probe<T> {
+    const FIXED: bool = true;
+}
+
+// checked at compile time
+const _: () = assert!(Ack::SIZE == 13);
+const _: () = assert!(Direction::SIZE == 4);
+const _: () = assert!(Sample::SIZE == 6);
+const _: () = assert!(Event::SIZE == 17);
+const _: () = assert!(shadow::Level::SIZE == 2);
+
+// variants of different sizes have a maximum size, but no `FixedSize`
+const _: () = assert!(Probe::<Ack>::FIXED && Probe::<Sample>::FIXED);
+const _: () = assert!(!Probe::<Control>::FIXED && !Probe::<Announce>::FIXED && !Probe::<Named>::FIXED);
+
+fn stack_size<T: FixedSize>() -> usize {
+    T::SIZE
+}
+
+#[test]
+fn test_fixed_size() {
+    assert_eq!(stack_size::<MessageId>(),8);
+    assert_eq!(stack_size::<Ack>(),13);
+    assert_eq!(stack_size::<Direction>(),4);
+    let source = Ack { id: MessageId(7), index: 3, last: true, };
+    let mut buffer = Vec::<u8>::with_capacity(Ack::SIZE);
+    assert_eq!(source.encode(&mut buffer),Ack::SIZE);
+    assert_eq!(source.size(),Ack::SIZE);
+    assert_eq!(<Control as Codec>::FIXED_SIZE,None);
+    assert_eq!(<Named as Codec>::FIXED_SIZE,None);
+}
+
+#[test]
+fn test_max_size() {
+    assert_eq!(<Ack as Codec>::MAX_SIZE,Some(13));
+    assert_eq!(<Control as Codec>::MAX_SIZE,Some(20));
+    assert_eq!(<Announce as Codec>::MAX_SIZE,Some(39));
+    assert_eq!(<Named as Codec>::MAX_SIZE,None);
+    let source = Announce { address: SocketAddr::new(Ipv4Addr::new(10,0,0,1).into(),7331), control: Control::Reset, };
+    let mut buffer = Vec::<u8>::new();
+    assert!(source.encode(&mut buffer) <= <Announce as Codec>::MAX_SIZE.unwrap());
+}
@@ -0,0 +1,113 @@
+// Echidna - Codec - tests
+
+use {
+    codec::*,
+    std::borrow::Cow,
+};
+
+#[derive(Codec,Clone,Debug,PartialEq)]
+struct Image {
+    ts: u64,
+    #[codec(bits = 4)]
+    channels: u8,
+    #[codec(bits = 4)]
+    depth: u8,
+    #[codec(quantize(f16))]
+    exposure: f32,
+    #[codec(bits)]
+    mask: Vec<bool>,
+    name: String,
+    data: Checked<Vec<u8>>,
+    thumbnail: Option<Vec<u8>>,
+}
+
+#[derive(Codec)]
+enum Frame<'a> {
+    Empty,
+    Raw(Cow<'a,[u8]>),
+    Text { text: Cow<'a,str>, },
+}
+
+fn image(size: usize) -> Image {
+    Image {
+        ts: 7,
+        channels: 3,
+        depth: 8,
+        exposure: 0.5,
+        mask: vec![true,false,true],
+        name: "front".to_string(),
+        data: Checked((0..size).map(|i| i as u8).collect()),
+        thumbnail: Some(vec![1; size / 4]),
+    }
+}
+
+#[test]
+fn test_gather() {
+    let image = image(10000);
+    let mut gather = Gather::new();
+    assert_eq!(image.encode_gather(&mut gather),image.size());
+    assert_eq!(gather.len(),image.size());
+    assert_eq!(gather.to_vec(),image.encode_to_vec());
+
+    // both byte vectors are referenced where they are, the rest goes in between
+    let slices = gather.slices();
+    assert_eq!(slices.len(),4);
+    assert_eq!(slices[1].as_ptr(),image.data.as_ptr());
+    assert_eq!(slices[3].as_ptr(),image.thumbnail.as_ref().unwrap().as_ptr());
+    assert_eq!(slices.iter().map(|slice| slice.len()).sum::<usize>(),image.size());
+    assert_eq!(Image::decode(&gather.to_vec()),Ok((image.size(),image)));
+}
+
+#[test]
+fn test_threshold() {
+    // small byte vectors are copied
+    let image = image(100);
+    let mut gather = Gather::new();
+    image.encode_gather(&mut gather);
+    assert_eq!(gather.slices().len(),1);
+    assert_eq!(gather.to_vec(),image.encode_to_vec());
+    let mut gather = Gather::with_threshold(1);
+    image.encode_gather(&mut gather);
+    assert_eq!(gather.slices().len(),6);
+    assert_eq!(gather.to_vec(),image.encode_to_vec());
+    assert_eq!(gather.parts_from(gather.len() - 30).concat(),image.encode_to_vec()[gather.len() - 30..].to_vec());
+}
+
+#[test]
+fn test_borrowed() {
+    let data = vec![9u8; 1000];
+    let text = "x".repeat(300);
+    let frames = [Frame::Empty,Frame::Raw(Cow::Borrowed(&data)),Frame::Text { text: Cow::Borrowed(&text), }];
+    let mut gather = Gather::new();
+    for frame in &frames {
+        frame.encode_gather(&mut gather);
+    }
+    let slices = gather.slices();
+    assert_eq!(slices.len(),4);
+    assert_eq!(slices[1].as_ptr(),data.as_ptr());
+    assert_eq!(slices[3].as_ptr(),text.as_ptr());
+    let mut buffer = Vec::<u8>::new();
+    for frame in &frames {
+        frame.encode(&mut buffer);
+    }
+    assert_eq!(gather.to_vec(),buffer);
+    assert!(Gather::new().is_empty());
+    assert_eq!(Gather::new().slices().len(),0);
+}
+
+#[test]
+fn test_parts() {
+    let image = image(10000);
+    let mut gather = Gather::new();
+    image.encode_gather(&mut gather);
+    let parts = gather.parts();
+    assert_eq!(parts.len(),4);
+    assert_eq!(parts[1],GatherPart::Slice(&image.data));
+    assert_eq!(parts[3],GatherPart::Slice(image.thumbnail.as_ref().unwrap()));
+    // the scratch ranges follow each other
+    match (parts[0],parts[2]) {
+        (GatherPart::Buffer(0,a),GatherPart::Buffer(b,c)) => assert_eq!((a,c),(b,gather.scratch().len())),
+        _ => panic!("expected scratch ranges"),
+    }
+    assert_eq!(Gather::new().parts(),Vec::<GatherPart>::new());
+}
@@ -0,0 +1,86 @@
+// Echidna - Codec - tests
+
+// the derived code must not pick up any of these
+#[allow(dead_code)]
+struct Codec;
+
+#[allow(dead_code,non_camel_case_types)]
+struct u32;
+
+#[allow(dead_code)]
+enum Option {
+    Some,
+    None,
+}
+
+#[allow(dead_code)]
+enum Result {
+    Ok,
+    Err,
+}
+
+mod wire {
+    pub use codec::*;
+}
+
+use wire::Codec as _;
+
+#[derive(wire::Codec,Debug,PartialEq)]
+struct Shadowed {
+    id: ::core::primitive::u32,
+    name: String,
+}
+
+#[derive(wire::Codec,wire::Message,Debug,PartialEq)]
+#[codec(crate = "crate::wire",schema,topic = "/reexported")]
+enum Reexported {
+    Empty,
+    Pair(::core::primitive::u32,f32),
+    Named { b: u16, ofs: u8, },
+}
+
+// raw identifiers can't be glued into generated names
+#[derive(wire::Codec,Debug,PartialEq)]
+#[codec(crate = "crate::wire",json,view,schema)]
+struct Raw {
+    r#type: u8,
+    r#match: String,
+}
+
+#[test]
+fn test_shadowed() {
+    let source = Shadowed { id: 42, name: "shadow".to_string(), };
+    let mut buffer = Vec::<u8>::new();
+    source.encode(&mut buffer);
+    if let ::core::result::Result::Ok((_,target)) = Shadowed::decode(&buffer) {
+        assert_eq!(source,target);
+    }
+    else {
+        assert!(false);
+    }
+}
+
+#[test]
+fn test_reexported() {
+    for source in [Reexported::Empty,Reexported::Pair(7,-1.5),Reexported::Named { b: 1000, ofs: 3, }] {
+        let mut buffer = Vec::<u8>::new();
+        source.encode(&mut buffer);
+        if let ::core::result::Result::Ok((_,target)) = Reexported::decode(&buffer) {
+            assert_eq!(source,target);
+        }
+        else {
+            assert!(false);
+        }
+    }
+}
+
+#[test]
+fn test_raw_identifiers() {
+    let source = Raw { r#type: 3, r#match: "all".to_string(), };
+    let buffer = wire::Codec::encode_to_vec(&source);
+    assert_eq!(<Raw as wire::Codec>::decode(&buffer),::core::result::Result::Ok((buffer.len(),source)));
+    assert_eq!(RawView::new(&buffer).r#match(),::core::result::Result::Ok("all".to_string()));
+    let json = wire::json::to_json_string(&<Raw as wire::Codec>::decode(&buffer).unwrap().1);
+    assert_eq!(json,"{\"type\":3,\"match\":\"all\"}");
+    assert!(wire::annotate::<Raw>(&buffer).contains("Raw.type"));
+}
@@ -0,0 +1,149 @@
+// Echidna - Codec - tests
+
+use {
+    codec::*,
+    std::{
+        borrow::Cow,
+        collections::HashMap,
+        net::SocketAddr,
+    },
+};
+
+#[derive(Codec,Clone,Debug,PartialEq)]
+#[codec(schema)]
+struct Frame {
+    seq: u32,
+    name: String,
+    samples: Vec<i16>,
+    tags: HashMap<String,u8>,
+    previous: Option<Shape>,
+    shape: Shape,
+}
+
+// none of these have a schema
+#[derive(Codec,Clone,Debug,PartialEq)]
+struct Packet {
+    from: SocketAddr,
+    payload: Checked<Vec<u8>>,
+    #[codec(bits = 4)]
+    level: u8,
+    #[codec(bits = 4)]
+    mode: u8,
+    mask: Bitset,
+    gain: F16,
+    label: Cow<'static,str>,
+}
+
+#[derive(Codec,Clone,Debug,PartialEq)]
+#[codec(schema)]
+enum Shape {
+    Empty,
+    Circle(f32),
+    Rect { w: f32, h: f32, },
+}
+
+fn frame(seq: u32) -> Frame {
+    let mut tags = HashMap::<String,u8>::new();
+    tags.insert("lidar".to_string(),1);
+    Frame {
+        seq: seq,
+        name: "front".to_string(),
+        samples: vec![-1,0,1],
+        tags: tags,
+        previous: Some(Shape::Circle(0.5)),
+        shape: Shape::Rect { w: 1.0, h: 2.0, },
+    }
+}
+
+#[test]
+fn test_byte_by_byte() {
+    let buffer = frame(1).encode_to_vec();
+    let mut decoder = IncrementalDecoder::<Frame>::new();
+    for b in &buffer[..buffer.len() - 1] {
+        match decoder.feed(&[*b]) {
+            Ok(Progress::NeedMore(n)) => assert!(n >= 1),
+            result => panic!("{:?}",result),
+        }
+    }
+    assert_eq!(decoder.buffered(),buffer.len() - 1);
+    assert_eq!(decoder.feed(&buffer[buffer.len() - 1..]),Ok(Progress::Done(frame(1),1)));
+    assert_eq!(decoder.buffered(),0);
+}
+
+#[test]
+fn test_need_more() {
+    // a fixed size type knows how many bytes are missing, others need at least one more
+    let mut decoder = IncrementalDecoder::<u64>::new();
+    assert_eq!(decoder.feed(&[]),Ok(Progress::NeedMore(8)));
+    assert_eq!(decoder.feed(&[1,0,0]),Ok(Progress::NeedMore(5)));
+    assert_eq!(decoder.feed(&[0,0,0,0,0]),Ok(Progress::Done(1,5)));
+    let buffer = frame(1).encode_to_vec();
+    let mut decoder = IncrementalDecoder::<Frame>::new();
+    assert_eq!(decoder.feed(&[]),Ok(Progress::NeedMore(1)));
+    assert_eq!(decoder.feed(&buffer[..10]),Ok(Progress::NeedMore(1)));
+    assert_eq!(decoder.feed(&buffer[10..]),Ok(Progress::Done(frame(1),buffer.len() - 10)));
+}
+
+#[test]
+fn test_any_codec() {
+    let packet = Packet {
+        from: "10.0.0.1:7332".parse().unwrap(),
+        payload: Checked(vec![1,2,3]),
+        level: 5,
+        mode: 2,
+        mask: Bitset(vec![true,false,true]),
+        gain: F16(0.5),
+        label: Cow::Borrowed("front"),
+    };
+    let buffer = packet.encode_to_vec();
+    let mut decoder = IncrementalDecoder::<Packet>::new();
+    for b in &buffer[..buffer.len() - 1] {
+        assert_eq!(decoder.feed(&[*b]),Ok(Progress::NeedMore(1)));
+    }
+    assert_eq!(decoder.feed(&buffer[buffer.len() - 1..]),Ok(Progress::Done(packet,1)));
+}
+
+#[test]
+fn test_stream() {
+    let mut stream = Vec::<u8>::new();
+    for seq in 0..10 {
+        frame(seq).encode(&mut stream);
+    }
+    for chunk in [1,3,7,64,stream.len()] {
+        let mut decoder = IncrementalDecoder::<Frame>::new();
+        let mut frames = Vec::<Frame>::new();
+        for mut data in stream.chunks(chunk) {
+            while !data.is_empty() {
+                match decoder.feed(data).unwrap() {
+                    Progress::NeedMore(_) => break,
+                    Progress::Done(frame,consumed) => {
+                        frames.push(frame);
+                        data = &data[consumed..];
+                    },
+                }
+            }
+        }
+        assert_eq!(frames,(0..10).map(frame).collect::<Vec<Frame>>());
+    }
+    let mut decoder = IncrementalDecoder::<u64>::new();
+    assert_eq!(decoder.feed(&[1,0,0,0,0,0,0,0,2]),Ok(Progress::Done(1,8)));
+}
+
+#[test]
+fn test_errors() {
+    // the wrong tag fails as soon as it arrives, and the decoder starts over
+    let mut buffer = frame(1).encode_to_vec();
+    let tag = buffer.len() - 12;
+    buffer[tag] = 3;
+    let mut decoder = IncrementalDecoder::<Frame>::new();
+    assert_eq!(decoder.feed(&buffer[..tag + 4]),Err(Error::InvalidTag(3)));
+    assert_eq!(decoder.buffered(),0);
+    assert_eq!(decoder.feed(&frame(2).encode_to_vec()),Ok(Progress::Done(frame(2),buffer.len())));
+
+    // so does a length beyond the limits, without waiting for the bytes
+    let limits = DecodeLimits { max_string_bytes: 16, ..DecodeLimits::default() };
+    let mut decoder = IncrementalDecoder::<Frame>::with_limits(&limits);
+    assert_eq!(decoder.feed(&[0,0,0,0,17,0,0,0]),Err(Error::TooLong(17)));
+    let mut decoder = IncrementalDecoder::<Option<u8>>::new();
+    assert_eq!(decoder.feed(&[2]),Err(Error::InvalidTag(2)));
+}
@@ -0,0 +1,128 @@
+// Echidna - Codec - tests
+
+use {
+    codec::{
+        Codec,
+        DecodeLimits,
+        json::*,
+        testing::*,
+    },
+    std::{
+        collections::HashMap,
+        net::SocketAddr,
+    },
+};
+
+#[derive(Codec,Random,Debug,PartialEq)]
+#[codec(json,transparent)]
+struct TopicId(u64);
+
+#[derive(Codec,Random,Debug,PartialEq)]
+#[codec(json)]
+struct Marker;
+
+#[derive(Codec,Random,Debug,PartialEq)]
+#[codec(json)]
+enum Command {
+    Stop,
+    Seek(u64),
+    Move(f32,f32),
+    Publish { topic: TopicId, data: Vec<u8>, },
+}
+
+#[derive(Codec,Random,Debug,PartialEq)]
+#[codec(json)]
+struct Config {
+    name: String,
+    peers: Vec<SocketAddr>,
+    topics: HashMap<String,TopicId>,
+    ranks: HashMap<u16,bool>,
+    marker: Marker,
+    commands: Vec<Command>,
+}
+
+#[derive(Codec,Debug,PartialEq)]
+#[codec(json)]
+struct Wrapper<T> {
+    inner: T,
+}
+
+#[test]
+fn test_json_mapping() {
+    let command = Command::Publish { topic: TopicId(7), data: b"hello".to_vec(), };
+    assert_eq!(to_json_string(&command),r#"{"Publish":{"topic":7,"data":"aGVsbG8="}}"#);
+    assert_eq!(to_json_string(&Command::Stop),r#""Stop""#);
+    assert_eq!(to_json_string(&Command::Seek(3)),r#"{"Seek":3}"#);
+    assert_eq!(to_json_string(&Command::Move(1.0,-0.5)),r#"{"Move":[1.0,-0.5]}"#);
+    assert_eq!(to_json_string(&Marker),"null");
+    assert_eq!(to_json_string(&"10.0.0.1:7331".parse::<SocketAddr>().unwrap()),r#""10.0.0.1:7331""#);
+    assert_eq!(to_json_string(&Wrapper { inner: vec![1u32,2], }),r#"{"inner":[1,2]}"#);
+    let mut ranks = HashMap::<u16,bool>::new();
+    ranks.insert(3,true);
+    assert_eq!(to_json_string(&ranks),r#"{"3":true}"#);
+}
+
+#[test]
+fn test_json_parse() {
+    let text = r#"
+        {
+            "name": "node",
+            "peers": [ "10.0.0.1:7331", "[::1]:80" ],
+            "topics": { "camera": 1, "lidar": 2 },
+            "ranks": { "5": false },
+            "marker": null,
+            "commands": [ "Stop", { "Stop": null }, { "Seek": 12 }, { "Publish": { "data": [1,2,3], "topic": 9 } } ]
+        }
+    "#;
+    let config: Config = from_json_str(text).unwrap();
+    assert_eq!(config.name,"node");
+    assert_eq!(config.peers[1],"[::1]:80".parse::<SocketAddr>().unwrap());
+    assert_eq!(config.topics["lidar"],TopicId(2));
+    assert!(!config.ranks[&5]);
+    assert_eq!(config.commands,vec![Command::Stop,Command::Stop,Command::Seek(12),Command::Publish { topic: TopicId(9), data: vec![1,2,3], }]);
+}
+
+#[test]
+fn test_json_errors() {
+    assert_eq!(from_json_str::<Command>(r#""Jump""#),Err(JsonError::UnknownVariant("Jump".to_string())));
+    assert_eq!(from_json_str::<Wrapper<u8>>(r#"{}"#),Err(JsonError::MissingField("inner".to_string())));
+    assert_eq!(from_json_str::<Wrapper<u8>>(r#"{"inner":300}"#),Err(JsonError::Expected("u8".to_string())));
+    assert!(matches!(from_json_str::<Marker>("nul"),Err(JsonError::Syntax { line: 1, .. })));
+}
+
+fn syntax_error(result: Result<Json,JsonError>) -> String {
+    match result {
+        Err(JsonError::Syntax { message,.. }) => message,
+        result => panic!("syntax error expected instead of {:?}",result),
+    }
+}
+
+#[test]
+fn test_json_nesting() {
+    // too deep fails instead of overflowing the stack
+    assert_eq!(syntax_error(Json::parse(&"[".repeat(1000000))),"nested too deeply");
+    assert_eq!(syntax_error(Json::parse(&r#"{"a":"#.repeat(1000000))),"nested too deeply");
+    let limits = DecodeLimits { max_depth: 3, ..DecodeLimits::default() };
+    assert!(Json::parse_limited(r#"[{"a":[1]},[2]]"#,&limits).is_ok());
+    assert_eq!(syntax_error(Json::parse_limited("[[[[1]]]]",&limits)),"nested too deeply");
+}
+
+#[test]
+fn test_json_surrogates() {
+    assert_eq!(Json::parse(r#""\ud83d\ude00""#),Ok(Json::String("😀".to_string())));
+    assert_eq!(syntax_error(Json::parse(r#""\ud83d\u0041""#)),"low surrogate expected");
+    assert_eq!(syntax_error(Json::parse(r#""\ud83d\ud83d""#)),"low surrogate expected");
+    assert_eq!(syntax_error(Json::parse(r#""\ud83d""#)),"low surrogate expected");
+    assert_eq!(syntax_error(Json::parse(r#""\ude00""#)),"invalid unicode escape");
+}
+
+#[test]
+fn test_json_roundtrip() {
+    let mut rng = Rng::new(0x4A534F4E);
+    for _ in 0..200 {
+        let config = Config::random(&mut rng);
+        assert_eq!(from_json_str::<Config>(&to_json_string(&config)),Ok(config));
+        let command = Command::random(&mut rng);
+        assert_eq!(from_json_str::<Command>(&command.to_json().pretty()),Ok(command));
+    }
+}
@@ -0,0 +1,57 @@
+// Echidna - Codec - tests
+
+use {
+    codec::Codec,
+    std::borrow::Cow,
+};
+
+#[derive(Codec,Debug,PartialEq)]
+struct Msg<'a> {
+    name: Cow<'a,str>,
+    payload: Cow<'a,[u8]>,
+}
+
+#[derive(Codec,Debug,PartialEq)]
+struct Labeled<'a,T> where T: Clone {
+    label: Cow<'a,str>,
+    value: T,
+}
+
+#[derive(Codec,Debug,PartialEq)]
+enum Event<'a> {
+    Start(Cow<'a,str>),
+    Stop { reason: Cow<'a,str>, },
+}
+
+#[test]
+fn test_lifetime_struct() {
+    let name = "depth".to_string();
+    let source = Msg { name: Cow::Borrowed(&name), payload: Cow::Borrowed(&[1,2,3]), };
+    let mut buffer = Vec::<u8>::new();
+    let size = source.encode(&mut buffer);
+    assert_eq!(size,source.size());
+    if let Ok((l,target)) = Msg::decode(&buffer) {
+        assert_eq!(l,buffer.len());
+        assert_eq!(source,target);
+    }
+    else {
+        assert!(false);
+    }
+}
+
+#[test]
+fn test_lifetime_generic() {
+    let source = Labeled { label: Cow::Borrowed("x"), value: 3.5f64, };
+    let mut buffer = Vec::<u8>::new();
+    source.encode(&mut buffer);
+    assert_eq!(Labeled::<f64>::decode(&buffer),Ok((buffer.len(),source)));
+}
+
+#[test]
+fn test_lifetime_enum() {
+    for source in [Event::Start(Cow::Borrowed("go")),Event::Stop { reason: Cow::Borrowed("done"), }] {
+        let mut buffer = Vec::<u8>::new();
+        source.encode(&mut buffer);
+        assert_eq!(Event::decode(&buffer),Ok((buffer.len(),source)));
+    }
+}
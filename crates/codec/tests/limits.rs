@@ -0,0 +1,111 @@
+// Echidna - Codec - tests
+
+use {
+    codec::*,
+    std::{
+        alloc::{
+            GlobalAlloc,
+            Layout,
+            System,
+        },
+        cell::Cell,
+        collections::{
+            HashMap,
+            HashSet,
+        },
+    },
+};
+
+// largest single allocation on this thread
+thread_local! {
+    static LARGEST: Cell<usize> = const { Cell::new(0) };
+}
+
+struct Largest;
+
+unsafe impl GlobalAlloc for Largest {
+    unsafe fn alloc(&self,layout: Layout) -> *mut u8 {
+        let _ = LARGEST.try_with(|largest| largest.set(largest.get().max(layout.size())));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self,ptr: *mut u8,layout: Layout) {
+        System.dealloc(ptr,layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Largest = Largest;
+
+#[derive(Codec,Debug,PartialEq)]
+enum Tree {
+    Leaf,
+    Node(Vec<Tree>),
+}
+
+#[derive(Codec,Debug,PartialEq)]
+struct Image {
+    width: u32,
+    height: u32,
+    data: Vec<u8>,
+}
+
+#[test]
+fn test_hostile_length() {
+    let buffer = [0xFF,0xFF,0xFF,0xFF,1,2,3];
+    assert_eq!(Vec::<u8>::decode(&buffer),Err(Error::TooLong(0xFFFFFFFF)));
+    assert_eq!(String::decode(&buffer),Err(Error::TooLong(0xFFFFFFFF)));
+}
+
+#[test]
+fn test_preallocation() {
+    // 5 bytes that claim a million elements, well within the limits
+    let buffer = [0x40,0x42,0x0F,0x00,7];
+    LARGEST.with(|largest| largest.set(0));
+    assert_eq!(Vec::<u64>::decode(&buffer),Err(Error::Truncated));
+    assert_eq!(HashMap::<u32,String>::decode(&buffer),Err(Error::Truncated));
+    assert_eq!(HashSet::<u16>::decode(&buffer),Err(Error::Truncated));
+    assert!(LARGEST.with(|largest| largest.get()) < 1024);
+    let mut target = Vec::<u32>::new();
+    assert_eq!(target.decode_into(&buffer),Err(Error::Truncated));
+    assert_eq!(target.capacity(),0);
+}
+
+#[test]
+fn test_truncated() {
+    let source = Image { width: 2, height: 2, data: vec![1,2,3,4], };
+    let mut buffer = Vec::<u8>::new();
+    source.encode(&mut buffer);
+    for len in 0..buffer.len() {
+        assert_eq!(Image::decode(&buffer[..len]),Err(Error::Truncated));
+    }
+    assert_eq!(Image::decode(&buffer),Ok((buffer.len(),source)));
+}
+
+#[test]
+fn test_max_alloc() {
+    let source = Image { width: 64, height: 64, data: vec![0; 4096], };
+    let mut buffer = Vec::<u8>::new();
+    source.encode(&mut buffer);
+    let limits = DecodeLimits { max_alloc: 1024, ..DecodeLimits::default() };
+    assert_eq!(Image::decode_limited(&buffer,&limits),Err(Error::TooLarge(4096)));
+    assert!(Image::decode(&buffer).is_ok());
+}
+
+#[test]
+fn test_max_depth() {
+    let mut source = Tree::Leaf;
+    for _ in 0..10 {
+        source = Tree::Node(vec![source]);
+    }
+    let mut buffer = Vec::<u8>::new();
+    source.encode(&mut buffer);
+    let limits = DecodeLimits { max_depth: 8, ..DecodeLimits::default() };
+    assert_eq!(Tree::decode_limited(&buffer,&limits),Err(Error::TooDeep));
+    assert_eq!(Tree::decode(&buffer),Ok((buffer.len(),source)));
+}
+
+#[test]
+fn test_invalid_tag() {
+    assert_eq!(Tree::decode(&[2,0,0,0]),Err(Error::InvalidTag(2)));
+}
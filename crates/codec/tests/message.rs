@@ -0,0 +1,130 @@
+// Echidna - Codec - tests
+
+use codec::{
+    *,
+    schema::*,
+};
+
+/// Where a robot is.
+#[derive(Codec,Message)]
+#[codec(schema)]
+struct Pose {
+    /// Microseconds since the epoch.
+    ts: u64,
+    x: f32,
+    y: f32,
+}
+
+#[derive(Codec,Message)]
+#[codec(schema,topic = "/arm/joints",version = 3)]
+struct JointState(u64,Vec<f32>);
+
+#[derive(Codec,Message)]
+#[codec(schema,name = "robot.Command")]
+enum Command {
+    Stop,
+    Go { to: Pose, },
+}
+
+#[derive(Codec,Message)]
+#[codec(schema)]
+struct HTTPRequest {
+    url: String,
+}
+
+// the same names, with different documentation or types
+mod other {
+
+    use codec::*;
+
+    #[derive(Codec,Message)]
+    #[codec(schema)]
+    pub struct Pose {
+        pub ts: u64,
+        pub x: f32,
+        pub y: f32,
+    }
+
+    #[derive(Codec,Message)]
+    #[codec(schema)]
+    pub struct JointState(pub u64,pub Vec<f64>);
+
+    #[derive(Codec,Message)]
+    #[codec(schema)]
+    pub enum Command {
+        Stop,
+        Go { to: super::Pose, },
+        Wait,
+    }
+}
+
+// the same name for different `JointState`s
+mod same {
+
+    #[derive(codec::Codec,codec::Message)]
+    #[codec(schema)]
+    pub struct Joints {
+        pub a: super::JointState,
+        pub b: super::JointState,
+    }
+}
+
+mod mixed {
+
+    #[derive(codec::Codec,codec::Message)]
+    #[codec(schema)]
+    pub struct Joints {
+        pub a: super::JointState,
+        pub b: super::other::JointState,
+    }
+}
+
+// codec types in a message
+#[derive(Codec,Message)]
+#[codec(schema)]
+struct Beacon {
+    from: std::net::SocketAddr,
+    data: Checked<Vec<u8>>,
+}
+
+#[test]
+fn test_message() {
+    assert_eq!(Pose::NAME,"message::Pose");
+    assert_eq!(Pose::TOPIC,"/pose");
+    assert_eq!(Pose::VERSION,1);
+    assert_eq!(JointState::TOPIC,"/arm/joints");
+    assert_eq!(JointState::VERSION,3);
+    assert_eq!(other::JointState::NAME,"message::other::JointState");
+    assert_eq!(other::JointState::TOPIC,"/joint_state");
+    assert_eq!(Command::NAME,"robot.Command");
+    assert_eq!(HTTPRequest::TOPIC,"/http_request");
+    assert_eq!(Command::schema(),Schema::of::<Command>());
+}
+
+#[test]
+fn test_fingerprint() {
+    // documentation doesn't count, types and variants do
+    assert_eq!(Pose::fingerprint(),other::Pose::fingerprint());
+    assert_ne!(JointState::fingerprint(),other::JointState::fingerprint());
+    assert_ne!(Command::fingerprint(),other::Command::fingerprint());
+    assert_ne!(Pose::fingerprint(),HTTPRequest::fingerprint());
+
+    // and so do the types referred to
+    let mut schema = Command::schema();
+    assert_eq!(schema.fingerprint(&Type::Named("Command".to_string())),Command::fingerprint());
+    if let ItemKind::Struct(fields) = &mut schema.items[1].kind {
+        fields[1].ty = Type::F64;
+    }
+    assert_ne!(schema.fingerprint(&Type::Named("Command".to_string())),Command::fingerprint());
+    assert_ne!(schema.fingerprint(&Type::Vec(Box::new(Type::Named("Command".to_string())))),schema.fingerprint(&Type::Named("Command".to_string())));
+}
+
+#[test]
+fn test_fingerprint_same_names() {
+    assert_ne!(same::Joints::fingerprint(),mixed::Joints::fingerprint());
+    assert_ne!(Beacon::fingerprint(),0);
+    assert_eq!(Beacon::schema().items[0].kind,ItemKind::Struct(vec![
+        Field { doc: Vec::new(), name: "from".to_string(), ty: Type::Socket, },
+        Field { doc: Vec::new(), name: "data".to_string(), ty: Type::Checked(Box::new(Type::Vec(Box::new(Type::U8)))), },
+    ]));
+}
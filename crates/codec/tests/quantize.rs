@@ -0,0 +1,100 @@
+// Echidna - Codec - tests
+
+use codec::{
+    *,
+    delta::*,
+    testing::*,
+};
+
+#[derive(Codec,Random,Clone,Debug,PartialEq)]
+#[codec(delta)]
+struct Point {
+    #[codec(quantize(f16))]
+    x: f32,
+    #[codec(quantize(bf16))]
+    y: f32,
+    #[codec(quantize(min = -1, max = 1, bits = 10))]
+    intensity: f32,
+    #[codec(quantize(min = 0, max = 100, bits = 24))]
+    range: f64,
+    ring: u8,
+}
+
+#[derive(Codec,Random,Clone,Debug,PartialEq)]
+struct Normal(#[codec(quantize(min = -1, max = 1, bits = 8))] f32,#[codec(quantize(min = -1, max = 1, bits = 8))] f32,#[codec(quantize(min = -1, max = 1, bits = 8))] f32);
+
+#[derive(Codec,Random,Clone,Debug,PartialEq)]
+enum Sample {
+    Depth(Quantized<f32,0,10,16>),
+    Color {
+        #[codec(quantize(f16))]
+        r: f32,
+        #[codec(quantize(f16))]
+        g: f32,
+        #[codec(quantize(f16))]
+        b: f32,
+    },
+}
+
+#[derive(Codec,Clone,Debug,PartialEq)]
+#[codec(view)]
+struct Cloud {
+    #[codec(quantize(f16))]
+    scale: f32,
+    points: Vec<Point>,
+}
+
+#[test]
+fn test_quantize() {
+    assert_eq!(Point::FIXED_SIZE,Some(2 + 2 + 2 + 3 + 1));
+    assert_eq!(<Normal as FixedSize>::SIZE,3);
+    assert_eq!(Sample::FIXED_SIZE,None);
+    let point = Point { x: 1.5, y: -3.0, intensity: 0.5, range: 42.0, ring: 7, };
+    let buffer = point.encode_to_vec();
+    assert_eq!(buffer.len(),10);
+    assert_eq!(point.size(),10);
+    assert_eq!(&buffer[..4],&[0x00,0x3E,0x40,0xC0]);
+    let (l,decoded) = Point::decode(&buffer).unwrap();
+    assert_eq!(l,10);
+    assert_eq!((decoded.x,decoded.y,decoded.ring),(1.5,-3.0,7));
+    assert!((decoded.intensity - 0.5).abs() <= 1.0 / 1023.0);
+    assert!((decoded.range - 42.0).abs() <= 100.0 / 16777215.0);
+    // values that are already steps come back exactly
+    assert_eq!(Point::decode(&decoded.encode_to_vec()),Ok((10,decoded)));
+    // out of range values clamp
+    let normal = Normal(2.0,-2.0,0.0);
+    assert_eq!(normal.encode_to_vec(),vec![0xFF,0x00,0x80]);
+    let (_,decoded) = Normal::decode(&[0xFF,0x00,0x80]).unwrap();
+    assert_eq!((decoded.0,decoded.1),(1.0,-1.0));
+    let sample = Sample::Color { r: 0.25, g: 0.5, b: 1.0, };
+    assert_eq!(sample.size(),4 + 6);
+    assert_eq!(Sample::decode(&sample.encode_to_vec()),Ok((10,sample)));
+}
+
+#[test]
+fn test_quantize_view() {
+    let cloud = Cloud { scale: 0.5, points: vec![Point { x: 1.0, y: 2.0, intensity: 1.0, range: 100.0, ring: 1, }], };
+    let buffer = cloud.encode_to_vec();
+    let view = CloudView::new(&buffer);
+    assert_eq!(view.scale(),Ok(0.5));
+    assert_eq!(view.points(),Ok(cloud.points.clone()));
+}
+
+#[test]
+fn test_quantize_delta() {
+    let reference = Point { x: 1.0, y: 2.0, intensity: 0.0, range: 0.0, ring: 0, };
+    let point = Point { x: 2.0, ..reference.clone() };
+    let mut buffer = Vec::<u8>::new();
+    assert_eq!(point.encode_delta(&reference,&mut buffer),3);
+    assert_eq!(Point::decode_delta(&reference,&buffer),Ok((3,point)));
+}
+
+#[test]
+fn test_quantize_conformance() {
+    roundtrip_check::<Point>(500);
+    roundtrip_check::<Normal>(500);
+    roundtrip_check::<Sample>(500);
+    roundtrip_check::<F16>(500);
+    roundtrip_check::<BF16>(500);
+    roundtrip_check::<Quantized<f64,{ -1000 },1000,20>>(500);
+}
@@ -0,0 +1,70 @@
+// Echidna - Codec - tests
+
+use {
+    codec::{
+        Codec,
+        testing::*,
+    },
+    std::{
+        borrow::Cow,
+        collections::{
+            HashMap,
+            HashSet,
+        },
+        net::{
+            IpAddr,
+            SocketAddr,
+        },
+    },
+};
+
+#[derive(Codec,Random,Clone,Copy,Debug,PartialEq,Eq,Hash)]
+#[codec(transparent)]
+struct Id(u64);
+
+#[derive(Codec,Random,Debug,PartialEq)]
+struct Marker;
+
+#[derive(Codec,Random,Debug,PartialEq)]
+enum Tree {
+    Leaf(i32),
+    Node(Vec<Tree>),
+}
+
+#[derive(Codec,Random,Debug,PartialEq)]
+struct Labeled<'a,T: Clone> {
+    label: Cow<'a,str>,
+    items: Cow<'a,[T]>,
+}
+
+#[derive(Codec,Random,Debug,PartialEq)]
+enum Message {
+    Ping,
+    Hello { id: Id, address: SocketAddr, },
+    Table(HashMap<Id,String>,HashSet<u16>),
+    Forest(Vec<Tree>,Marker),
+    Route(IpAddr,f64,f32,bool,i8),
+}
+
+#[test]
+fn test_roundtrip() {
+    roundtrip_check::<Id>(100);
+    roundtrip_check::<Marker>(10);
+    roundtrip_check::<Tree>(200);
+    roundtrip_check::<Labeled<u16>>(100);
+    roundtrip_check::<Message>(500);
+}
+
+#[test]
+fn test_truncation() {
+    truncation_check::<Tree>(50);
+    truncation_check::<Labeled<u16>>(50);
+    truncation_check::<Message>(100);
+}
+
+#[test]
+fn test_bitflip() {
+    bitflip_check::<Tree>(100);
+    bitflip_check::<Labeled<u16>>(100);
+    bitflip_check::<Message>(500);
+}
@@ -0,0 +1,143 @@
+// Echidna - Codec - tests
+
+use codec::{
+    Checked,
+    Codec,
+    Compressed,
+    F16,
+    schema::*,
+};
+
+/// Tree of names.
+#[derive(Codec)]
+#[codec(schema)]
+struct Node {
+    /// Shown in the tree.
+    name: String,
+    children: Vec<Node>,
+    parent: Option<Id>,
+}
+
+#[derive(Codec)]
+#[codec(transparent,schema)]
+struct Id(u64);
+
+#[derive(Codec)]
+#[codec(schema)]
+struct Pair(u8,Shape);
+
+// the codec types, and fields that encode as them
+#[derive(Codec)]
+#[codec(schema)]
+struct Link {
+    half: F16,
+    #[codec(quantize(bf16))]
+    brain: f32,
+    #[codec(quantize(min = -1,max = 1,bits = 10))]
+    level: f64,
+    #[codec(bits)]
+    flags: Vec<bool>,
+    addrs: Vec<std::net::IpAddr>,
+    name: std::borrow::Cow<'static,str>,
+    data: Compressed<Checked<std::borrow::Cow<'static,[u16]>>>,
+}
+
+// two different types called `Pose`
+mod a {
+
+    use codec::Codec;
+
+    #[derive(Codec)]
+    #[codec(schema)]
+    pub struct Pose {
+        pub x: u8,
+    }
+}
+
+mod b {
+
+    use codec::Codec;
+
+    #[derive(Codec)]
+    #[codec(schema)]
+    pub struct Pose {
+        pub s: String,
+        pub next: Vec<Pose>,
+    }
+}
+
+#[derive(Codec)]
+#[codec(schema)]
+struct Both {
+    a: a::Pose,
+    b: b::Pose,
+    again: a::Pose,
+}
+
+#[derive(Codec)]
+#[codec(schema)]
+enum Shape {
+    Empty,
+    /// Radius only.
+    Circle(f32),
+    Rect { w: f32, h: f32, },
+}
+
+fn field(doc: &[&str],name: &str,ty: Type) -> Field {
+    Field {
+        doc: doc.iter().map(|line| line.to_string()).collect(),
+        name: name.to_string(),
+        ty: ty,
+    }
+}
+
+#[test]
+fn test_recursive() {
+    let schema = Schema::of::<Node>();
+    assert_eq!(schema.items.len(),1);
+    assert_eq!(schema.items[0],Item {
+        doc: vec!["Tree of names.".to_string()],
+        name: "Node".to_string(),
+        kind: ItemKind::Struct(vec![
+            field(&["Shown in the tree."],"name",Type::String),
+            field(&[],"children",Type::Vec(Box::new(Type::Named("Node".to_string())))),
+            field(&[],"parent",Type::Option(Box::new(Type::U64))),
+        ]),
+        consts: Vec::new(),
+    });
+}
+
+#[test]
+fn test_tuple_and_enum() {
+    let schema = Schema::of::<Pair>();
+    assert_eq!(schema.items.iter().map(|item| item.name.as_str()).collect::<Vec<&str>>(),vec!["Pair","Shape"]);
+    assert_eq!(schema.items[0].kind,ItemKind::Struct(vec![field(&[],"_0",Type::U8),field(&[],"_1",Type::Named("Shape".to_string()))]));
+    assert_eq!(schema.items[1].kind,ItemKind::Enum(vec![
+        Variant { doc: Vec::new(), name: "Empty".to_string(), fields: Vec::new(), },
+        Variant { doc: vec!["Radius only.".to_string()], name: "Circle".to_string(), fields: vec![field(&[],"_0",Type::F32)], },
+        Variant { doc: Vec::new(), name: "Rect".to_string(), fields: vec![field(&[],"w",Type::F32),field(&[],"h",Type::F32)], },
+    ]));
+    assert_eq!(<Vec<Option<Id>> as Describe>::describe(&mut Schema::default()),Type::Vec(Box::new(Type::Option(Box::new(Type::U64)))));
+}
+
+#[test]
+fn test_same_names() {
+    let schema = Schema::of::<Both>();
+    assert_eq!(schema.items.iter().map(|item| item.name.as_str()).collect::<Vec<&str>>(),vec!["Both","Pose","schema_b_Pose"]);
+    let named = |name: &str| Type::Named(name.to_string());
+    assert_eq!(schema.items[0].kind,ItemKind::Struct(vec![field(&[],"a",named("Pose")),field(&[],"b",named("schema_b_Pose")),field(&[],"again",named("Pose"))]));
+    assert_eq!(schema.items[1].kind,ItemKind::Struct(vec![field(&[],"x",Type::U8)]));
+    assert_eq!(schema.items[2].kind,ItemKind::Struct(vec![field(&[],"s",Type::String),field(&[],"next",Type::Vec(Box::new(named("schema_b_Pose"))))]));
+}
+
+#[test]
+fn test_codec_types() {
+    let schema = Schema::of::<Link>();
+    let types: Vec<String> = match &schema.items[0].kind {
+        ItemKind::Struct(fields) => fields.iter().map(|field| field.ty.to_string()).collect(),
+        _ => panic!("`Link` is not a struct"),
+    };
+    assert_eq!(types,vec!["f16","bf16","quantized<-1,1,10>","bitset","ip[]","string","compressed<checked<u16[]>>"]);
+    assert!(Type::Vec(Box::new(Type::U8)).is_portable());
+    assert!(!Type::Option(Box::new(Type::Socket)).is_portable());
+}
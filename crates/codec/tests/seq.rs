@@ -0,0 +1,74 @@
+// Echidna - Codec - tests
+
+use {
+    codec::*,
+    std::collections::HashMap,
+};
+
+#[derive(Codec,Clone,Debug,PartialEq)]
+struct Point {
+    x: f32,
+    y: f32,
+    z: f32,
+    label: String,
+}
+
+fn points(n: usize) -> Vec<Point> {
+    (0..n).map(|i| Point { x: i as f32, y: 0.0, z: -(i as f32), label: format!("p{}",i), }).collect()
+}
+
+#[test]
+fn test_vec() {
+    let buffer = points(1000).encode_to_vec();
+    let mut iter = SeqIter::<Point>::new(&buffer).unwrap();
+    assert_eq!(iter.remaining(),1000);
+    assert_eq!(iter.next(),Some(Ok(points(1).remove(0))));
+    assert_eq!(iter.position(),4 + 4 * 3 + 4 + 2);
+    let rest: Vec<Point> = iter.by_ref().map(|point| point.unwrap()).collect();
+    assert_eq!(rest,points(1000)[1..].to_vec());
+    assert_eq!(iter.position(),buffer.len());
+    assert_eq!(iter.next(),None);
+
+    // filtering and downsampling while decoding
+    let near = SeqIter::<Point>::new(&buffer).unwrap().filter_map(|point| point.ok()).filter(|point| point.x < 10.0).count();
+    assert_eq!(near,10);
+    let every: Vec<Point> = SeqIter::<Point>::new(&buffer).unwrap().step_by(100).map(|point| point.unwrap()).collect();
+    assert_eq!(every,points(1000).into_iter().step_by(100).collect::<Vec<Point>>());
+    assert_eq!(SeqIter::<Point>::new(&buffer).unwrap().nth(1000),None);
+}
+
+#[test]
+fn test_map() {
+    let mut map = HashMap::<String,u32>::new();
+    for i in 0..100 {
+        map.insert(format!("key{}",i),i);
+    }
+    let buffer = map.encode_to_vec();
+    let entries: HashMap<String,u32> = SeqIter::<(String,u32)>::entries(&buffer).unwrap().map(|entry| entry.unwrap()).collect();
+    assert_eq!(entries,map);
+}
+
+#[test]
+fn test_errors() {
+    // elements before the bad one still come out
+    let mut buffer = points(3).encode_to_vec();
+    buffer.truncate(buffer.len() - 1);
+    let mut iter = SeqIter::<Point>::new(&buffer).unwrap();
+    assert!(matches!(iter.next(),Some(Ok(_))));
+    assert!(matches!(iter.next(),Some(Ok(_))));
+    assert_eq!(iter.next(),Some(Err(Error::Truncated)));
+    assert_eq!(iter.next(),None);
+    let mut iter = SeqIter::<Point>::new(&buffer).unwrap();
+    assert_eq!(iter.nth(2),Some(Err(Error::Truncated)));
+    assert_eq!(iter.nth(1),None);
+
+    // every element is decoded within the limits
+    let limits = DecodeLimits { max_string_bytes: 2, ..DecodeLimits::default() };
+    let buffer = points(20).encode_to_vec();
+    let results: Vec<Result<Point>> = SeqIter::<Point>::new_limited(&buffer,&limits).unwrap().collect();
+    assert_eq!(results.len(),11);
+    assert_eq!(results[10],Err(Error::TooLong(3)));
+    let limits = DecodeLimits { max_len: 19, ..DecodeLimits::default() };
+    assert_eq!(SeqIter::<Point>::new_limited(&buffer,&limits).err(),Some(Error::TooLong(20)));
+    assert_eq!(SeqIter::<Point>::new(&buffer[..3]).err(),Some(Error::Truncated));
+}
@@ -0,0 +1,66 @@
+// Echidna - Codec - tests
+
+use {
+    codec::*,
+    std::collections::HashMap,
+};
+
+#[derive(Codec,Debug,PartialEq)]
+enum Packet {
+    Ping,
+    Data { topic: String, payload: Vec<u8>, },
+    Table(HashMap<u16,Vec<String>>),
+}
+
+#[derive(Codec,Debug,PartialEq)]
+struct Frame(u32,Packet,bool);
+
+fn frames() -> Vec<Frame> {
+    let mut table = HashMap::<u16,Vec<String>>::new();
+    table.insert(1,vec!["one".to_string()]);
+    table.insert(2,vec!["two".to_string(),"zwei".to_string()]);
+    vec![
+        Frame(0,Packet::Ping,false),
+        Frame(1,Packet::Data { topic: "video".to_string(), payload: vec![7; 1000], },true),
+        Frame(2,Packet::Table(table),false),
+    ]
+}
+
+#[test]
+fn test_skip() {
+    let mut buffer = Vec::<u8>::new();
+    let mut ends = Vec::<usize>::new();
+    for frame in frames() {
+        frame.encode(&mut buffer);
+        ends.push(buffer.len());
+    }
+    let mut ofs = 0usize;
+    for end in ends {
+        assert_eq!(Frame::validate(&buffer[ofs..]),Ok(()));
+        ofs += Frame::skip(&buffer[ofs..]).unwrap();
+        assert_eq!(ofs,end);
+    }
+}
+
+#[test]
+fn test_skip_truncated() {
+    let mut buffer = Vec::<u8>::new();
+    frames()[1].encode(&mut buffer);
+    for len in 0..buffer.len() {
+        assert_eq!(Frame::skip(&buffer[..len]),Err(Error::Truncated));
+        assert_eq!(Frame::validate(&buffer[..len]),Err(Error::Truncated));
+    }
+}
+
+#[test]
+fn test_validate_limits() {
+    let mut buffer = Vec::<u8>::new();
+    frames()[1].encode(&mut buffer);
+    let limits = DecodeLimits { max_alloc: 512, ..DecodeLimits::default() };
+    assert_eq!(Frame::validate_limited(&buffer,&limits),Err(Error::TooLarge(1005)));
+    assert_eq!(Frame::skip(&buffer),Ok(buffer.len()));
+    let hostile = [0xFF,0xFF,0xFF,0xFF,1,2,3];
+    assert_eq!(Vec::<u8>::validate(&hostile),Err(Error::TooLong(0xFFFFFFFF)));
+    assert_eq!(Vec::<u8>::skip(&hostile),Err(Error::Truncated));
+    assert_eq!(Packet::skip(&[3,0,0,0]),Err(Error::InvalidTag(3)));
+}
@@ -3,9 +3,12 @@
 //mod dump;
 //use dump::*;
 
-use codec::Codec;
+use codec::{
+    Codec,
+    testing::*,
+};
 
-#[derive(Codec)]
+#[derive(Codec,Random,Debug,PartialEq)]
 pub struct MyStruct {
     yesno: bool,
     ubyte: u8,
@@ -44,7 +47,7 @@ fn test_struct() {
 
     //dump(&buffer);
 
-    if let Some((_,target)) = MyStruct::decode(&buffer) {
+    if let Ok((_,target)) = MyStruct::decode(&buffer) {
         println!("yesno: {}",target.yesno);
         println!("ubyte: {}",target.ubyte);
         println!("byte: {}",target.byte);
@@ -64,3 +67,10 @@ fn test_struct() {
         assert!(false);
     }
 }
+
+#[test]
+fn test_struct_roundtrip() {
+    roundtrip_check::<MyStruct>(100);
+    truncation_check::<MyStruct>(20);
+    bitflip_check::<MyStruct>(100);
+}
@@ -0,0 +1,51 @@
+// Echidna - Codec - tests
+
+use codec::Codec;
+
+#[derive(Codec,Clone,Copy,Debug,PartialEq)]
+#[codec(transparent)]
+struct MessageId(u64);
+
+#[derive(Codec,Debug,PartialEq)]
+#[codec(transparent)]
+struct Topic {
+    name: String,
+}
+
+#[derive(Codec,Debug,PartialEq)]
+struct Header {
+    id: MessageId,
+    topic: Topic,
+}
+
+#[test]
+fn test_transparent_tuple() {
+    let source = MessageId(0x0123456789ABCDEF);
+    let mut newtype = Vec::<u8>::new();
+    let mut inner = Vec::<u8>::new();
+    source.encode(&mut newtype);
+    source.0.encode(&mut inner);
+    assert_eq!(newtype,inner);
+    assert_eq!(source.size(),source.0.size());
+    assert_eq!(MessageId::decode(&newtype),Ok((8,source)));
+}
+
+#[test]
+fn test_transparent_struct() {
+    let source = Topic { name: "/hello".to_string(), };
+    let mut newtype = Vec::<u8>::new();
+    let mut inner = Vec::<u8>::new();
+    source.encode(&mut newtype);
+    source.name.encode(&mut inner);
+    assert_eq!(newtype,inner);
+    assert_eq!(Topic::decode(&newtype),Ok((newtype.len(),source)));
+}
+
+#[test]
+fn test_transparent_field() {
+    let source = Header { id: MessageId(7), topic: Topic { name: "/depth".to_string(), }, };
+    let mut buffer = Vec::<u8>::new();
+    source.encode(&mut buffer);
+    assert_eq!(&buffer[0..8],&[7,0,0,0,0,0,0,0]);
+    assert_eq!(Header::decode(&buffer),Ok((buffer.len(),source)));
+}
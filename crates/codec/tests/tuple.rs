@@ -3,9 +3,12 @@
 //mod dump;
 //use dump::*;
 
-use codec::Codec;
+use codec::{
+    Codec,
+    testing::*,
+};
 
-#[derive(Codec)]
+#[derive(Codec,Random,Debug,PartialEq)]
 struct MyTuple(f32,f32);
 
 #[test]
@@ -16,7 +19,7 @@ fn test_tuple() {
 
     //dump(&buffer);
 
-    if let Some((_,target)) = MyTuple::decode(&buffer) {
+    if let Ok((_,target)) = MyTuple::decode(&buffer) {
         println!(".0: {}",target.0);
         println!(".1: {}",target.1);
         assert!(true);
@@ -25,3 +28,10 @@ fn test_tuple() {
         assert!(false);
     }
 }
+
+#[test]
+fn test_tuple_roundtrip() {
+    roundtrip_check::<MyTuple>(100);
+    truncation_check::<MyTuple>(20);
+    bitflip_check::<MyTuple>(100);
+}
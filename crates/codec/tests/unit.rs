@@ -0,0 +1,50 @@
+// Echidna - Codec - tests
+
+use codec::Codec;
+
+#[derive(Codec,Debug,PartialEq)]
+struct Marker;
+
+#[derive(Codec,Debug,PartialEq)]
+struct Nothing { }
+
+#[derive(Codec,Debug,PartialEq)]
+struct Empty();
+
+#[derive(Codec)]
+enum Never { }
+
+#[derive(Codec,Debug,PartialEq)]
+struct Wrapped {
+    before: u8,
+    marker: Marker,
+    after: u8,
+}
+
+#[test]
+fn test_unit_struct() {
+    let mut buffer = Vec::<u8>::new();
+    assert_eq!(Marker.encode(&mut buffer),0);
+    assert_eq!(Nothing { }.encode(&mut buffer),0);
+    assert_eq!(Empty().encode(&mut buffer),0);
+    assert_eq!(buffer.len(),0);
+    assert_eq!(Marker.size(),0);
+    assert_eq!(Marker::decode(&buffer),Ok((0,Marker)));
+    assert_eq!(Nothing::decode(&buffer),Ok((0,Nothing { })));
+    assert_eq!(Empty::decode(&buffer),Ok((0,Empty())));
+}
+
+#[test]
+fn test_unit_field() {
+    let source = Wrapped { before: 1, marker: Marker, after: 2, };
+    let mut buffer = Vec::<u8>::new();
+    source.encode(&mut buffer);
+    assert_eq!(buffer,vec![1,2]);
+    assert_eq!(Wrapped::decode(&buffer),Ok((2,source)));
+}
+
+#[test]
+fn test_empty_enum() {
+    assert!(Never::decode(&[0,0,0,0]).is_err());
+    assert!(Never::decode(&[1,0,0,0]).is_err());
+}
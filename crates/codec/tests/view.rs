@@ -0,0 +1,73 @@
+// Echidna - Codec - tests
+
+use codec::*;
+
+#[derive(Codec,Clone,Copy,Debug,PartialEq)]
+#[codec(transparent)]
+pub struct Id(u64);
+
+#[derive(Codec,Debug,PartialEq)]
+#[codec(view)]
+pub struct Chunk {
+    pub ts: u64,
+    pub id: Id,
+    pub index: u32,
+    pub name: String,
+    pub data: Vec<u8>,
+    pub last: bool,
+}
+
+#[derive(Codec,Debug,PartialEq)]
+#[codec(view)]
+struct Pair(String,u16);
+
+fn chunk() -> Chunk {
+    Chunk { ts: 123456789, id: Id(42), index: 3, name: "frame".to_string(), data: vec![1,2,3,4,5], last: true, }
+}
+
+#[test]
+fn test_view() {
+    let source = chunk();
+    let mut buffer = Vec::<u8>::new();
+    source.encode(&mut buffer);
+    let view = ChunkView::new(&buffer);
+    assert_eq!(view.ts(),Ok(123456789));
+    assert_eq!(view.id(),Ok(Id(42)));
+    assert_eq!(view.index(),Ok(3));
+    assert_eq!(view.name(),Ok("frame".to_string()));
+    assert_eq!(view.data(),Ok(vec![1,2,3,4,5]));
+    assert_eq!(view.last(),Ok(true));
+    assert_eq!(view.decode(),Ok(source));
+    assert_eq!(view.as_bytes(),&buffer[..]);
+
+    let source = Pair("left".to_string(),7);
+    let mut buffer = Vec::<u8>::new();
+    source.encode(&mut buffer);
+    let view = PairView::new(&buffer);
+    assert_eq!(view._0(),Ok("left".to_string()));
+    assert_eq!(view._1(),Ok(7));
+}
+
+#[test]
+fn test_view_fixed() {
+    assert_eq!(<Id as Codec>::FIXED_SIZE,Some(8));
+    assert_eq!(<Chunk as Codec>::FIXED_SIZE,None);
+    // the header is found by offset, so a buffer that only holds the header is enough
+    let mut buffer = Vec::<u8>::new();
+    chunk().encode(&mut buffer);
+    let view = ChunkView::new(&buffer[..20]);
+    assert_eq!(view.index(),Ok(3));
+    assert_eq!(view.name(),Err(Error::Truncated));
+    let view = ChunkView::new(&buffer[..10]);
+    assert_eq!(view.index(),Err(Error::Truncated));
+}
+
+#[test]
+fn test_view_limits() {
+    let mut buffer = Vec::<u8>::new();
+    chunk().encode(&mut buffer);
+    let limits = DecodeLimits { max_len: 4, ..DecodeLimits::default() };
+    let view = ChunkView::with_limits(&buffer,&limits);
+    assert_eq!(view.name(),Ok("frame".to_string()));
+    assert_eq!(view.data(),Err(Error::TooLong(5)));
+}
@@ -58,7 +58,7 @@ impl Participant {
     pub async fn new(pubsub_port: u16,beacon_port: u16,domain: &str) -> Arc<Participant> {
 
         // new ID
-        let id = rand::random::<u64>();
+        let id = ParticipantId(rand::random::<u64>());
 
         // create participant listener
         let part_listener = net::TcpListener::bind("0.0.0.0:0").await.expect("cannot bind participant listener socket");
@@ -122,8 +122,7 @@ impl Participant {
                 domain: self.domain.clone(),
                 port: self.part_port,
             };
-            let mut buffer: Vec<u8> = Vec::new();
-            beacon.encode(&mut buffer);
+            let buffer = beacon.encode_to_vec();
             socket.send_to(&buffer,("239.255.0.1",beacon_port)).await.expect("cannot send beacon");
 
             // sleep until next tick
@@ -147,7 +146,7 @@ impl Participant {
             let (_,address) = socket.recv_from(&mut buffer).await.expect("cannot receive beacon");
 
             // decode beacon
-            if let Some((_,beacon)) = Beacon::decode(&buffer) {
+            if let Ok((_,beacon)) = Beacon::decode(&buffer) {
 
                 // if this is not a local echo, and it's the correct domain
                 if (beacon.id != self.id) && (beacon.domain == self.domain) {
@@ -211,7 +210,7 @@ impl Participant {
 
                 // read first message, should be ToPart::InitPub or ToPart::InitSub
                 if let Ok(_) = stream.read(&mut buffer).await {
-                    if let Some((_,message)) = ToParticipant::decode(&buffer) {
+                    if let Ok((_,message)) = ToParticipant::decode(&buffer) {
                         match message {
 
                             ToParticipant::InitPub(id,domain,publisher) => {
@@ -455,7 +454,7 @@ impl Participant {
         // get counter announcement from passive side
         let mut recv_buffer = vec![0u8; 65536];
         if let Ok(_) = stream_read.read(&mut recv_buffer).await {
-            if let Some((_,message)) = ParticipantAnnounce::decode(&recv_buffer) {
+            if let Ok((_,message)) = ParticipantAnnounce::decode(&recv_buffer) {
 
                 peer.pubs = message.pubs;
                 for (id,s) in message.subs.iter() {
@@ -526,7 +525,7 @@ impl Participant {
         // get announcement from active side
         let mut recv_buffer = vec![0u8; 65536];
         if let Ok(_) = stream_read.read(&mut recv_buffer).await {
-            if let Some((_,message)) = ParticipantAnnounce::decode(&recv_buffer) {
+            if let Ok((_,message)) = ParticipantAnnounce::decode(&recv_buffer) {
 
                 // store new peer ID
                 let peer_id = message.id;
@@ -633,7 +632,7 @@ impl Participant {
             if length == 0 {
                 break;
             }
-            if let Some((_,message)) = ParticipantToParticipant::decode(&buffer) {
+            if let Ok((_,message)) = ParticipantToParticipant::decode(&buffer) {
                 match message {
 
                     // peer has new publisher
@@ -3,22 +3,72 @@
 use {
     tokio::{
         io,
-        io::AsyncWriteExt,
+        io::{
+            AsyncWriteExt,
+            Interest,
+        },
+        net::UdpSocket,
+    },
+    codec::{
+        Codec,
+        Checked,
+        DecodeLimits,
+        Error,
+        Gather,
     },
-    codec::Codec,
     std::{
+        borrow::Cow,
         collections::HashMap,
+        io::IoSlice,
         net::SocketAddr,
+        fmt,
+        mem,
+        os::unix::io::AsRawFd,
     },
     libc::{
         c_int,
     },
 };
 
-pub type MessageId = u64;
-pub type ParticipantId = u64;
-pub type PublisherId = u64;
-pub type SubscriberId = u64;
+#[derive(Clone,Copy,PartialEq,Eq,Hash,Debug,Codec)]
+#[codec(transparent)]
+pub struct MessageId(pub u64);
+
+#[derive(Clone,Copy,PartialEq,Eq,Hash,Debug,Codec)]
+#[codec(transparent)]
+pub struct ParticipantId(pub u64);
+
+#[derive(Clone,Copy,PartialEq,Eq,Hash,Debug,Codec)]
+#[codec(transparent)]
+pub struct PublisherId(pub u64);
+
+#[derive(Clone,Copy,PartialEq,Eq,Hash,Debug,Codec)]
+#[codec(transparent)]
+pub struct SubscriberId(pub u64);
+
+impl fmt::UpperHex for MessageId {
+    fn fmt(&self,f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::UpperHex::fmt(&self.0,f)
+    }
+}
+
+impl fmt::UpperHex for ParticipantId {
+    fn fmt(&self,f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::UpperHex::fmt(&self.0,f)
+    }
+}
+
+impl fmt::UpperHex for PublisherId {
+    fn fmt(&self,f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::UpperHex::fmt(&self.0,f)
+    }
+}
+
+impl fmt::UpperHex for SubscriberId {
+    fn fmt(&self,f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::UpperHex::fmt(&self.0,f)
+    }
+}
 
 pub type ShmDescr = c_int;
 
@@ -28,6 +78,7 @@ pub struct SharedMemory {
 }
 
 #[derive(Codec)]
+#[codec(view)]
 pub struct Chunk {
     pub ts: u64,
     pub id: MessageId,
@@ -35,7 +86,8 @@ pub struct Chunk {
     pub chunk_size: u32,
     pub total: u32,
     pub index: u32,
-    pub data: Vec<u8>,
+    // UDP checksums are optional on IPv4, so corrupted chunks are caught here
+    pub data: Checked<Vec<u8>>,
 }
 
 #[derive(Codec)]
@@ -44,12 +96,85 @@ pub enum PublisherToSubscriber {
     Chunk(Chunk),
 }
 
+/// `Chunk` for sending, with the data borrowed from the message. Encodes
+/// exactly like `Chunk`, so `encode_gather` can send the data in place.
+#[derive(Codec)]
+pub struct ChunkRef<'a> {
+    pub ts: u64,
+    pub id: MessageId,
+    pub total_bytes: u64,
+    pub chunk_size: u32,
+    pub total: u32,
+    pub index: u32,
+    pub data: Checked<Cow<'a,[u8]>>,
+}
+
+/// `PublisherToSubscriber` for sending, with the same variants in the same order.
+#[derive(Codec)]
+pub enum PublisherToSubscriberRef<'a> {
+    Heartbeat(MessageId),
+    Chunk(ChunkRef<'a>),
+}
+
+/// What a `PublisherToSubscriber` datagram turned out to be, see `Chunk::receive`.
+pub enum Received {
+    Heartbeat(MessageId),
+    Chunk,
+}
+
+impl Chunk {
+
+    /// Empty chunk to receive into.
+    pub fn new() -> Chunk {
+        Chunk {
+            ts: 0,
+            id: MessageId(0),
+            total_bytes: 0,
+            chunk_size: 0,
+            total: 0,
+            index: 0,
+            data: Checked(Vec::new()),
+        }
+    }
+
+    /// Decode a `PublisherToSubscriber` datagram. Chunks are decoded into
+    /// `self`, and heartbeats are returned without touching it, so the data
+    /// buffer is reused even when heartbeats come in between.
+    pub fn receive(&mut self,buffer: &[u8],limits: &DecodeLimits) -> codec::Result<Received> {
+        // the tag is the variant index of `PublisherToSubscriber`, checked by `test_tags`
+        let (_,tag) = u32::decode(buffer)?;
+        match tag {
+            0 => {
+                let (_,id) = MessageId::decode_limited(&buffer[4..],limits)?;
+                Ok(Received::Heartbeat(id))
+            },
+            1 => {
+                self.decode_into_limited(&buffer[4..],limits)?;
+                Ok(Received::Chunk)
+            },
+            _ => Err(Error::InvalidTag(tag)),
+        }
+    }
+}
+
+impl Default for Chunk {
+    fn default() -> Self {
+        Chunk::new()
+    }
+}
+
 #[derive(Codec)]
 pub enum SubscriberToPublisher {
     Ack(MessageId,u32),
     NAck(MessageId,u32,u32),
 }
 
+/// Largest UDP payload.
+pub const MAX_DATAGRAM_SIZE: usize = 65507;
+
+// acks and nacks always fit in a single datagram
+const _: () = assert!(matches!(<SubscriberToPublisher as Codec>::MAX_SIZE,Some(n) if n <= MAX_DATAGRAM_SIZE));
+
 #[derive(Codec)]
 pub struct Beacon {
     pub id: ParticipantId,
@@ -116,7 +241,63 @@ pub enum ParticipantToSubscriber {
 }
 
 pub async fn send_message<S: io::AsyncWrite + Unpin,M: Codec>(stream: &mut S,message: M) {
-    let mut send_buffer = Vec::new();
-    message.encode(&mut send_buffer);
+    let send_buffer = message.encode_to_vec();
     stream.write_all(&send_buffer).await.expect("cannot send message");
 }
+
+// `address` as a `sockaddr` for the system calls
+fn sockaddr(address: &SocketAddr) -> (libc::sockaddr_storage,libc::socklen_t) {
+    let mut storage: libc::sockaddr_storage = unsafe { mem::zeroed() };
+    let len = match address {
+        SocketAddr::V4(address) => {
+            let sin = unsafe { &mut *(&mut storage as *mut libc::sockaddr_storage as *mut libc::sockaddr_in) };
+            sin.sin_family = libc::AF_INET as libc::sa_family_t;
+            sin.sin_port = address.port().to_be();
+            sin.sin_addr = libc::in_addr { s_addr: u32::from_ne_bytes(address.ip().octets()), };
+            mem::size_of::<libc::sockaddr_in>()
+        },
+        SocketAddr::V6(address) => {
+            let sin6 = unsafe { &mut *(&mut storage as *mut libc::sockaddr_storage as *mut libc::sockaddr_in6) };
+            sin6.sin6_family = libc::AF_INET6 as libc::sa_family_t;
+            sin6.sin6_port = address.port().to_be();
+            sin6.sin6_flowinfo = address.flowinfo();
+            sin6.sin6_addr = libc::in6_addr { s6_addr: address.ip().octets(), };
+            sin6.sin6_scope_id = address.scope_id();
+            mem::size_of::<libc::sockaddr_in6>()
+        },
+    };
+    (storage,len as libc::socklen_t)
+}
+
+/// Send the slices of `gather` as one datagram to `address`, see `send_slices`.
+pub async fn send_gather(socket: &UdpSocket,gather: &Gather<'_>,address: SocketAddr) -> io::Result<usize> {
+    send_slices(socket,&gather.slices(),address).await
+}
+
+/// Send `slices` as one datagram to `address`, without copying them
+/// together first.
+pub async fn send_slices(socket: &UdpSocket,slices: &[IoSlice<'_>],address: SocketAddr) -> io::Result<usize> {
+    let (mut name,namelen) = sockaddr(&address);
+    loop {
+        socket.writable().await?;
+        let result = socket.try_io(Interest::WRITABLE,|| {
+            let mut header: libc::msghdr = unsafe { mem::zeroed() };
+            header.msg_name = &mut name as *mut libc::sockaddr_storage as *mut libc::c_void;
+            header.msg_namelen = namelen;
+            // `IoSlice` has the layout of `iovec` on unix
+            header.msg_iov = slices.as_ptr() as *mut libc::iovec;
+            header.msg_iovlen = slices.len() as _;
+            let result = unsafe { libc::sendmsg(socket.as_raw_fd(),&header,0) };
+            if result < 0 {
+                Err(io::Error::last_os_error())
+            }
+            else {
+                Ok(result as usize)
+            }
+        });
+        match result {
+            Err(error) if error.kind() == io::ErrorKind::WouldBlock => continue,
+            result => return result,
+        }
+    }
+}
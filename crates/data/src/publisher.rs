@@ -2,7 +2,12 @@
 
 use {
     crate::*,
-    codec::Codec,
+    codec::{
+        Codec,
+        Checked,
+        Gather,
+        GatherPart,
+    },
     tokio::{
         net,
         task,
@@ -11,8 +16,11 @@ use {
         time,
     },
     std::{
+        borrow::Cow,
         sync::Arc,
         net::SocketAddr,
+        io::IoSlice,
+        ops::Range,
         collections::{
             HashMap,
             HashSet,
@@ -29,6 +37,78 @@ pub struct SubscriberControl {
     pub socket: net::UdpSocket,
 }
 
+// part of an encoded chunk
+enum Part {
+    // header or CRC bytes
+    Copied(Vec<u8>),
+    // chunk data, as a range of the message
+    Message(Range<usize>),
+}
+
+/// The chunks of one message and its heartbeat, encoded once and shared by
+/// the tasks sending them to each subscriber.
+///
+/// The chunk data stays in `message` and is never copied, only the headers
+/// and CRCs are.
+struct Chunks {
+    message: Arc<[u8]>,
+    parts: Vec<Vec<Part>>,
+    heartbeat: Vec<u8>,
+}
+
+impl Chunks {
+
+    fn new(id: MessageId,message: Arc<[u8]>,chunk_size: usize) -> Chunks {
+        let total_bytes = message.len();
+        let mut total = total_bytes / chunk_size;
+        if (total_bytes % chunk_size) != 0 {
+            total += 1;
+        }
+        let mut parts = Vec::<Vec<Part>>::new();
+        for index in 0..total {
+            let offset = index * chunk_size;
+            let size = chunk_size.min(total_bytes - offset);
+            let chunk = PublisherToSubscriberRef::Chunk(ChunkRef {
+                ts: 0,
+                id: id,
+                total_bytes: total_bytes as u64,
+                chunk_size: chunk_size as u32,
+                total: total as u32,
+                index: index as u32,
+                data: Checked(Cow::Borrowed(&message[offset..offset + size])),
+            });
+            let mut gather = Gather::new();
+            chunk.encode_gather(&mut gather);
+
+            // the only part that can be referenced is the data, which becomes a range of the message so it outlives the borrow
+            parts.push(gather.parts().into_iter().map(|part| match part {
+                GatherPart::Buffer(start,end) => Part::Copied(gather.scratch()[start..end].to_vec()),
+                GatherPart::Slice(slice) => {
+                    debug_assert_eq!(slice.len(),size);
+                    Part::Message(offset..offset + size)
+                },
+            }).collect());
+        }
+        Chunks {
+            message: message,
+            parts: parts,
+            heartbeat: PublisherToSubscriber::Heartbeat(id).encode_to_vec(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.parts.len()
+    }
+
+    // slices of chunk `index`, for `send_slices`
+    fn slices(&self,index: usize) -> Vec<IoSlice<'_>> {
+        self.parts[index].iter().map(|part| match part {
+            Part::Copied(bytes) => IoSlice::new(bytes),
+            Part::Message(range) => IoSlice::new(&self.message[range.clone()]),
+        }).collect()
+    }
+}
+
 pub struct Publisher {
     pub id: PublisherId,
     pub domain: String,
@@ -60,7 +140,7 @@ impl Publisher {
     ) -> Arc<Publisher> {
 
         // new ID
-        let id = rand::random::<u64>();
+        let id = PublisherId(rand::random::<u64>());
 
         // create publisher
         let publisher = Arc::new(Publisher {
@@ -117,7 +197,7 @@ impl Publisher {
                     if length == 0 {
                         break;
                     }
-                    if let Some((_,message)) = ParticipantToPublisher::decode(&recv_buffer) {
+                    if let Ok((_,message)) = ParticipantToPublisher::decode(&recv_buffer) {
                         match message {
                             ParticipantToPublisher::Init(local_subs,peer_subs) => {
                                 let mut state_subs = self.local_subs.lock().await;
@@ -184,7 +264,16 @@ impl Publisher {
         }
     }
 
+    /// Publish `message`.
+    ///
+    /// The message is copied once, into the `Arc` the sending tasks share.
+    /// Use `set_shared` to avoid that copy.
     pub async fn set(self: &Arc<Publisher>,message: &[u8]) {
+        self.set_shared(Arc::from(message)).await
+    }
+
+    /// Publish `message`, which is sent from where it is and never copied.
+    pub async fn set_shared(self: &Arc<Publisher>,message: Arc<[u8]>) {
 
         // update shared memory, if needed
         if {
@@ -230,54 +319,11 @@ impl Publisher {
                 }
             }
 
-            // calculate number of chunks for this message
-            let total_bytes = message.len();
-            let mut total = total_bytes / self.chunk_size;
-            if (total_bytes % self.chunk_size) != 0 {
-                total += 1;
-            }
-            println!("sending message of {} bytes in {} chunks",total_bytes,total);
-            
-            // prepare chunks
-            let id = rand::random::<u64>();
-            let mut chunks = Vec::new();
-
-            // build chunks
-            let mut index = 0u32;
-            let mut offset = 0usize;
-            while offset < total_bytes {
-
-                // create chunk
-                let size = {
-                    if (offset + self.chunk_size) > total_bytes {
-                        total_bytes - offset
-                    }
-                    else {
-                        self.chunk_size
-                    }
-                };
-                let chunk = Chunk {
-                    ts: 0,
-                    id: id,
-                    total_bytes: total_bytes as u64,
-                    chunk_size: self.chunk_size as u32,
-                    total: total as u32,
-                    index: index,
-                    data: Vec::<u8>::from(&message[offset..offset + size]),
-                };
-
-                // encode
-                let mut buffer = Vec::<u8>::new();
-                PublisherToSubscriber::Chunk(chunk).encode(&mut buffer);
-
-                // store
-                chunks.push(buffer);
-
-                // next
-                offset += size;
-                index += 1;
-            }
-            let master_chunks = Arc::new(chunks);
+            // encode the chunks once, for all subscribers
+            let id = MessageId(rand::random::<u64>());
+            let chunks = Arc::new(Chunks::new(id,message,self.chunk_size));
+            let total = chunks.len();
+            println!("sending message of {} bytes in {} chunks",chunks.message.len(),total);
 
             // take snapshot of current subscriber set
             let peer_subs = self.peer_subs.lock().await.clone();
@@ -297,7 +343,7 @@ impl Publisher {
             for (subscriber_id,control) in peer_subs.iter() {
 
                 let sub_id = *subscriber_id;
-                let chunks = Arc::clone(&master_chunks);
+                let chunks = Arc::clone(&chunks);
                 let control = Arc::clone(&control);
                 let finished = Arc::clone(&self.finished);
 
@@ -355,7 +401,7 @@ impl Publisher {
 
                         // send chunks
                         for index in indices.iter() {
-                            control.socket.send_to(&chunks[*index as usize],control.address).await.expect("error sending chunk");
+                            send_slices(&control.socket,&chunks.slices(*index as usize),control.address).await.expect("error sending chunk");
                             interval.tick().await;
                         }
 
@@ -366,9 +412,7 @@ impl Publisher {
 
                         // send heartbeat
                         //println!("send heartbeat");
-                        let mut send_buffer = Vec::<u8>::new();
-                        PublisherToSubscriber::Heartbeat(id).encode(&mut send_buffer);
-                        control.socket.send_to(&send_buffer,control.address).await.expect("error sending heartbeat");
+                        control.socket.send_to(&chunks.heartbeat,control.address).await.expect("error sending heartbeat");
 
                         // flush incoming acks and nacks
                         // TODO: it's currently not exactly flushing, but rather processing at most one message
@@ -379,7 +423,7 @@ impl Publisher {
                         }
                         else {
             
-                            if let Some((_,stp)) = SubscriberToPublisher::decode(&buffer) {
+                            if let Ok((_,stp)) = SubscriberToPublisher::decode(&buffer) {
             
                                 match stp {
             
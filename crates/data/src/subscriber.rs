@@ -9,9 +9,18 @@ use {
         sync::Mutex,
         time,
     },
-    codec::Codec,
+    codec::{
+        Codec,
+        DecodeLimits,
+    },
     std::{
-        sync::Arc,
+        sync::{
+            Arc,
+            atomic::{
+                AtomicUsize,
+                Ordering,
+            },
+        },
         net::SocketAddr,
         time::Duration,
     },
@@ -28,20 +37,29 @@ pub struct SubscriberState {
 }
 
 pub struct Subscriber {
-    pub id: PublisherId,
+    pub id: SubscriberId,
     pub domain: String,
     pub topic: String,
     pub socket: net::UdpSocket,
     pub address: SocketAddr,
     pub state: Mutex<SubscriberState>,
     pub shmem: Shmem,
+    pub limits: DecodeLimits,
+    /// Largest message reassembled from chunks, in bytes. The buffer for a
+    /// message is allocated when its first chunk arrives, so this bounds
+    /// what a single datagram can make the subscriber allocate. Starts at
+    /// `DecodeLimits::default().max_alloc`, change it with
+    /// `set_max_message_bytes`.
+    pub max_message_bytes: AtomicUsize,
+    /// Number of messages dropped for being larger than `max_message_bytes`.
+    pub dropped: AtomicUsize,
 }
 
 impl Subscriber {
     pub async fn new(pubsub_port: u16,domain: &str,topic: &str,on_data: impl Fn(&[u8]) + Send + 'static) -> Arc<Subscriber> {
 
         // new ID
-        let id = rand::random::<u64>();
+        let id = SubscriberId(rand::random::<u64>());
 
         // open data socket
         let socket = net::UdpSocket::bind("0.0.0.0:0").await.expect("cannot create subscriber socket");
@@ -55,11 +73,14 @@ impl Subscriber {
             socket: socket,
             address: address,
             state: Mutex::new(SubscriberState {
-                id: 0,
+                id: MessageId(0),
                 buffer: Vec::new(),
                 received: Vec::new(),
             }),
             shmem: ShmemConf::new().size(16384).os_id(topic).open().expect("cannot open shared memory"),
+            limits: DecodeLimits::default(),
+            max_message_bytes: AtomicUsize::new(DecodeLimits::default().max_alloc),
+            dropped: AtomicUsize::new(0),
         });
 
         // spawn participant receiver
@@ -79,6 +100,12 @@ impl Subscriber {
         subscriber
     }
 
+    /// Set the largest message to reassemble, in bytes. Messages already
+    /// being reassembled keep their buffer.
+    pub fn set_max_message_bytes(&self,max_message_bytes: usize) {
+        self.max_message_bytes.store(max_message_bytes,Ordering::Relaxed);
+    }
+
     pub async fn run_participant_connection(self: &Arc<Subscriber>,pubsub_port: u16) {
 
         loop {
@@ -98,7 +125,7 @@ impl Subscriber {
                     if length == 0 {
                         break;
                     }
-                    if let Some((_,message)) = ParticipantToSubscriber::decode(&recv_buffer) {
+                    if let Ok((_,message)) = ParticipantToSubscriber::decode(&recv_buffer) {
                         match message {
                             ParticipantToSubscriber::Init => { },
                             ParticipantToSubscriber::InitFailed(reason) => {
@@ -139,123 +166,152 @@ impl Subscriber {
         let mut measure_total_mbps = 0usize;
         let mut measure_total_waste = 0usize;
 
+        // messages that are too large are reported once, not for every chunk
+        let mut dropped_id: Option<MessageId> = None;
+
+        // chunks are decoded in place, so the data buffer is reused between datagrams
+        let mut chunk = Chunk::new();
+
         loop {
 
             // receive heartbeat or chunk
-            let (_,address) = self.socket.recv_from(&mut buffer).await.expect("error receiving");
+            let (length,address) = self.socket.recv_from(&mut buffer).await.expect("error receiving");
 
-            if let Some((_,pts)) = PublisherToSubscriber::decode(&buffer) {
+            match chunk.receive(&buffer[..length],&self.limits) {
 
-                match pts {
+                // heartbeat, respond with Ack
+                Ok(Received::Heartbeat(id)) => {
 
-                    // heartbeat, respond with Ack
-                    PublisherToSubscriber::Heartbeat(id) => {
+                    let state = self.state.lock().await;
 
-                        let state = self.state.lock().await;
+                    let mut send_buffer = Vec::<u8>::with_capacity(<SubscriberToPublisher as Codec>::MAX_SIZE.unwrap_or(0));
 
-                        let mut send_buffer = Vec::<u8>::new();
+                    // only respond if this is for the current message
+                    if id == state.id {
+           
+                        //println!("receive heartbeat");
 
-                        // only respond if this is for the current message
-                        if id == state.id {
-               
-                            //println!("receive heartbeat");
-
-                            if let Some(last) = last_missing {
-                                if last > first_missing {
-                                    //println!("send nack {}-{}",first_missing,last);
-                                    SubscriberToPublisher::NAck(id,first_missing,last).encode(&mut send_buffer);
-                                }
-                                else {
-                                    //println!("send ack {}",first_missing);
-                                    SubscriberToPublisher::Ack(id,first_missing).encode(&mut send_buffer);
-                                }
+                        if let Some(last) = last_missing {
+                            if last > first_missing {
+                                //println!("send nack {}-{}",first_missing,last);
+                                SubscriberToPublisher::NAck(id,first_missing,last).encode(&mut send_buffer);
                             }
                             else {
                                 //println!("send ack {}",first_missing);
                                 SubscriberToPublisher::Ack(id,first_missing).encode(&mut send_buffer);
                             }
-                            self.socket.send_to(&mut send_buffer,address).await.expect("error sending retransmit request");
                         }
-                    },
+                        else {
+                            //println!("send ack {}",first_missing);
+                            SubscriberToPublisher::Ack(id,first_missing).encode(&mut send_buffer);
+                        }
+                        self.socket.send_to(&mut send_buffer,address).await.expect("error sending retransmit request");
+                    }
+                },
 
-                    // chunk
-                    PublisherToSubscriber::Chunk(chunk) => {
+                // chunk
+                Ok(Received::Chunk) => {
 
-                        let mut state = self.state.lock().await;
+                    // drop chunks that don't fit the message they claim to be part of, before allocating anything for it
+                    let end = (chunk.index as u64) * (chunk.chunk_size as u64) + (chunk.data.len() as u64);
+                    if (chunk.total as usize > self.limits.max_len) || (chunk.index >= chunk.total) || (end > chunk.total_bytes) {
+                        continue;
+                    }
 
-                        // if this is a new chunk, reset state
-                        if chunk.id != state.id {
+                    // drop messages that are too large to reassemble
+                    let max_message_bytes = self.max_message_bytes.load(Ordering::Relaxed);
+                    if chunk.total_bytes > max_message_bytes as u64 {
+                        if dropped_id != Some(chunk.id) {
+                            dropped_id = Some(chunk.id);
+                            self.dropped.fetch_add(1,Ordering::Relaxed);
+                            println!("subscriber {:016X} dropped message {:016X} of {} bytes, more than {}",self.id,chunk.id,chunk.total_bytes,max_message_bytes);
+                        }
+                        continue;
+                    }
 
-                            start_time = time::Instant::now();
+                    let mut state = self.state.lock().await;
 
-                            state.id = chunk.id;
-                            state.buffer = vec![0; chunk.total_bytes as usize];
-                            state.received = vec![false; chunk.total as usize];
+                    // if this is a new chunk, reset state
+                    if chunk.id != state.id {
 
-                            chunks_total = 0;
-                            chunks_ignored = 0;
-                        }
+                        start_time = time::Instant::now();
 
-                        chunks_total += 1;
-                
-                        // if we don't already have this chunk
-                        if !state.received[chunk.index as usize] {
-
-                            //println!("receive {}",chunk.index);
-
-                            // copy data into final message buffer
-                            let start = (chunk.index * chunk.chunk_size) as usize;
-                            let end = start + chunk.data.len();
-                            state.buffer[start..end].copy_from_slice(&chunk.data);
-                
-                            // mark the chunk as received
-                            state.received[chunk.index as usize] = true;
-
-                            // find first missing chunk
-                            first_missing = chunk.total;
-                            for i in 0..state.received.len() {
-                                if !state.received[i] {
-                                    first_missing = i as u32;
-                                    break;
-                                }
-                            }
+                        state.id = chunk.id;
+                        state.buffer.clear();
+                        state.buffer.resize(chunk.total_bytes as usize,0);
+                        state.received.clear();
+                        state.received.resize(chunk.total as usize,false);
 
-                            if first_missing < chunk.total {
+                        chunks_total = 0;
+                        chunks_ignored = 0;
+                    }
 
-                                // find last missing chunk of this range
-                                last_missing = None;
-                                for i in first_missing..state.received.len() as u32 {
-                                    if state.received[i as usize] {
-                                        last_missing = Some(i);
-                                        break;
-                                    }
-                                }
-                            }
-                            else {
-                                let end_time = time::Instant::now();
-                                let mbps = chunk.total_bytes / ((end_time - start_time).as_micros() as u64);
-                                let waste = (chunks_ignored * 100) / chunks_total;
+                    // drop chunks that disagree with the earlier chunks of this message
+                    if (state.buffer.len() as u64 != chunk.total_bytes) || (state.received.len() != chunk.total as usize) {
+                        continue;
+                    }
 
-                                measure_total += 1;
-                                measure_total_mbps += mbps as usize;
-                                measure_total_waste += waste;
+                    chunks_total += 1;
+            
+                    // if we don't already have this chunk
+                    if !state.received[chunk.index as usize] {
+
+                        //println!("receive {}",chunk.index);
+
+                        // copy data into final message buffer
+                        let start = (chunk.index * chunk.chunk_size) as usize;
+                        let end = start + chunk.data.len();
+                        state.buffer[start..end].copy_from_slice(&chunk.data);
+            
+                        // mark the chunk as received
+                        state.received[chunk.index as usize] = true;
+
+                        // find first missing chunk
+                        first_missing = chunk.total;
+                        for i in 0..state.received.len() {
+                            if !state.received[i] {
+                                first_missing = i as u32;
+                                break;
+                            }
+                        }
 
-                                let avg_mbps = measure_total_mbps / measure_total;
-                                let avg_waste = measure_total_waste / measure_total;
-                                println!("throughput {:3} Mbps, waste {:2}%",avg_mbps,avg_waste);
+                        if first_missing < chunk.total {
 
-                                on_data(&state.buffer);
+                            // find last missing chunk of this range
+                            last_missing = None;
+                            for i in first_missing..state.received.len() as u32 {
+                                if state.received[i as usize] {
+                                    last_missing = Some(i);
+                                    break;
+                                }
                             }
                         }
                         else {
-                            chunks_ignored += 1;
-                            //println!("ignore {}",chunk.index);
+                            let end_time = time::Instant::now();
+                            let mbps = chunk.total_bytes / ((end_time - start_time).as_micros() as u64);
+                            let waste = (chunks_ignored * 100) / chunks_total;
+
+                            measure_total += 1;
+                            measure_total_mbps += mbps as usize;
+                            measure_total_waste += waste;
+
+                            let avg_mbps = measure_total_mbps / measure_total;
+                            let avg_waste = measure_total_waste / measure_total;
+                            println!("throughput {:3} Mbps, waste {:2}%",avg_mbps,avg_waste);
+
+                            on_data(&state.buffer);
                         }
-                    },
-                }
-            }
-            else {
-                //println!("message error");
+                    }
+                    else {
+                        chunks_ignored += 1;
+                        //println!("ignore {}",chunk.index);
+                    }
+                },
+
+                // anything else
+                Err(_) => {
+                    //println!("message error");
+                },
             }
         }
     }
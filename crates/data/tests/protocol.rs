@@ -0,0 +1,57 @@
+// Echidna - Data - tests
+
+use {
+    data::*,
+    codec::{
+        Codec,
+        Checked,
+        DecodeLimits,
+        Error,
+    },
+};
+
+fn chunk(index: u32,data: Vec<u8>) -> Vec<u8> {
+    PublisherToSubscriber::Chunk(Chunk {
+        ts: 1,
+        id: MessageId(7),
+        total_bytes: 10000,
+        chunk_size: 1000,
+        total: 10,
+        index: index,
+        data: Checked(data),
+    }).encode_to_vec()
+}
+
+#[test]
+fn test_receive() {
+    let limits = DecodeLimits::default();
+    let heartbeat = PublisherToSubscriber::Heartbeat(MessageId(7)).encode_to_vec();
+    let mut target = Chunk::new();
+    assert!(matches!(target.receive(&chunk(0,vec![0; 1000]),&limits),Ok(Received::Chunk)));
+    let (ptr,capacity) = (target.data.as_ptr(),target.data.capacity());
+
+    // heartbeats in between leave the data buffer alone
+    for index in 1..10 {
+        assert!(matches!(target.receive(&heartbeat,&limits),Ok(Received::Heartbeat(MessageId(7)))));
+        assert!(matches!(target.receive(&chunk(index,vec![index as u8; 1000]),&limits),Ok(Received::Chunk)));
+        assert_eq!(target.index,index);
+        assert_eq!(target.data.0,vec![index as u8; 1000]);
+        assert_eq!((target.data.as_ptr(),target.data.capacity()),(ptr,capacity));
+    }
+    assert!(matches!(target.receive(&[2,0,0,0],&limits),Err(Error::InvalidTag(2))));
+    assert!(matches!(target.receive(&heartbeat[..6],&limits),Err(Error::Truncated)));
+}
+
+#[test]
+fn test_tags() {
+    // `Chunk::receive` matches on these
+    let limits = DecodeLimits::default();
+    let heartbeat = PublisherToSubscriber::Heartbeat(MessageId(7)).encode_to_vec();
+    let chunk = chunk(3,vec![3; 1000]);
+    assert_eq!(&heartbeat[..4],&0u32.encode_to_vec()[..]);
+    assert_eq!(&chunk[..4],&1u32.encode_to_vec()[..]);
+    let mut target = Chunk::new();
+    assert!(matches!(target.receive(&heartbeat,&limits),Ok(Received::Heartbeat(MessageId(7)))));
+    assert!(matches!(target.receive(&chunk,&limits),Ok(Received::Chunk)));
+    assert!(matches!(PublisherToSubscriber::decode(&chunk),Ok((_,PublisherToSubscriber::Chunk(Chunk { index: 3, .. })))));
+}